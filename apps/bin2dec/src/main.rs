@@ -1,41 +1,349 @@
-use clap::Parser;
+use clap::{ArgAction, Parser, ValueEnum};
+use log::{LevelFilter, debug, info};
+
+#[derive(Clone, Copy, Debug, PartialEq, ValueEnum)]
+enum Base {
+    Bin,
+    Oct,
+    Dec,
+    Hex,
+    /// A single character, read or written as its Unicode codepoint.
+    Char,
+}
+
+impl Base {
+    fn label(self) -> &'static str {
+        match self {
+            Base::Bin => "Binary",
+            Base::Oct => "Octal",
+            Base::Dec => "Decimal",
+            Base::Hex => "Hexadecimal",
+            Base::Char => "Character",
+        }
+    }
+
+    /// The radix of positional bases; `None` for `Char`.
+    fn radix(self) -> Option<u32> {
+        match self {
+            Base::Bin => Some(2),
+            Base::Oct => Some(8),
+            Base::Dec => Some(10),
+            Base::Hex => Some(16),
+            Base::Char => None,
+        }
+    }
+
+    fn parse(self, input: &str) -> Result<u32, String> {
+        let radix = match self {
+            Base::Bin => return bin2dec(input),
+            Base::Char => {
+                let mut chars = input.chars();
+                return match (chars.next(), chars.next()) {
+                    (Some(c), None) => Ok(c as u32),
+                    _ => Err("input must be exactly one character".into()),
+                };
+            }
+            _ => self.radix().unwrap_or(10),
+        };
+        let digits = match self {
+            Base::Hex => input
+                .strip_prefix("0x")
+                .or_else(|| input.strip_prefix("0X"))
+                .unwrap_or(input),
+            _ => input,
+        };
+        if let Some(err) = digit_error(input, input.len() - digits.len(), self) {
+            return Err(err);
+        }
+        u32::from_str_radix(digits, radix).map_err(|e| {
+            format!(
+                "'{}' is not a valid {} number: {}",
+                input,
+                self.label().to_lowercase(),
+                e
+            )
+        })
+    }
+
+    fn format(self, value: u32) -> Result<String, String> {
+        Ok(match self {
+            Base::Bin => format!("{:b}", value),
+            Base::Oct => format!("{:o}", value),
+            Base::Dec => value.to_string(),
+            Base::Hex => format!("{:X}", value),
+            Base::Char => char::from_u32(value)
+                .ok_or_else(|| format!("U+{:04X} is not a valid Unicode codepoint", value))?
+                .to_string(),
+        })
+    }
+}
 
 #[derive(Parser, Debug)]
-#[command(name="Bin2Dec", author="Junkai Ji", about="Convert binary numbers (up to 8 digits) to decimal format.", long_about = None)]
+#[command(name="Bin2Dec", author="Junkai Ji", about="Convert a number between binary, octal, decimal, hex and Unicode characters (binary to decimal by default).", long_about = None)]
 struct Args {
     #[arg(
-        value_name = "BINARY",
-        help = "Binary number to convert (max 8 digits)"
+        value_name = "VALUE",
+        help = "Value to convert, written in the --from base (binary input is limited to 8 digits)",
+        required_unless_present = "sequence"
+    )]
+    value: Option<String>,
+
+    #[arg(
+        long,
+        num_args = 1..=2,
+        value_names = ["START", "END"],
+        conflicts_with = "value",
+        help = "Print a binary/decimal table from START to END"
     )]
-    binary: String,
+    sequence: Option<Vec<u32>>,
+
+    #[arg(
+        short = 'n',
+        long,
+        value_name = "COUNT",
+        requires = "sequence",
+        conflicts_with = "value",
+        help = "Number of values to print when END is omitted"
+    )]
+    count: Option<u32>,
+
+    #[arg(
+        long,
+        value_name = "WIDTH",
+        requires = "sequence",
+        conflicts_with = "value",
+        help = "Zero-pad the binary column of --sequence to WIDTH bits [default: 8]"
+    )]
+    bits: Option<usize>,
+
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = Base::Bin,
+        conflicts_with = "sequence",
+        help = "Base of the input value"
+    )]
+    from: Base,
+
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = Base::Dec,
+        conflicts_with = "sequence",
+        help = "Base to print the value in"
+    )]
+    to: Base,
+
+    #[arg(
+        long,
+        conflicts_with = "sequence",
+        help = "Show the place-value breakdown behind the conversion"
+    )]
+    explain: bool,
+
+    #[arg(
+        short,
+        long,
+        action = ArgAction::Count,
+        help = "Log what the conversion does to stderr (-vv for more detail)"
+    )]
+    verbose: u8,
 }
 
 fn main() {
     let args = Args::parse();
+    init_logging(args.verbose);
+
+    if let Some(bounds) = &args.sequence {
+        match sequence_table(bounds, args.count, args.bits.unwrap_or(8)) {
+            Ok(table) => print!("{}", table),
+            Err(e) => eprintln!("Error: {}", e),
+        }
+        return;
+    }
 
-    match bin2dec(&args.binary) {
-        Ok(value) => println!("Decimal output: {}", value),
+    let input = args.value.as_deref().unwrap_or_default();
+    info!(
+        "converting {:?} from {} to {}",
+        input,
+        args.from.label(),
+        args.to.label()
+    );
+    let converted = args
+        .from
+        .parse(input)
+        .and_then(|value| Ok((value, args.to.format(value)?)));
+    match converted {
+        Ok((value, output)) => {
+            debug!("parsed value {} (U+{:04X})", value, value);
+            if args.explain {
+                let bases = if args.from == args.to {
+                    vec![args.from]
+                } else {
+                    vec![args.from, args.to]
+                };
+                for line in bases.into_iter().filter_map(|b| explain(value, b)) {
+                    println!("{}", line);
+                }
+            }
+            println!("{} output: {}", args.to.label(), output);
+        }
         Err(e) => eprintln!("Error: {}", e),
     }
 }
 
-fn bin2dec(input: &str) -> Result<u32, String> {
-    if input.len() > 8 {
-        return Err("input must be no more than 8 digits".into());
+/// Sends log records to stderr: warnings only by default, then info and
+/// debug with each `-v`. `RUST_LOG` overrides the level.
+fn init_logging(verbose: u8) {
+    let level = match verbose {
+        0 => LevelFilter::Warn,
+        1 => LevelFilter::Info,
+        2 => LevelFilter::Debug,
+        _ => LevelFilter::Trace,
+    };
+    env_logger::Builder::new()
+        .filter_level(level)
+        .parse_default_env()
+        .format_timestamp(None)
+        .init();
+}
+
+fn sequence_table(bounds: &[u32], count: Option<u32>, bits: usize) -> Result<String, String> {
+    let start = bounds[0];
+    let end = match (bounds.get(1), count) {
+        (Some(end), None) => *end,
+        (None, Some(0)) => return Err("count must be at least 1".into()),
+        (None, Some(n)) => start
+            .checked_add(n - 1)
+            .ok_or("sequence runs past the largest supported value")?,
+        (None, None) => start.saturating_add(15),
+        (Some(_), Some(_)) => return Err("give either END or --count, not both".into()),
+    };
+    if start > end {
+        return Err(format!(
+            "START ({}) must not be greater than END ({})",
+            start, end
+        ));
     }
+    info!("printing values {} to {}", start, end);
+
+    let bin_width = bits.max((u32::BITS - end.leading_zeros()).max(1) as usize);
+    debug!("binary column is {} digits wide", bin_width);
+    let bin_col = bin_width.max("Binary".len());
+    let dec_col = end.to_string().len().max("Decimal".len());
 
-    if !input.chars().all(|c| c == '0' || c == '1') {
-        return Err("input must only contain 0 and 1.".into());
+    let mut out = format!(
+        "{:>bin_col$} | {:>dec_col$}\n{}-+-{}\n",
+        "Binary",
+        "Decimal",
+        "-".repeat(bin_col),
+        "-".repeat(dec_col),
+    );
+    for value in start..=end {
+        let binary = format!("{:0bin_width$b}", value);
+        out.push_str(&format!("{:>bin_col$} | {:>dec_col$}\n", binary, value));
     }
+    Ok(out)
+}
+
+/// Writes `value` out in `base` with each digit's contribution, e.g.
+/// `1011 = 1×8 + 0×4 + 1×2 + 1×1 = 11`. Decimal needs no explaining.
+fn explain(value: u32, base: Base) -> Option<String> {
+    let radix = match base {
+        Base::Dec => return None,
+        Base::Char => {
+            let c = char::from_u32(value)?;
+            return Some(format!("'{}' = U+{:04X} = {}", c, value, value));
+        }
+        _ => base.radix()?,
+    };
+    let digits = base.format(value).ok()?;
+    let terms: Vec<String> = place_values(&digits, radix)
+        .into_iter()
+        .map(|(digit, place)| format!("{}×{}", digit, place))
+        .collect();
+    Some(format!("{} = {} = {}", digits, terms.join(" + "), value))
+}
 
-    input
+/// Each digit of `digits` paired with its place value, most significant first.
+/// The digits must already be valid in `radix`.
+fn place_values(digits: &str, radix: u32) -> Vec<(u32, u32)> {
+    let len = digits.chars().count() as u32;
+    digits
         .chars()
-        .rev()
         .enumerate()
         .map(|(i, c)| {
-            let digit = c.to_digit(10).unwrap();
-            Ok(digit * 2u32.pow(i as u32))
+            let digit = c.to_digit(radix).unwrap();
+            (digit, radix.pow(len - 1 - i as u32))
         })
-        .try_fold(0u32, |acc, x| x.map(|val| acc + val))
+        .collect()
 }
 
+/// Points a caret at the first character after `skip` bytes of `input` that
+/// isn't a digit in `base`, and suggests a base that would accept the input.
+fn digit_error(input: &str, skip: usize, base: Base) -> Option<String> {
+    let radix = base.radix()?;
+    let (index, bad) = input
+        .char_indices()
+        .skip_while(|(i, _)| *i < skip)
+        .find(|(_, c)| !c.is_digit(radix))?;
+    let column = input[..index].chars().count();
+
+    let mut message = format!(
+        "'{}' at position {} is not a digit in {}\n  {}\n  {}^",
+        bad,
+        column + 1,
+        base.label().to_lowercase(),
+        input,
+        " ".repeat(column)
+    );
+    let suggestion = [Base::Dec, Base::Hex].into_iter().find(|b| {
+        *b != base
+            && b.radix()
+                .is_some_and(|r| input.chars().all(|c| c.is_digit(r)))
+    });
+    if let Some(other) = suggestion {
+        message.push_str(&format!(
+            "\nhint: it reads as the {} number {}; try --from {}",
+            other.label().to_lowercase(),
+            input,
+            format!("{:?}", other).to_lowercase()
+        ));
+    }
+    Some(message)
+}
+
+fn bin2dec(input: &str) -> Result<u32, String> {
+    debug!("reading {} binary digits", input.len());
+    if input.len() > 8 {
+        return Err("input must be no more than 8 digits".into());
+    }
+
+    if let Some(err) = digit_error(input, 0, Base::Bin) {
+        return Err(err);
+    }
+
+    Ok(place_values(input, 2)
+        .into_iter()
+        .map(|(digit, place)| digit * place)
+        .sum())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Args, clap::Error> {
+        Args::try_parse_from(std::iter::once("bin2dec").chain(args.iter().copied()))
+    }
+
+    #[test]
+    fn sequence_options_need_a_sequence() {
+        assert!(parse(&["101", "--bits", "4"]).is_err());
+        assert!(parse(&["101", "-n", "3"]).is_err());
+        assert!(parse(&["--bits", "4"]).is_err());
+        let args = parse(&["--sequence", "0", "-n", "2", "--bits", "4"]).unwrap();
+        assert_eq!(args.bits, Some(4));
+        assert_eq!(parse(&["101"]).unwrap().value.as_deref(), Some("101"));
+    }
+}
@@ -2,25 +2,55 @@
 // -----------------------------------------------------------------------------
 // Usage examples:
 //   csvjson to-json -i data.csv -o data.json --pretty
+//   csvjson to-json -i big.csv --head 100
 //   csvjson to-csv -i data.json -o data.csv
 //   cat data.csv | csvjson to-json > out.json
 //   cat data.json | csvjson to-csv > out.csv
+//   csvjson to-json                 # paste CSV, then a blank line
+//   csvjson view -i data.csv --max-col-width 20 --color
+//   csvjson to-json -i data.tsv --delimiter tab
+//   csvjson to-json -i data.csv -o data.json --dry-run
+//   csvjson schema -i data.csv -o data.schema.json
+//   csvjson undo data.json          # put back what the last write replaced
 //
 // Run `csvjson to-json --help` or `csvjson to-csv --help` for CLI flags
 // -----------------------------------------------------------------------------
 
-use std::collections::BTreeSet;
-use std::io::{self, Read};
-use std::{fs, path::PathBuf};
+use std::collections::{BTreeSet, VecDeque};
+use std::fs;
+use std::io::{self, IsTerminal, Read, Write};
+use std::path::{Path, PathBuf};
 
-use clap::{Parser, Subcommand};
-use serde_json::Value;
+use clap::{ArgAction, Parser, Subcommand};
+use colored::*;
+use log::{LevelFilter, debug, info};
+use serde_json::{Value, json};
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
 
 #[derive(Parser)]
 #[command(name = "csv2json", author = "Junkai Ji", version, about= "Convert CSV to JSON or JSON to CSV.", long_about =None)]
 struct Cli {
     #[command(subcommand)]
     command: Commands,
+
+    /// Report what would be written to the output file without writing it
+    #[arg(long, global = true)]
+    dry_run: bool,
+
+    /// Overwrite an existing output file without asking
+    #[arg(short, long, global = true)]
+    force: bool,
+
+    /// Log what's read, parsed and written to stderr (-vv for more detail)
+    #[arg(short, long, action = ArgAction::Count, global = true)]
+    verbose: u8,
+}
+
+/// How `write_output` treats the output file.
+#[derive(Clone, Copy)]
+struct WriteMode {
+    dry_run: bool,
+    force: bool,
 }
 
 #[derive(Subcommand)]
@@ -35,6 +65,23 @@ enum Commands {
 
         #[arg(short, long)]
         pretty: bool,
+
+        /// Convert only the first N data rows
+        #[arg(
+            long,
+            value_name = "N",
+            visible_alias = "limit",
+            conflicts_with = "tail"
+        )]
+        head: Option<usize>,
+
+        /// Convert only the last N data rows
+        #[arg(long, value_name = "N")]
+        tail: Option<usize>,
+
+        /// Field separator, a single character or `tab` (sniffed from the input when omitted)
+        #[arg(short, long, value_name = "CHAR", value_parser = parse_delimiter)]
+        delimiter: Option<char>,
     },
 
     /// Convert JSON to CSV
@@ -45,43 +92,235 @@ enum Commands {
         #[arg(short, long)]
         output: Option<PathBuf>,
     },
+
+    /// Print CSV as an aligned table
+    View {
+        #[arg(short, long)]
+        input: Option<PathBuf>,
+
+        /// Truncate cells wider than N characters
+        #[arg(long, value_name = "N")]
+        max_col_width: Option<usize>,
+
+        /// Color the header row
+        #[arg(short, long)]
+        color: bool,
+
+        /// Field separator, a single character or `tab` (sniffed from the input when omitted)
+        #[arg(short, long, value_name = "CHAR", value_parser = parse_delimiter)]
+        delimiter: Option<char>,
+    },
+
+    /// Infer a JSON Schema for the JSON that to-json would make from a CSV
+    Schema {
+        #[arg(short, long)]
+        input: Option<PathBuf>,
+
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+
+        /// Field separator, a single character or `tab` (sniffed from the input when omitted)
+        #[arg(short, long, value_name = "CHAR", value_parser = parse_delimiter)]
+        delimiter: Option<char>,
+    },
+
+    /// Restore an output file from the backup kept when it was last overwritten
+    Undo {
+        /// The file that was overwritten
+        output: PathBuf,
+    },
 }
 
 fn main() {
     let cli = Cli::parse();
+    init_logging(cli.verbose);
+    let mode = WriteMode {
+        dry_run: cli.dry_run,
+        force: cli.force,
+    };
 
     match cli.command {
         Commands::ToJson {
             input,
             output,
             pretty,
+            head,
+            tail,
+            delimiter,
         } => match read_input(input.as_ref()) {
-            Ok(csv_text) => match csv_to_json(&csv_text, pretty) {
-                Ok(json_text) => write_output(output.as_ref(), &json_text),
-                Err(e) => exit_with_error(&e),
-            },
+            Ok(csv_text) => {
+                let delimiter = delimiter.unwrap_or_else(|| sniff_delimiter(&csv_text));
+                match csv_to_json(
+                    &csv_text,
+                    pretty,
+                    RowLimit::from_args(head, tail),
+                    delimiter,
+                ) {
+                    Ok(json_text) => write_output(output.as_ref(), &json_text, mode),
+                    Err(e) => exit_with_error(&e),
+                }
+            }
             Err(e) => exit_with_error(&e),
         },
         Commands::ToCsv { input, output } => match read_input(input.as_ref()) {
             Ok(json_text) => match json_to_csv(&json_text) {
-                Ok(csv_text) => write_output(output.as_ref(), &csv_text),
+                Ok(csv_text) => write_output(output.as_ref(), &csv_text, mode),
                 Err(e) => exit_with_error(&e),
             },
             Err(e) => exit_with_error(&e),
         },
+        Commands::View {
+            input,
+            max_col_width,
+            color,
+            delimiter,
+        } => match read_input(input.as_ref()) {
+            Ok(csv_text) => {
+                let delimiter = delimiter.unwrap_or_else(|| sniff_delimiter(&csv_text));
+                match csv_to_table(&csv_text, max_col_width, color, delimiter) {
+                    Ok(table) => print!("{}", table),
+                    Err(e) => exit_with_error(&e),
+                }
+            }
+            Err(e) => exit_with_error(&e),
+        },
+        Commands::Schema {
+            input,
+            output,
+            delimiter,
+        } => match read_input(input.as_ref()) {
+            Ok(csv_text) => {
+                let delimiter = delimiter.unwrap_or_else(|| sniff_delimiter(&csv_text));
+                match csv_to_schema(&csv_text, delimiter) {
+                    Ok(schema) => write_output(output.as_ref(), &schema, mode),
+                    Err(e) => exit_with_error(&e),
+                }
+            }
+            Err(e) => exit_with_error(&e),
+        },
+        Commands::Undo { output } => restore_backup(&output, mode),
+    }
+}
+
+#[derive(Clone, Copy)]
+enum RowLimit {
+    All,
+    Head(usize),
+    Tail(usize),
+}
+
+impl RowLimit {
+    fn from_args(head: Option<usize>, tail: Option<usize>) -> Self {
+        match (head, tail) {
+            (Some(n), _) => RowLimit::Head(n),
+            (None, Some(n)) => RowLimit::Tail(n),
+            (None, None) => RowLimit::All,
+        }
+    }
+}
+
+fn parse_delimiter(s: &str) -> Result<char, String> {
+    if s.eq_ignore_ascii_case("tab") {
+        return Ok('\t');
+    }
+    let mut chars = s.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) if c != '"' && c != '\n' => Ok(c),
+        _ => Err("expected a single character other than a quote, or 'tab'".into()),
     }
 }
 
+/// Separators tried when sniffing, in order of preference.
+const DELIMITERS: [char; 4] = [',', '\t', ';', '|'];
+/// Lines sampled from the top of the input when sniffing.
+const SNIFF_LINES: usize = 10;
+
+/// Picks the separator that splits the first few lines into the same number
+/// of columns (more than one). Input with none of the candidates is a single
+/// column and gets comma; anything still unclear gets comma with a warning.
+fn sniff_delimiter(csv_input: &str) -> char {
+    let sample: Vec<&str> = csv_input
+        .lines()
+        .filter(|l| !l.trim().is_empty())
+        .take(SNIFF_LINES)
+        .collect();
+    if !sample.iter().any(|l| l.contains(DELIMITERS)) {
+        return ',';
+    }
+
+    let consistent: Vec<(char, usize)> = DELIMITERS
+        .into_iter()
+        .filter_map(|d| {
+            let columns = parse_csv_line(sample[0], d).len();
+            debug!("{:?} splits the header into {} columns", d, columns);
+            let same = sample
+                .iter()
+                .all(|line| parse_csv_line(line, d).len() == columns);
+            (same && columns > 1).then_some((d, columns))
+        })
+        .collect();
+    let most = consistent.iter().map(|(_, columns)| *columns).max();
+    let best: Vec<char> = consistent
+        .iter()
+        .filter(|(_, columns)| Some(*columns) == most)
+        .map(|(d, _)| *d)
+        .collect();
+    match best[..] {
+        [d] => {
+            info!("sniffed delimiter {:?}", d);
+            d
+        }
+        _ => {
+            eprintln!(
+                "{} couldn't tell which delimiter the input uses; assuming comma (set one with --delimiter)",
+                "warning:".yellow()
+            );
+            ','
+        }
+    }
+}
+
+/// Sends log records to stderr: warnings only by default, then info and
+/// debug with each `-v`. `RUST_LOG` overrides the level.
+fn init_logging(verbose: u8) {
+    let level = match verbose {
+        0 => LevelFilter::Warn,
+        1 => LevelFilter::Info,
+        2 => LevelFilter::Debug,
+        _ => LevelFilter::Trace,
+    };
+    env_logger::Builder::new()
+        .filter_level(level)
+        .parse_default_env()
+        .format_timestamp(None)
+        .init();
+}
+
 fn read_input(path: Option<&PathBuf>) -> Result<String, String> {
     match path {
         Some(p) if p.as_os_str() != "-" => {
-            fs::read_to_string(p).map_err(|e| format!("failed to read: '{}': {}", p.display(), e))
+            info!("reading {}", p.display());
+            let text = fs::read_to_string(p)
+                .map_err(|e| format!("failed to read: '{}': {}", p.display(), e))?;
+            debug!("read {} bytes", text.len());
+            Ok(text)
+        }
+        _ if io::stdin().is_terminal() => {
+            info!("reading pasted input");
+            let buf = read_pasted()?;
+            if buf.trim().is_empty() {
+                return Err("input is empty".into());
+            }
+
+            Ok(buf)
         }
         _ => {
+            info!("reading STDIN");
             let mut buf = String::new();
             io::stdin()
                 .read_to_string(&mut buf)
                 .map_err(|e| format!("failed to read from STDIN: {}", e))?;
+            debug!("read {} bytes", buf.len());
             if buf.trim().is_empty() {
                 return Err("input is empty".into());
             }
@@ -91,11 +330,60 @@ fn read_input(path: Option<&PathBuf>) -> Result<String, String> {
     }
 }
 
-fn write_output(path: Option<&PathBuf>, data: &str) {
+/// Reads input typed or pasted at the terminal, which ends at a blank line
+/// as well as at end of file, so nobody is left waiting on a silent prompt.
+fn read_pasted() -> Result<String, String> {
+    let eof = if cfg!(windows) {
+        "Ctrl-Z, Enter"
+    } else {
+        "Ctrl-D"
+    };
+    eprintln!(
+        "{}",
+        format!("Paste your input, then press Enter on a blank line (or {eof}) to finish:")
+            .dimmed()
+    );
+    let mut buf = String::new();
+    for line in io::stdin().lines() {
+        let line = line.map_err(|e| format!("failed to read from STDIN: {}", e))?;
+        if line.trim().is_empty() {
+            break;
+        }
+        buf.push_str(&line);
+        buf.push('\n');
+    }
+    Ok(buf)
+}
+
+fn write_output(path: Option<&PathBuf>, data: &str, mode: WriteMode) {
     if let Some(p) = path {
         if p.as_os_str() == "-" {
             println!("{data}");
+        } else if mode.dry_run {
+            info!("dry run; leaving {} untouched", p.display());
+            println!(
+                "Would write {} bytes to {}{}",
+                data.len(),
+                p.display(),
+                if p.exists() { " (overwriting it)" } else { "" }
+            );
         } else {
+            if p.exists() {
+                debug!("{} already exists", p.display());
+            }
+            if p.exists() && !mode.force && !confirm_overwrite(p) {
+                exit_with_error(&format!(
+                    "'{}' already exists; pass --force to overwrite it",
+                    p.display()
+                ));
+            }
+            if p.exists() {
+                let bak = backup_path(p);
+                debug!("backing up {} to {}", p.display(), bak.display());
+                if let Err(e) = fs::copy(p, &bak) {
+                    exit_with_error(&format!("failed to back up '{}': {}", p.display(), e));
+                }
+            }
             match fs::write(p, data) {
                 Ok(_) => println!("Wrote {} bytes to {}", data.len(), p.display()),
                 Err(e) => exit_with_error(&format!("failed to write '{}': {}", p.display(), e)),
@@ -106,29 +394,91 @@ fn write_output(path: Option<&PathBuf>, data: &str) {
     }
 }
 
+/// Where `write_output` keeps the contents of a file it overwrites:
+/// `data.json` is backed up to `data.json.bak`.
+fn backup_path(path: &Path) -> PathBuf {
+    let mut bak = path.as_os_str().to_owned();
+    bak.push(".bak");
+    PathBuf::from(bak)
+}
+
+/// Moves the backup of `path` back over it, undoing the last overwrite.
+fn restore_backup(path: &Path, mode: WriteMode) {
+    let bak = backup_path(path);
+    if !bak.exists() {
+        exit_with_error(&format!("no backup of '{}' to restore", path.display()));
+    }
+    if mode.dry_run {
+        println!("Would restore {} from {}", path.display(), bak.display());
+        return;
+    }
+    match fs::rename(&bak, path) {
+        Ok(_) => println!("Restored {} from {}", path.display(), bak.display()),
+        Err(e) => exit_with_error(&format!("failed to restore '{}': {}", path.display(), e)),
+    }
+}
+
+/// Asks before clobbering `path`. Without a terminal to ask on (input was
+/// piped in, say) the answer is no.
+fn confirm_overwrite(path: &Path) -> bool {
+    if !io::stdin().is_terminal() {
+        return false;
+    }
+    eprint!("'{}' already exists. Overwrite it? [y/N] ", path.display());
+    let _ = io::stderr().flush();
+    let mut answer = String::new();
+    if io::stdin().read_line(&mut answer).is_err() {
+        return false;
+    }
+    matches!(answer.trim(), "y" | "Y" | "yes")
+}
+
 fn exit_with_error(msg: &str) {
     eprintln!("error: {}", msg);
     std::process::exit(1)
 }
 
-fn csv_to_json(csv_input: &str, pretty: bool) -> Result<String, String> {
+fn csv_to_json(
+    csv_input: &str,
+    pretty: bool,
+    limit: RowLimit,
+    delimiter: char,
+) -> Result<String, String> {
     let mut lines = csv_input.lines().peekable();
     if lines.peek().is_none() {
         return Err("CSV input is empty".into());
     }
 
-    let headers = parse_csv_line(lines.next().unwrap());
+    let headers = parse_csv_line(lines.next().unwrap(), delimiter);
+    debug!("headers: {:?}", headers);
 
     if headers.is_empty() {
         return Err("CSV header row is empty".into());
     }
 
-    let mut records = Vec::new();
-    for (idx, line) in lines.enumerate() {
-        if line.trim().is_empty() {
-            continue;
+    let data = lines
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty());
+    let rows: Box<dyn Iterator<Item = (usize, &str)>> = match limit {
+        RowLimit::All => Box::new(data),
+        RowLimit::Head(n) => Box::new(data.take(n)),
+        RowLimit::Tail(n) => {
+            let mut last = VecDeque::new();
+            for row in data {
+                if last.len() == n {
+                    last.pop_front();
+                }
+                if n > 0 {
+                    last.push_back(row);
+                }
+            }
+            Box::new(last.into_iter())
         }
-        let fields = parse_csv_line(line);
+    };
+
+    let mut records = Vec::new();
+    for (idx, line) in rows {
+        let fields = parse_csv_line(line, delimiter);
         if fields.len() != headers.len() {
             return Err(format!(
                 "CSV row {} has {} fields but header has {}",
@@ -144,6 +494,7 @@ fn csv_to_json(csv_input: &str, pretty: bool) -> Result<String, String> {
         }
         records.push(Value::Object(map));
     }
+    info!("parsed {} rows", records.len());
 
     if pretty {
         serde_json::to_string_pretty(&records).map_err(|e| e.to_string())
@@ -152,7 +503,209 @@ fn csv_to_json(csv_input: &str, pretty: bool) -> Result<String, String> {
     }
 }
 
-fn parse_csv_line(line: &str) -> Vec<String> {
+/// The JSON type of a column, as `guess_json_value` reads its cells.
+#[derive(Clone, Copy, PartialEq)]
+enum ColumnType {
+    /// Every cell so far was empty.
+    Unknown,
+    Integer,
+    Number,
+    Boolean,
+    String,
+}
+
+impl ColumnType {
+    fn of(value: &Value) -> Self {
+        match value {
+            Value::Null => ColumnType::Unknown,
+            Value::Bool(_) => ColumnType::Boolean,
+            Value::Number(n) if n.is_f64() => ColumnType::Number,
+            Value::Number(_) => ColumnType::Integer,
+            _ => ColumnType::String,
+        }
+    }
+
+    /// The narrowest type covering both: integers widen to numbers, and any
+    /// other disagreement to strings.
+    fn widen(self, other: Self) -> Self {
+        use ColumnType::*;
+        match (self, other) {
+            (a, b) if a == b => a,
+            (Unknown, t) | (t, Unknown) => t,
+            (Integer, Number) | (Number, Integer) => Number,
+            _ => String,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            ColumnType::Unknown => "null",
+            ColumnType::Integer => "integer",
+            ColumnType::Number => "number",
+            ColumnType::Boolean => "boolean",
+            ColumnType::String => "string",
+        }
+    }
+}
+
+/// A JSON Schema for the array of objects `csv_to_json` makes: each column's
+/// type across every row, with columns that have empty cells nullable and
+/// the rest required.
+fn csv_to_schema(csv_input: &str, delimiter: char) -> Result<String, String> {
+    let mut lines = csv_input
+        .lines()
+        .enumerate()
+        .filter(|(_, l)| !l.trim().is_empty());
+    let headers = match lines.next() {
+        Some((_, line)) => parse_csv_line(line, delimiter),
+        None => return Err("CSV input is empty".into()),
+    };
+    debug!("headers: {:?}", headers);
+
+    let mut types = vec![ColumnType::Unknown; headers.len()];
+    let mut nullable = vec![false; headers.len()];
+    let mut count = 0;
+    for (idx, line) in lines {
+        let fields = parse_csv_line(line, delimiter);
+        if fields.len() != headers.len() {
+            return Err(format!(
+                "CSV row {} has {} fields but header has {}",
+                idx + 1,
+                fields.len(),
+                headers.len(),
+            ));
+        }
+        for (col, field) in fields.iter().enumerate() {
+            let value = guess_json_value(field);
+            nullable[col] |= value.is_null();
+            types[col] = types[col].widen(ColumnType::of(&value));
+        }
+        count += 1;
+    }
+    info!("inferred types from {} rows", count);
+
+    let mut properties = serde_json::Map::new();
+    let mut required = Vec::new();
+    for (col, header) in headers.iter().enumerate() {
+        let ty = match (types[col], nullable[col]) {
+            (ColumnType::Unknown, _) | (_, false) => json!(types[col].name()),
+            (ty, true) => json!([ty.name(), "null"]),
+        };
+        properties.insert(header.clone(), json!({ "type": ty }));
+        if !nullable[col] {
+            required.push(header.clone());
+        }
+    }
+
+    let schema = json!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "type": "array",
+        "items": {
+            "type": "object",
+            "properties": properties,
+            "required": required,
+        },
+    });
+    serde_json::to_string_pretty(&schema).map_err(|e| e.to_string())
+}
+
+fn csv_to_table(
+    csv_input: &str,
+    max_col_width: Option<usize>,
+    color: bool,
+    delimiter: char,
+) -> Result<String, String> {
+    let mut lines = csv_input
+        .lines()
+        .enumerate()
+        .filter(|(_, l)| !l.trim().is_empty());
+    let headers = match lines.next() {
+        Some((_, line)) => parse_csv_line(line, delimiter),
+        None => return Err("CSV input is empty".into()),
+    };
+    debug!("headers: {:?}", headers);
+
+    let truncate = |cell: String| match max_col_width {
+        Some(max) => truncate_cell(cell, max),
+        None => cell,
+    };
+
+    let mut rows = Vec::new();
+    for (idx, line) in lines {
+        let fields = parse_csv_line(line, delimiter);
+        if fields.len() != headers.len() {
+            return Err(format!(
+                "CSV row {} has {} fields but header has {}",
+                idx + 1,
+                fields.len(),
+                headers.len(),
+            ));
+        }
+        rows.push(fields.into_iter().map(truncate).collect::<Vec<_>>());
+    }
+    let headers: Vec<String> = headers.into_iter().map(truncate).collect();
+
+    let mut widths: Vec<usize> = headers.iter().map(|h| h.width()).collect();
+    for row in &rows {
+        for (w, cell) in widths.iter_mut().zip(row) {
+            *w = (*w).max(cell.width());
+        }
+    }
+
+    let border = {
+        let segments: Vec<String> = widths.iter().map(|w| "-".repeat(w + 2)).collect();
+        format!("+{}+\n", segments.join("+"))
+    };
+    let format_row = |cells: &[String], header: bool| {
+        let padded: Vec<String> = cells
+            .iter()
+            .zip(&widths)
+            .map(|(cell, w)| {
+                let text = format!("{}{}", cell, " ".repeat(w - cell.width()));
+                if header && color {
+                    text.bold().cyan().to_string()
+                } else {
+                    text
+                }
+            })
+            .collect();
+        format!("| {} |\n", padded.join(" | "))
+    };
+
+    let mut out = String::new();
+    out.push_str(&border);
+    out.push_str(&format_row(&headers, true));
+    out.push_str(&border);
+    for row in &rows {
+        out.push_str(&format_row(row, false));
+    }
+    if !rows.is_empty() {
+        out.push_str(&border);
+    }
+    Ok(out)
+}
+
+/// Cuts `cell` to at most `max` columns on screen, ending in "…" when
+/// anything was dropped.
+fn truncate_cell(cell: String, max: usize) -> String {
+    if cell.width() <= max {
+        return cell;
+    }
+    let mut cut = String::new();
+    let mut width = 0;
+    for c in cell.chars() {
+        let w = c.width().unwrap_or(0);
+        if width + w + 1 > max {
+            break;
+        }
+        width += w;
+        cut.push(c);
+    }
+    cut.push('…');
+    cut
+}
+
+fn parse_csv_line(line: &str, delimiter: char) -> Vec<String> {
     let mut fields = Vec::<String>::new();
     let mut current = String::new();
     let mut chars = line.chars().peekable();
@@ -172,7 +725,7 @@ fn parse_csv_line(line: &str) -> Vec<String> {
                     in_quotes = true;
                 }
             }
-            ',' if !in_quotes => {
+            c if c == delimiter && !in_quotes => {
                 fields.push(current.clone());
                 current.clear();
             }
@@ -226,6 +779,8 @@ fn json_to_csv(json_input: &str) -> Result<String, String> {
     }
 
     let keys: Vec<String> = keys.into_iter().collect();
+    info!("{} records with {} columns", arr.len(), keys.len());
+    debug!("columns: {:?}", keys);
 
     let mut out = String::new();
     out.push_str(&keys.join(","));
@@ -264,3 +819,96 @@ fn escape_csv_field(s: &str) -> String {
         s.to_string()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn table_rejects_rows_of_the_wrong_length() {
+        let long = csv_to_table("a,b\n1,2\n1,2,3\n", None, false, ',');
+        assert_eq!(long.unwrap_err(), "CSV row 3 has 3 fields but header has 2");
+        let short = csv_to_table("a,b\n\n1\n", None, false, ',');
+        assert_eq!(
+            short.unwrap_err(),
+            "CSV row 3 has 1 fields but header has 2"
+        );
+    }
+
+    #[test]
+    fn schema_errors_name_the_line_past_blank_ones() {
+        let error = csv_to_schema("a,b\n\n1,2\n\n3\n", ',').unwrap_err();
+        assert_eq!(error, "CSV row 5 has 1 fields but header has 2");
+        // The same line as to-json reports.
+        let json = csv_to_json("a,b\n\n1,2\n\n3\n", false, RowLimit::All, ',');
+        assert_eq!(json.unwrap_err(), error);
+    }
+
+    #[test]
+    fn table_lines_up_wide_characters() {
+        let table = csv_to_table("name,city\n張偉,北京\nBob,Paris\n", None, false, ',').unwrap();
+        let widths: Vec<usize> = table.lines().map(UnicodeWidthStr::width).collect();
+        assert!(widths.iter().all(|w| *w == widths[0]), "{}", table);
+        assert!(table.contains("| 張偉 | 北京  |"), "{}", table);
+    }
+
+    #[test]
+    fn table_cuts_cells_by_width() {
+        assert_eq!(truncate_cell("張偉偉偉".into(), 5), "張偉…");
+        assert_eq!(truncate_cell("abcdef".into(), 4), "abc…");
+        assert_eq!(truncate_cell("abcd".into(), 4), "abcd");
+    }
+
+    #[test]
+    fn sniffs_each_delimiter() {
+        assert_eq!(sniff_delimiter("a,b,c\n1,2,3\n"), ',');
+        assert_eq!(sniff_delimiter("a\tb\tc\n1\t2\t3\n"), '\t');
+        assert_eq!(sniff_delimiter("a;b;c\n1;2;3\n"), ';');
+        assert_eq!(sniff_delimiter("a|b|c\n1|2|3\n"), '|');
+    }
+
+    #[test]
+    fn a_tie_falls_back_to_comma() {
+        assert_eq!(sniff_delimiter("a,b;c\n1,2;3\n"), ',');
+        assert_eq!(sniff_delimiter("a;b;c\n1;2;3\n4;5\n"), ',');
+    }
+
+    #[test]
+    fn a_single_column_is_comma() {
+        assert_eq!(sniff_delimiter("name\nAda\nBob\n"), ',');
+        assert_eq!(sniff_delimiter(""), ',');
+    }
+
+    #[test]
+    fn semicolons_inside_comma_cells_stay_comma() {
+        let csv = "id,tags,note\n1,a;b;c,x\n2,d,\"y; z\"\n";
+        assert_eq!(sniff_delimiter(csv), ',');
+        let json = csv_to_json(csv, false, RowLimit::All, sniff_delimiter(csv)).unwrap();
+        assert_eq!(
+            json,
+            r#"[{"id":1,"note":"x","tags":"a;b;c"},{"id":2,"note":"y; z","tags":"d"}]"#
+        );
+    }
+
+    fn ids(limit: RowLimit) -> String {
+        csv_to_json("id\n1\n2\n\n3\n", false, limit, ',').unwrap()
+    }
+
+    #[test]
+    fn tail_keeps_the_last_rows() {
+        assert_eq!(ids(RowLimit::Tail(0)), "[]");
+        assert_eq!(ids(RowLimit::Tail(1)), r#"[{"id":3}]"#);
+        assert_eq!(ids(RowLimit::Tail(2)), r#"[{"id":2},{"id":3}]"#);
+        assert_eq!(ids(RowLimit::Tail(10)), ids(RowLimit::All));
+        assert_eq!(ids(RowLimit::Head(1)), r#"[{"id":1}]"#);
+    }
+
+    #[test]
+    fn tail_reports_the_line_of_a_bad_row() {
+        let error = csv_to_json("a,b\n1\n2,3\n\n4\n", false, RowLimit::Tail(1), ',');
+        assert_eq!(
+            error.unwrap_err(),
+            "CSV row 5 has 1 fields but header has 2"
+        );
+    }
+}
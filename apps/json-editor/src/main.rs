@@ -1,6 +1,7 @@
 use ratatui::{
     Terminal,
     crossterm::{
+        cursor,
         event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEventKind},
         execute,
     },
@@ -21,6 +22,7 @@ use crate::{
 };
 
 fn main() -> Result<(), Box<dyn Error>> {
+    set_panic_hook();
     enable_raw_mode()?;
     let mut stderr = io::stderr();
     execute!(stderr, EnterAlternateScreen, EnableMouseCapture)?;
@@ -50,6 +52,22 @@ fn main() -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
+/// Leaves raw mode, the alternate screen and mouse capture before the
+/// default hook prints the panic, so the shell is usable afterwards.
+fn set_panic_hook() {
+    let hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let _ = disable_raw_mode();
+        let _ = execute!(
+            io::stderr(),
+            LeaveAlternateScreen,
+            DisableMouseCapture,
+            cursor::Show
+        );
+        hook(info);
+    }));
+}
+
 fn run_app<B: Backend>(terminal: &mut Terminal<B>, app: &mut App) -> io::Result<bool> {
     loop {
         terminal.draw(|f| ui(f, app))?;
@@ -1,20 +1,85 @@
 use std::io::{self};
-use std::{fs, path::PathBuf};
+use std::{fs, path::PathBuf, process::Command};
 
 use chrono::{DateTime, Local};
-use clap::{Parser, Subcommand};
+use clap::{ArgAction, ColorChoice, Parser, Subcommand, ValueEnum, builder::BoolishValueParser};
 use directories::ProjectDirs;
-use owo_colors::OwoColorize;
+use log::{LevelFilter, debug, info, warn};
+use owo_colors::{OwoColorize, Stream::Stdout, Style};
 use serde::{Deserialize, Serialize};
 
 use color_eyre::eyre::{Result, eyre};
 
-#[derive(Debug, Serialize, Deserialize)]
+mod store;
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
 struct Note {
     id: usize,
     title: String,
     body: String,
     created: DateTime<Local>,
+    /// Earlier bodies, oldest first; one is added each time the body changes.
+    #[serde(default)]
+    history: Vec<NoteVersion>,
+    /// Copies of attached files, stored under the data directory.
+    #[serde(default)]
+    attachments: Vec<String>,
+    /// The color the note's ID is shown in.
+    #[serde(default)]
+    label: Option<Label>,
+}
+
+/// A color to pick notes out by in `list`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+enum Label {
+    Red,
+    Yellow,
+    Green,
+    Blue,
+    Magenta,
+    Cyan,
+}
+
+impl Label {
+    fn style(self) -> Style {
+        let style = Style::new().bold();
+        match self {
+            Label::Red => style.red(),
+            Label::Yellow => style.yellow(),
+            Label::Green => style.green(),
+            Label::Blue => style.blue(),
+            Label::Magenta => style.magenta(),
+            Label::Cyan => style.cyan(),
+        }
+    }
+}
+
+/// The style of a note's ID badge: its label's color, or plain cyan.
+fn badge_style(note: &Note) -> Style {
+    note.label.map_or(Style::new().cyan().bold(), Label::style)
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct NoteVersion {
+    saved: DateTime<Local>,
+    body: String,
+}
+
+impl Note {
+    /// Replaces the body, keeping the old one in the history and dropping the
+    /// oldest versions beyond `max_versions`.
+    fn set_body(&mut self, body: String, max_versions: Option<usize>) {
+        let old = std::mem::replace(&mut self.body, body);
+        self.history.push(NoteVersion {
+            saved: Local::now(),
+            body: old,
+        });
+        if let Some(max) = max_versions {
+            let excess = self.history.len().saturating_sub(max);
+            self.history.drain(..excess);
+        }
+    }
 }
 
 #[derive(Parser)]
@@ -28,6 +93,23 @@ struct Note {
 struct Cli {
     #[command(subcommand)]
     command: Commands,
+
+    /// When to use colors (auto turns them off for pipes and NO_COLOR)
+    #[arg(long, value_enum, default_value_t = ColorChoice::Auto, global = true)]
+    color: ColorChoice,
+
+    /// Record changes in an append-only log instead of rewriting the whole store
+    #[arg(
+        long,
+        env = "NOTECTL_APPEND_LOG",
+        value_parser = BoolishValueParser::new(),
+        global = true
+    )]
+    append_log: bool,
+
+    /// Log what notectl reads, writes and runs to stderr (-vv for more detail)
+    #[arg(short, long, action = ArgAction::Count, global = true)]
+    verbose: u8,
 }
 
 #[derive(Subcommand)]
@@ -42,12 +124,32 @@ enum Commands {
         /// Body (omit to enter via stdin)
         #[arg(short, long)]
         body: Vec<String>,
+
+        /// Write the body in $EDITOR instead of on stdin
+        #[arg(short, long, conflicts_with = "body")]
+        editor: bool,
+
+        /// Refuse bodies longer than N words (no limit by default)
+        #[arg(long, value_name = "N")]
+        max_words: Option<usize>,
+
+        /// Save the note even if it is over --max-words
+        #[arg(long, requires = "max_words")]
+        force: bool,
     },
     /// List notes
     #[command(about = "List existing notes")]
     List {
-        #[arg(short, long, help = "Show full body text for each note")]
-        verbose: bool,
+        #[arg(
+            short,
+            long,
+            help = "Show full body text for each note (formerly -v/--verbose, now the log level)"
+        )]
+        full: bool,
+
+        /// Show an aligned table (with --full, a shortened body column)
+        #[arg(long)]
+        table: bool,
     },
     /// View a note by ID
     #[command(about = "Show a note")]
@@ -60,6 +162,75 @@ enum Commands {
     Delete {
         /// Note ID
         id: usize,
+
+        /// Also remove the note's attached files
+        #[arg(long)]
+        purge: bool,
+    },
+    /// Attach a file to a note
+    #[command(about = "Attach a file to a note")]
+    Attach {
+        #[arg(short, long, value_name = "ID")]
+        id: usize,
+
+        /// File to copy into the note's attachments
+        #[arg(short, long, value_name = "PATH")]
+        file: PathBuf,
+    },
+    /// Open an attachment with the default application
+    #[command(about = "Open an attachment")]
+    Open {
+        #[arg(short, long, value_name = "ID")]
+        id: usize,
+
+        /// Attachment number, as listed by `view`
+        #[arg(short, long, value_name = "K")]
+        attachment: usize,
+    },
+    /// Edit a note's title or body
+    #[command(about = "Edit a note")]
+    Edit {
+        #[arg(short, long, value_name = "ID")]
+        id: usize,
+
+        /// New title
+        #[arg(short, long)]
+        title: Option<String>,
+
+        /// New body (omit, along with --title, to enter via stdin)
+        #[arg(short, long)]
+        body: Vec<String>,
+
+        /// Keep at most this many previous versions
+        #[arg(long, value_name = "N")]
+        max_versions: Option<usize>,
+    },
+    /// Show the previous versions of a note
+    #[command(about = "Show a note's history")]
+    History {
+        #[arg(short, long, value_name = "ID")]
+        id: usize,
+    },
+    /// Restore a previous version of a note
+    #[command(about = "Restore a previous version")]
+    Restore {
+        #[arg(short, long, value_name = "ID")]
+        id: usize,
+
+        /// Version number, as shown by `history`
+        #[arg(long, value_name = "K")]
+        version: usize,
+
+        /// Keep at most this many previous versions
+        #[arg(long, value_name = "N")]
+        max_versions: Option<usize>,
+    },
+    /// Add to today's note, titled with the date, creating it if needed
+    #[command(about = "Add to today's daily note")]
+    Today {
+        /// Line to append (omit to enter lines via stdin)
+        #[arg(short, long, value_name = "TEXT")]
+        append: Option<String>,
     },
     /// Search for notes containing a query string
     #[command(about = "Search notes")]
@@ -67,48 +238,158 @@ enum Commands {
         #[arg(short, long, value_name = "QUERY")]
         query: String,
     },
+    /// Append one note's body to another and delete the first
+    #[command(about = "Merge one note into another")]
+    Merge {
+        /// Note that receives the body and keeps its ID
+        #[arg(long, value_name = "ID")]
+        into: usize,
+
+        /// Note whose body is appended
+        #[arg(long, value_name = "ID")]
+        from: usize,
+
+        /// Keep the --from note instead of deleting it
+        #[arg(long)]
+        keep: bool,
+    },
+    /// Give a note a color label, shown on its ID in `list`
+    #[command(about = "Color-label a note")]
+    Label {
+        #[arg(short, long, value_name = "ID")]
+        id: usize,
+
+        /// The label's color
+        #[arg(value_enum, value_name = "COLOR", required_unless_present = "clear")]
+        label: Option<Label>,
+
+        /// Remove the note's label
+        #[arg(long, conflicts_with = "label")]
+        clear: bool,
+    },
+    /// Write notes out as JSON, for copying them to another system
+    #[command(about = "Export notes as JSON")]
+    Export {
+        /// Only export notes with an ID greater than N; N itself is left
+        /// out, so pass the highest ID of the last export to get what's new
+        #[arg(long, value_name = "N")]
+        since_id: Option<usize>,
+
+        /// Write to PATH instead of stdout
+        #[arg(short, long, value_name = "PATH")]
+        output: Option<PathBuf>,
+    },
+    /// Fold the change log from --append-log into a fresh copy of the store
+    #[command(about = "Rewrite the store and clear its change log")]
+    Compact,
 }
 
 fn main() -> Result<()> {
     color_eyre::install()?;
     let cli = Cli::parse();
-    print_banner();
+    match cli.color {
+        ColorChoice::Always => owo_colors::set_override(true),
+        ColorChoice::Never => owo_colors::set_override(false),
+        ColorChoice::Auto => {}
+    }
+    init_logging(cli.verbose);
+    // Keep stdout to the JSON when exporting to it.
+    if !matches!(cli.command, Commands::Export { output: None, .. }) {
+        print_banner();
+    }
 
-    let mut notes = load_notes()?;
+    debug!("data directory: {}", get_data_dir()?.display());
+    let store = store::Store::open()?;
+    let mut notes = store.load_notes()?;
+    let append_log = cli.append_log;
 
     match cli.command {
-        Commands::Add { title, body } => {
-            let body_text = if body.is_empty() {
+        Commands::Add {
+            title,
+            body,
+            editor,
+            max_words,
+            force,
+        } => {
+            let body_text = if editor {
+                match edit_in_editor()? {
+                    Some(text) => text,
+                    None => {
+                        println!(
+                            "{}",
+                            "Empty note or editor failed; nothing saved."
+                                .if_supports_color(Stdout, |t| t.yellow())
+                        );
+                        return Ok(());
+                    }
+                }
+            } else if body.is_empty() {
                 prompt_multiline("Enter note body. Finish with an empty line:")?
             } else {
                 body.join(" ")
             };
-            let id = notes.last().map(|n| n.id + 1).unwrap_or(1);
+            let words = body_text.split_whitespace().count();
+            if let Some(max) = max_words.filter(|max| words > *max) {
+                let message = format!("The body has {} words, over the limit of {}.", words, max);
+                if !force {
+                    println!(
+                        "{}",
+                        format!("{} Nothing saved; use --force to add it anyway.", message)
+                            .if_supports_color(Stdout, |t| t.red())
+                    );
+                    return Ok(());
+                }
+                println!("{}", message.if_supports_color(Stdout, |t| t.yellow()));
+            }
+            let id = store.next_id(&notes)?;
             let note = Note {
                 id,
                 title,
                 body: body_text,
                 created: Local::now(),
+                history: vec![],
+                attachments: vec![],
+                label: None,
             };
             notes.push(note);
-            save_notes(&notes)?;
-            println!("{}", "✅ Note added!".green().bold());
+            store.save_notes(&notes, append_log)?;
+            println!(
+                "{}",
+                "✅ Note added!"
+                    .if_supports_color(Stdout, |t| t.style(Style::new().green().bold()))
+            );
         }
-        Commands::List { verbose } => {
+        Commands::List { full, table } => {
+            // `-v` was `list`'s own flag for bodies until it became the log
+            // level, so it still shows them, with a nudge towards `-f`.
+            let full = if !full && cli.verbose > 0 {
+                warn!(
+                    "`list -v/--verbose` is now the log level; use `list -f/--full` for note bodies"
+                );
+                true
+            } else {
+                full
+            };
             if notes.is_empty() {
                 println!(
                     "{}",
-                    "No notes yet. Add one with `notectl add <title>`!".yellow()
+                    "No notes yet. Add one with `notectl add <title>`!"
+                        .if_supports_color(Stdout, |t| t.yellow())
                 )
+            } else if table {
+                print_table(&notes, full);
             } else {
                 for note in notes {
                     println!(
                         "{} {} · {}",
-                        format!("[#{}]", note.id).cyan().bold(),
-                        note.title.bold(),
-                        note.created.format("%Y-%m-%d %H:%M").dimmed()
+                        format!("[#{}]", note.id)
+                            .if_supports_color(Stdout, |t| t.style(badge_style(&note))),
+                        note.title.if_supports_color(Stdout, |t| t.bold()),
+                        note.created
+                            .format("%Y-%m-%d %H:%M")
+                            .if_supports_color(Stdout, |t| t.dimmed())
                     );
-                    if verbose {
+                    if full {
                         println!("  {}", note.body);
                     }
                 }
@@ -118,24 +399,349 @@ fn main() -> Result<()> {
             if let Some(note) = notes.iter().find(|n| n.id == id) {
                 println!(
                     "{}\n{}\n{}",
-                    note.title.bold().underline(),
-                    "-".repeat(note.title.len()).green(),
+                    note.title
+                        .if_supports_color(Stdout, |t| t.style(Style::new().bold().underline())),
+                    "-".repeat(note.title.len())
+                        .if_supports_color(Stdout, |t| t.green()),
                     note.body
-                )
+                );
+                if !note.attachments.is_empty() {
+                    println!(
+                        "\n{}",
+                        "Attachments:".if_supports_color(Stdout, |t| t.bold())
+                    );
+                    for (i, path) in note.attachments.iter().enumerate() {
+                        println!(
+                            "  {} {}",
+                            format!("{}.", i + 1).if_supports_color(Stdout, |t| t.cyan()),
+                            path
+                        );
+                    }
+                }
             } else {
-                println!("{}", "Note not found".red());
+                println!(
+                    "{}",
+                    "Note not found".if_supports_color(Stdout, |t| t.red())
+                );
             }
         }
-        Commands::Delete { id } => {
+        Commands::Delete { id, purge } => {
             let original_len = notes.len();
-            notes.retain(|n| n.id != id);
+            let removed: Vec<_> = notes.extract_if(.., |n| n.id == id).collect();
             if notes.len() < original_len {
-                save_notes(&notes)?;
-                println!("{}", "🗑️ Note deleted".red().bold());
+                store.save_notes(&notes, append_log)?;
+                if purge {
+                    for path in removed.iter().flat_map(|n| &n.attachments) {
+                        info!("removing attachment {}", path);
+                        fs::remove_file(path).or_else(|e| match e.kind() {
+                            io::ErrorKind::NotFound => Ok(()),
+                            _ => Err(e),
+                        })?;
+                    }
+                }
+                println!(
+                    "{}",
+                    "🗑️ Note deleted"
+                        .if_supports_color(Stdout, |t| t.style(Style::new().red().bold()))
+                );
+            } else {
+                println!(
+                    "{}",
+                    "Note not found".if_supports_color(Stdout, |t| t.red())
+                );
+            }
+        }
+        Commands::Edit {
+            id,
+            title,
+            body,
+            max_versions,
+        } => {
+            let Some(note) = notes.iter_mut().find(|n| n.id == id) else {
+                println!(
+                    "{}",
+                    "Note not found".if_supports_color(Stdout, |t| t.red())
+                );
+                return Ok(());
+            };
+            let new_body = if !body.is_empty() {
+                Some(body.join(" "))
+            } else if title.is_none() {
+                Some(prompt_multiline(
+                    "Enter the new body. Finish with an empty line:",
+                )?)
+            } else {
+                None
+            };
+            if let Some(title) = title {
+                note.title = title;
+            }
+            if let Some(new_body) = new_body.filter(|b| *b != note.body) {
+                note.set_body(new_body, max_versions);
+            }
+            store.save_notes(&notes, append_log)?;
+            println!(
+                "{}",
+                "✏️ Note updated!"
+                    .if_supports_color(Stdout, |t| t.style(Style::new().green().bold()))
+            );
+        }
+        Commands::History { id } => {
+            let Some(note) = notes.iter().find(|n| n.id == id) else {
+                println!(
+                    "{}",
+                    "Note not found".if_supports_color(Stdout, |t| t.red())
+                );
+                return Ok(());
+            };
+            if note.history.is_empty() {
+                println!(
+                    "{}",
+                    "No previous versions.".if_supports_color(Stdout, |t| t.yellow())
+                );
+            }
+            for (i, version) in note.history.iter().enumerate() {
+                println!(
+                    "{} {}",
+                    format!("[v{}]", i + 1)
+                        .if_supports_color(Stdout, |t| t.style(Style::new().cyan().bold())),
+                    version
+                        .saved
+                        .format("%Y-%m-%d %H:%M")
+                        .if_supports_color(Stdout, |t| t.dimmed())
+                );
+                println!("  {}", version.body);
+            }
+        }
+        Commands::Restore {
+            id,
+            version,
+            max_versions,
+        } => {
+            let Some(note) = notes.iter_mut().find(|n| n.id == id) else {
+                println!(
+                    "{}",
+                    "Note not found".if_supports_color(Stdout, |t| t.red())
+                );
+                return Ok(());
+            };
+            let Some(old) = version
+                .checked_sub(1)
+                .and_then(|i| note.history.get(i))
+                .map(|v| v.body.clone())
+            else {
+                println!(
+                    "{}",
+                    "Version not found".if_supports_color(Stdout, |t| t.red())
+                );
+                return Ok(());
+            };
+            note.set_body(old, max_versions);
+            store.save_notes(&notes, append_log)?;
+            println!(
+                "{}",
+                format!("⏪ Restored version {}", version)
+                    .if_supports_color(Stdout, |t| t.style(Style::new().green().bold()))
+            );
+        }
+        Commands::Attach { id, file } => {
+            let Some(note) = notes.iter_mut().find(|n| n.id == id) else {
+                println!(
+                    "{}",
+                    "Note not found".if_supports_color(Stdout, |t| t.red())
+                );
+                return Ok(());
+            };
+            let file_name = file
+                .file_name()
+                .ok_or_else(|| eyre!("'{}' is not a file", file.display()))?;
+            let dir = get_data_dir()?.join("attachments").join(id.to_string());
+            fs::create_dir_all(&dir)?;
+            let mut dest = dir.join(file_name);
+            for n in 1.. {
+                if !dest.exists() {
+                    break;
+                }
+                dest = dir.join(format!("{}-{}", n, file_name.to_string_lossy()));
+            }
+            info!("copying {} to {}", file.display(), dest.display());
+            fs::copy(&file, &dest).map_err(|e| eyre!("cannot copy '{}': {}", file.display(), e))?;
+            note.attachments.push(dest.to_string_lossy().into_owned());
+            store.save_notes(&notes, append_log)?;
+            println!(
+                "{}",
+                "📎 File attached!"
+                    .if_supports_color(Stdout, |t| t.style(Style::new().green().bold()))
+            );
+        }
+        Commands::Open { id, attachment } => {
+            let Some(note) = notes.iter().find(|n| n.id == id) else {
+                println!(
+                    "{}",
+                    "Note not found".if_supports_color(Stdout, |t| t.red())
+                );
+                return Ok(());
+            };
+            let Some(path) = attachment
+                .checked_sub(1)
+                .and_then(|i| note.attachments.get(i))
+            else {
+                println!(
+                    "{}",
+                    "Attachment not found".if_supports_color(Stdout, |t| t.red())
+                );
+                return Ok(());
+            };
+            open_with_default_app(path)?;
+        }
+        Commands::Today { append } => {
+            let title = Local::now().format("%Y-%m-%d").to_string();
+            let existing = notes.iter().position(|n| n.title == title);
+            let text = match append {
+                Some(text) => text,
+                None => {
+                    if let Some(note) = existing.map(|i| &notes[i])
+                        && !note.body.is_empty()
+                    {
+                        println!("{}\n", note.body);
+                    }
+                    prompt_multiline("Add to today's note. Finish with an empty line:")?
+                }
+            };
+            if text.trim().is_empty() {
+                println!(
+                    "{}",
+                    "Nothing to add.".if_supports_color(Stdout, |t| t.yellow())
+                );
+                return Ok(());
+            }
+            // Only now is there something to save, so only now take an ID.
+            let index = match existing {
+                Some(index) => index,
+                None => {
+                    let id = store.next_id(&notes)?;
+                    notes.push(Note {
+                        id,
+                        title: title.clone(),
+                        body: String::new(),
+                        created: Local::now(),
+                        history: vec![],
+                        attachments: vec![],
+                        label: None,
+                    });
+                    notes.len() - 1
+                }
+            };
+            let note = &mut notes[index];
+            if note.body.is_empty() {
+                note.body = text;
+            } else {
+                let body = format!("{}\n{}", note.body, text);
+                note.set_body(body, None);
+            }
+            let id = note.id;
+            store.save_notes(&notes, append_log)?;
+            println!(
+                "{}",
+                format!("📓 Added to {} [#{}]", title, id)
+                    .if_supports_color(Stdout, |t| t.style(Style::new().green().bold()))
+            );
+        }
+        Commands::Label { id, label, .. } => {
+            let Some(note) = notes.iter_mut().find(|n| n.id == id) else {
+                println!(
+                    "{}",
+                    "Note not found".if_supports_color(Stdout, |t| t.red())
+                );
+                return Ok(());
+            };
+            note.label = label;
+            store.save_notes(&notes, append_log)?;
+            let message = match label {
+                Some(label) => format!(
+                    "🏷️ Note labelled {}",
+                    label.to_possible_value().unwrap().get_name()
+                ),
+                None => "🏷️ Label removed".to_string(),
+            };
+            println!(
+                "{}",
+                message.if_supports_color(Stdout, |t| t.style(Style::new().green().bold()))
+            );
+        }
+        Commands::Merge { into, from, keep } => {
+            if into == from {
+                return Err(eyre!("cannot merge note #{} into itself", into));
+            }
+            let (Some(source), true) = (
+                notes.iter().position(|n| n.id == from),
+                notes.iter().any(|n| n.id == into),
+            ) else {
+                println!(
+                    "{}",
+                    "Note not found".if_supports_color(Stdout, |t| t.red())
+                );
+                return Ok(());
+            };
+            // A deleted note's attachments move over with its body; a kept
+            // one holds on to them.
+            let (body, attachments) = if keep {
+                (notes[source].body.clone(), vec![])
+            } else {
+                let source = notes.remove(source);
+                (source.body, source.attachments)
+            };
+            let note = notes.iter_mut().find(|n| n.id == into).unwrap();
+            let merged = if note.body.is_empty() {
+                body
             } else {
-                println!("{}", "Note not found".red());
+                format!("{}\n\n---\n\n{}", note.body, body)
+            };
+            note.set_body(merged, None);
+            note.attachments.extend(attachments);
+            store.save_notes(&notes, append_log)?;
+            println!(
+                "{}",
+                format!(
+                    "🔗 Merged #{} into #{}{}",
+                    from,
+                    into,
+                    if keep {
+                        format!(" (kept #{})", from)
+                    } else {
+                        String::new()
+                    }
+                )
+                .if_supports_color(Stdout, |t| t.style(Style::new().green().bold()))
+            );
+        }
+        Commands::Export { since_id, output } => {
+            let exported: Vec<&Note> = notes
+                .iter()
+                .filter(|n| since_id.is_none_or(|since| n.id > since))
+                .collect();
+            let json = serde_json::to_string_pretty(&exported)?;
+            match output {
+                Some(path) => {
+                    fs::write(&path, json + "\n")?;
+                    info!("exported {} notes to {}", exported.len(), path.display());
+                    println!(
+                        "{}",
+                        format!("📤 Exported {} notes to {}", exported.len(), path.display())
+                            .if_supports_color(Stdout, |t| t.style(Style::new().green().bold()))
+                    );
+                }
+                None => println!("{}", json),
             }
         }
+        Commands::Compact => {
+            store.compact(&notes)?;
+            println!(
+                "{}",
+                format!("🗜️ Compacted {} notes", notes.len())
+                    .if_supports_color(Stdout, |t| t.style(Style::new().green().bold()))
+            );
+        }
         Commands::Search { query } => {
             let query_lower = query.to_lowercase();
             let results: Vec<_> = notes
@@ -146,13 +752,17 @@ fn main() -> Result<()> {
                 })
                 .collect();
             if results.is_empty() {
-                println!("{}", "No matches 😯".yellow());
+                println!(
+                    "{}",
+                    "No matches 😯".if_supports_color(Stdout, |t| t.yellow())
+                );
             } else {
                 for note in results {
                     println!(
                         "{} {}",
-                        format!("[#{}]", note.id).cyan().bold(),
-                        note.title.bold()
+                        format!("[#{}]", note.id)
+                            .if_supports_color(Stdout, |t| t.style(badge_style(note))),
+                        note.title.if_supports_color(Stdout, |t| t.bold())
                     );
                 }
             }
@@ -162,8 +772,85 @@ fn main() -> Result<()> {
     Ok(())
 }
 
+/// Widest a title may be in `list --table` before it's cut short.
+const MAX_TITLE_WIDTH: usize = 40;
+/// Widest the body column may be in `list --table --full`.
+const MAX_BODY_WIDTH: usize = 50;
+
+/// Cuts `text` to `max` characters, ending in "…" when anything was dropped.
+fn ellipsize(text: &str, max: usize) -> String {
+    if text.chars().count() <= max {
+        return text.to_string();
+    }
+    let mut cut: String = text.chars().take(max.saturating_sub(1)).collect();
+    cut.push('…');
+    cut
+}
+
+fn print_table(notes: &[Note], full: bool) {
+    let mut header = vec!["ID", "Title", "Created"];
+    if full {
+        header.push("Body");
+    }
+    let rows: Vec<Vec<String>> = notes
+        .iter()
+        .map(|note| {
+            let mut row = vec![
+                note.id.to_string(),
+                ellipsize(&note.title, MAX_TITLE_WIDTH),
+                note.created.format("%Y-%m-%d %H:%M").to_string(),
+            ];
+            if full {
+                let first_line = note.body.lines().next().unwrap_or_default();
+                let more = if note.body.lines().nth(1).is_some() {
+                    " …"
+                } else {
+                    ""
+                };
+                row.push(ellipsize(
+                    &format!("{}{}", first_line, more),
+                    MAX_BODY_WIDTH,
+                ));
+            }
+            row
+        })
+        .collect();
+
+    let mut widths: Vec<usize> = header.iter().map(|h| h.chars().count()).collect();
+    for row in &rows {
+        for (w, cell) in widths.iter_mut().zip(row) {
+            *w = (*w).max(cell.chars().count());
+        }
+    }
+    let pad = |cell: &str, w: usize| format!("{}{}", cell, " ".repeat(w - cell.chars().count()));
+
+    let header: Vec<String> = header
+        .iter()
+        .zip(&widths)
+        .map(|(h, w)| pad(h, *w))
+        .collect();
+    println!(
+        "{}",
+        header
+            .join("  ")
+            .if_supports_color(Stdout, |t| t.style(Style::new().bold().underline()))
+    );
+    for (note, row) in notes.iter().zip(rows) {
+        let cells: Vec<String> = row.iter().zip(&widths).map(|(c, w)| pad(c, *w)).collect();
+        let id_style = note.label.map_or(Style::new().cyan(), Label::style);
+        println!(
+            "{}  {}",
+            cells[0].if_supports_color(Stdout, |t| t.style(id_style)),
+            cells[1..].join("  ")
+        );
+    }
+}
+
 fn prompt_multiline(prompt: &str) -> Result<String> {
-    println!("{}", prompt.blue().bold());
+    println!(
+        "{}",
+        prompt.if_supports_color(Stdout, |t| t.style(Style::new().blue().bold()))
+    );
     let mut lines = Vec::new();
 
     loop {
@@ -178,36 +865,106 @@ fn prompt_multiline(prompt: &str) -> Result<String> {
     Ok(lines.join("\n"))
 }
 
-fn print_banner() {
-    use figlet_rs::FIGfont;
-    let font = FIGfont::standard().unwrap();
-    let figure = font.convert("Notectl").unwrap();
-    println!("{}", figure.to_string().bright_magenta());
+/// Opens `$EDITOR` (or `vi`) on a temporary file and returns what was saved,
+/// or `None` if the editor exited non-zero or left the file empty.
+fn edit_in_editor() -> Result<Option<String>> {
+    let editor = std::env::var("EDITOR").unwrap_or_else(|_| "vi".into());
+    let mut parts = editor.split_whitespace();
+    let program = parts.next().ok_or_else(|| eyre!("$EDITOR is empty"))?;
+    let path = create_temp_file()?;
+
+    info!("running {} on {}", editor, path.display());
+    let status = Command::new(program).args(parts).arg(&path).status();
+    let text = fs::read_to_string(&path);
+    fs::remove_file(&path)?;
+
+    let status = status.map_err(|e| eyre!("cannot run editor '{}': {}", editor, e))?;
+    let text = text?.trim_end().to_owned();
+    Ok((status.success() && !text.trim().is_empty()).then_some(text))
 }
 
-fn load_notes() -> Result<Vec<Note>> {
-    let path = get_db_path()?;
-    if !path.exists() {
-        return Ok(vec![]);
+/// Creates an empty file with a random name in the temp directory, readable
+/// only by us on Unix. It must not exist already, so a file or symlink put
+/// there by someone else is never written through.
+fn create_temp_file() -> Result<PathBuf> {
+    use std::hash::{BuildHasher, RandomState};
+
+    for _ in 0..16 {
+        let random = RandomState::new().hash_one(std::process::id());
+        let path = std::env::temp_dir().join(format!("notectl-{:016x}.md", random));
+        let mut options = fs::OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        match options.open(&path) {
+            Ok(_) => return Ok(path),
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(e.into()),
+        }
     }
-    let data = fs::read_to_string(path)?;
-    let notes: Vec<Note> = serde_json::from_str(&data)?;
-    Ok(notes)
+    Err(eyre!("cannot create a temporary file for the editor"))
 }
 
-fn save_notes(notes: &[Note]) -> Result<()> {
-    let path = get_db_path()?;
-    let data = serde_json::to_string_pretty(notes)?;
-    fs::write(path, data)?;
+fn open_with_default_app(path: &str) -> Result<()> {
+    let mut command = if cfg!(target_os = "macos") {
+        Command::new("open")
+    } else if cfg!(windows) {
+        let mut c = Command::new("cmd");
+        c.args(["/C", "start", ""]);
+        c
+    } else {
+        Command::new("xdg-open")
+    };
+    info!(
+        "opening {} with {}",
+        path,
+        command.get_program().to_string_lossy()
+    );
+    let status = command.arg(path).status().map_err(|e| {
+        eyre!(
+            "cannot launch '{}': {}",
+            command.get_program().to_string_lossy(),
+            e
+        )
+    })?;
+    if !status.success() {
+        return Err(eyre!("could not open '{}'", path));
+    }
     Ok(())
 }
 
-fn get_db_path() -> Result<PathBuf> {
+/// Sends log records to stderr: warnings only by default, then info and
+/// debug with each `-v`. `RUST_LOG` overrides the level.
+fn init_logging(verbose: u8) {
+    let level = match verbose {
+        0 => LevelFilter::Warn,
+        1 => LevelFilter::Info,
+        2 => LevelFilter::Debug,
+        _ => LevelFilter::Trace,
+    };
+    env_logger::Builder::new()
+        .filter_level(level)
+        .parse_default_env()
+        .format_timestamp(None)
+        .init();
+}
+
+fn print_banner() {
+    use figlet_rs::FIGfont;
+    let font = FIGfont::standard().unwrap();
+    let figure = font.convert("Notectl").unwrap();
+    println!(
+        "{}",
+        figure
+            .to_string()
+            .if_supports_color(Stdout, |t| t.bright_magenta())
+    );
+}
+
+fn get_data_dir() -> Result<PathBuf> {
     let proj = ProjectDirs::from("", "", "notectl")
         .ok_or_else(|| eyre!("cannot determine data directory"))?;
-    let path = proj.data_dir().join("notes.json");
-    if let Some(parent) = path.parent() {
-        fs::create_dir_all(parent)?;
-    }
-    Ok(path)
+    let dir = proj.data_dir().to_path_buf();
+    fs::create_dir_all(&dir)?;
+    Ok(dir)
 }
@@ -0,0 +1,336 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use color_eyre::eyre::{Result, eyre};
+use log::{debug, info};
+use serde::{Deserialize, Serialize};
+
+use crate::{Note, get_data_dir};
+
+/// Log entries kept before an `--append-log` save folds them into the
+/// snapshot.
+const COMPACT_AFTER: usize = 200;
+
+/// One line of `notes.log`: a note as it now stands, or the ID of one that
+/// was deleted. Replayed in order over the `notes.json` snapshot.
+#[derive(Serialize, Deserialize)]
+enum Entry<N> {
+    Put(N),
+    Delete(usize),
+}
+
+/// Where the notes are kept: `notes.json`, `notes.log` and `next_id` in
+/// one directory.
+#[derive(Debug)]
+pub struct Store {
+    dir: PathBuf,
+}
+
+impl Store {
+    pub fn open() -> Result<Self> {
+        Self::open_in(get_data_dir()?)
+    }
+
+    /// Opens the store kept in `dir`, creating the directory if missing.
+    pub fn open_in(dir: PathBuf) -> Result<Self> {
+        fs::create_dir_all(&dir)?;
+        Ok(Store { dir })
+    }
+
+    /// The snapshot with the log replayed over it, how many log entries there
+    /// were, and whether the last one was cut short.
+    fn read(&self) -> Result<(Vec<Note>, usize, bool)> {
+        let path = self.db_path();
+        let mut notes: Vec<Note> = if path.exists() {
+            serde_json::from_str(&fs::read_to_string(&path)?)?
+        } else {
+            vec![]
+        };
+        debug!("{} notes in {}", notes.len(), path.display());
+
+        let log_path = self.log_path();
+        if !log_path.exists() {
+            return Ok((notes, 0, false));
+        }
+        let log = fs::read_to_string(&log_path)?;
+        let lines: Vec<&str> = log.lines().filter(|l| !l.trim().is_empty()).collect();
+        for (i, line) in lines.iter().enumerate() {
+            let entry: Entry<Note> = match serde_json::from_str(line) {
+                Ok(entry) => entry,
+                // A write cut short leaves at most the last line unfinished.
+                Err(_) if i + 1 == lines.len() => return Ok((notes, i, true)),
+                Err(e) => return Err(eyre!("{}, line {}: {}", log_path.display(), i + 1, e)),
+            };
+            match entry {
+                Entry::Put(note) => match notes.iter_mut().find(|n| n.id == note.id) {
+                    Some(old) => *old = note,
+                    None => notes.push(note),
+                },
+                Entry::Delete(id) => notes.retain(|n| n.id != id),
+            }
+        }
+        debug!(
+            "replayed {} entries from {}",
+            lines.len(),
+            log_path.display()
+        );
+        Ok((notes, lines.len(), false))
+    }
+
+    pub fn load_notes(&self) -> Result<Vec<Note>> {
+        Ok(self.read()?.0)
+    }
+
+    /// Saves `notes`. With `--append-log` only the notes that changed since the
+    /// last save are written, as lines at the end of the log; otherwise, or once
+    /// the log grows past `COMPACT_AFTER` entries, the whole store is rewritten.
+    pub fn save_notes(&self, notes: &[Note], append_log: bool) -> Result<()> {
+        if !append_log {
+            return self.compact(notes);
+        }
+        let (old, logged, torn) = self.read()?;
+        let mut lines = String::new();
+        for note in notes {
+            if !old.iter().any(|o| o == note) {
+                lines.push_str(&serde_json::to_string(&Entry::Put(note))?);
+                lines.push('\n');
+            }
+        }
+        for gone in old.iter().filter(|o| !notes.iter().any(|n| n.id == o.id)) {
+            lines.push_str(&serde_json::to_string(&Entry::<&Note>::Delete(gone.id))?);
+            lines.push('\n');
+        }
+        if lines.is_empty() {
+            return Ok(());
+        }
+        // Appending after a torn line would bury it mid-log, so start afresh.
+        if torn || logged + lines.lines().count() > COMPACT_AFTER {
+            return self.compact(notes);
+        }
+
+        let mut log = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.log_path())?;
+        log.write_all(lines.as_bytes())?;
+        log.sync_data()?;
+        info!(
+            "appended {} entries ({} bytes) to the log",
+            lines.lines().count(),
+            lines.len()
+        );
+        Ok(())
+    }
+
+    /// Writes every note to a fresh snapshot and empties the log. The snapshot
+    /// is written beside the old one, synced and renamed over it, so a crash
+    /// leaves one or the other intact; the log goes only once the new snapshot
+    /// is on disk.
+    pub fn compact(&self, notes: &[Note]) -> Result<()> {
+        let path = self.db_path();
+        let tmp = path.with_extension("json.tmp");
+        let data = serde_json::to_string_pretty(notes)?;
+        let mut file = File::create(&tmp)?;
+        file.write_all(data.as_bytes())?;
+        file.sync_all()?;
+        fs::rename(&tmp, &path)?;
+        sync_dir(&self.dir)?;
+        info!(
+            "wrote {} notes ({} bytes) to {}",
+            notes.len(),
+            data.len(),
+            path.display()
+        );
+
+        let log_path = self.log_path();
+        if log_path.exists() {
+            fs::remove_file(&log_path)?;
+            sync_dir(&self.dir)?;
+            debug!("removed {}", log_path.display());
+        }
+        Ok(())
+    }
+
+    /// The ID for a new note: one past the highest ever handed out, kept in
+    /// `next_id` so a deleted note's ID is never reused and `export --since-id`
+    /// can't take a new note for one already exported. Stores from before the
+    /// counter carry on from their highest ID.
+    pub fn next_id(&self, notes: &[Note]) -> Result<usize> {
+        let path = self.next_id_path();
+        let counted = if path.exists() {
+            let text = fs::read_to_string(&path)?;
+            text.trim()
+                .parse()
+                .map_err(|e| eyre!("{}: {}", path.display(), e))?
+        } else {
+            1
+        };
+        let id = notes.iter().map(|n| n.id + 1).fold(counted, usize::max);
+        fs::write(&path, (id + 1).to_string())?;
+        debug!("handed out id {}", id);
+        Ok(id)
+    }
+
+    fn db_path(&self) -> PathBuf {
+        self.dir.join("notes.json")
+    }
+
+    fn log_path(&self) -> PathBuf {
+        self.dir.join("notes.log")
+    }
+
+    fn next_id_path(&self) -> PathBuf {
+        self.dir.join("next_id")
+    }
+}
+
+/// Makes renames and removals in `dir` durable. Only Unix can open a
+/// directory to sync it.
+fn sync_dir(dir: &Path) -> Result<()> {
+    #[cfg(unix)]
+    File::open(dir)?.sync_all()?;
+    #[cfg(not(unix))]
+    let _ = dir;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Local;
+
+    use super::*;
+
+    fn note(id: usize, title: &str) -> Note {
+        Note {
+            id,
+            title: title.into(),
+            body: String::new(),
+            created: Local::now(),
+            history: vec![],
+            attachments: vec![],
+            label: None,
+        }
+    }
+
+    fn titles(notes: &[Note]) -> Vec<(usize, &str)> {
+        notes.iter().map(|n| (n.id, n.title.as_str())).collect()
+    }
+
+    fn log_lines(store: &Store) -> usize {
+        fs::read_to_string(store.log_path()).map_or(0, |log| log.lines().count())
+    }
+
+    fn open(dir: &tempfile::TempDir) -> Store {
+        Store::open_in(dir.path().join("notes")).unwrap()
+    }
+
+    #[test]
+    fn replays_puts_and_deletes_over_the_snapshot() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = open(&dir);
+        let mut notes = vec![note(1, "one"), note(2, "two")];
+        store.save_notes(&notes, false).unwrap();
+
+        notes[0].title = "uno".into();
+        notes.push(note(3, "three"));
+        store.save_notes(&notes, true).unwrap();
+        notes.remove(1);
+        store.save_notes(&notes, true).unwrap();
+
+        assert_eq!(log_lines(&store), 3);
+        let reopened = open(&dir).load_notes().unwrap();
+        assert_eq!(titles(&reopened), [(1, "uno"), (3, "three")]);
+    }
+
+    #[test]
+    fn saving_nothing_new_writes_nothing() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = open(&dir);
+        let notes = vec![note(1, "one")];
+        store.save_notes(&notes, true).unwrap();
+        store.save_notes(&notes, true).unwrap();
+        assert_eq!(log_lines(&store), 1);
+    }
+
+    #[test]
+    fn a_torn_last_line_is_skipped() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = open(&dir);
+        store.save_notes(&[note(1, "one")], true).unwrap();
+        let mut log = OpenOptions::new()
+            .append(true)
+            .open(store.log_path())
+            .unwrap();
+        log.write_all(br#"{"Put":{"id":2,"ti"#).unwrap();
+
+        assert_eq!(titles(&store.load_notes().unwrap()), [(1, "one")]);
+        assert_eq!(store.read().unwrap().1, 1);
+        assert!(store.read().unwrap().2);
+    }
+
+    #[test]
+    fn a_torn_line_before_the_end_is_an_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = open(&dir);
+        store.save_notes(&[note(1, "one")], true).unwrap();
+        let mut log = OpenOptions::new()
+            .append(true)
+            .open(store.log_path())
+            .unwrap();
+        log.write_all(b"{\"Put\":{\"id\":2\n{\"Delete\":1}\n")
+            .unwrap();
+
+        let error = store.load_notes().unwrap_err().to_string();
+        assert!(error.contains("line 2"), "{}", error);
+    }
+
+    #[test]
+    fn saving_after_a_torn_line_compacts() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = open(&dir);
+        store.save_notes(&[note(1, "one")], true).unwrap();
+        let mut log = OpenOptions::new()
+            .append(true)
+            .open(store.log_path())
+            .unwrap();
+        log.write_all(b"{\"Delete\":").unwrap();
+
+        let notes = vec![note(1, "one"), note(2, "two")];
+        store.save_notes(&notes, true).unwrap();
+        assert!(!store.log_path().exists());
+        assert!(!store.db_path().with_extension("json.tmp").exists());
+        assert_eq!(titles(&store.load_notes().unwrap()), titles(&notes));
+    }
+
+    #[test]
+    fn compacts_once_the_log_is_full() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = open(&dir);
+        let mut notes = vec![note(1, "one")];
+        for i in 0..COMPACT_AFTER {
+            notes[0].title = i.to_string();
+            store.save_notes(&notes, true).unwrap();
+        }
+        assert_eq!(log_lines(&store), COMPACT_AFTER);
+        assert!(!store.db_path().exists());
+
+        notes[0].title = "last".into();
+        store.save_notes(&notes, true).unwrap();
+        assert!(!store.log_path().exists());
+        assert_eq!(titles(&store.load_notes().unwrap()), [(1, "last")]);
+    }
+
+    #[test]
+    fn ids_are_never_reused() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = open(&dir);
+        let mut notes = vec![note(1, "one")];
+        assert_eq!(store.next_id(&notes).unwrap(), 2);
+        notes.clear();
+        assert_eq!(store.next_id(&notes).unwrap(), 3);
+        // A store from before the counter carries on from its highest ID.
+        fs::remove_file(store.next_id_path()).unwrap();
+        assert_eq!(store.next_id(&[note(7, "seven")]).unwrap(), 8);
+    }
+}
@@ -1,8 +1,12 @@
-use clap::{ColorChoice, Parser, Subcommand, arg, command};
+use clap::{ColorChoice, Parser, Subcommand};
 use colored::*;
 use std::io::{self, Write};
 use std::time::Instant;
 
+mod practice;
+mod scores;
+mod tui;
+
 #[derive(Clone)]
 struct Question {
     text: &'static str,
@@ -18,6 +22,19 @@ struct Quiz {
     pass_mark: f32,
 }
 
+impl Question {
+    /// `choice` is the 1-based option number, as typed by the player.
+    fn is_correct(&self, choice: usize) -> bool {
+        choice == self.correct
+    }
+}
+
+impl Quiz {
+    fn passed(&self, correct: usize) -> bool {
+        correct as f32 / self.questions.len() as f32 >= self.pass_mark
+    }
+}
+
 fn quizzes() -> Vec<Quiz> {
     vec![
         Quiz {
@@ -106,9 +123,25 @@ enum Commands {
     List,
     /// Take a quiz by name (see `list`)
     Take {
-        /// /// The quiz's short name (e.g. "general")")
+        /// The quiz's short name (e.g. "general"), or enough of it to tell it apart
         #[arg(value_parser = clap::builder::NonEmptyStringValueParser::new())]
         name: String,
+
+        /// Take the quiz in a full-screen terminal UI
+        #[arg(long)]
+        tui: bool,
+    },
+    /// Show past results, the daily streak and lifetime accuracy
+    Scores,
+    /// Answer questions from every quiz, mostly ones you often get wrong
+    Practice {
+        /// How many questions to ask
+        #[arg(short = 'n', long, default_value_t = 5)]
+        count: usize,
+
+        /// Practice in a full-screen terminal UI
+        #[arg(long)]
+        tui: bool,
     },
 }
 
@@ -126,16 +159,99 @@ fn main() {
             }
         }
 
-        Commands::Take { name } => match quizzes.iter().find(|q| q.name == name) {
-            Some(quiz) => run_quiz(quiz),
-            None => {
+        Commands::Take { name, tui } => match find_quiz(&quizzes, &name) {
+            Ok(quiz) => {
+                let origins = vec![quiz.name; quiz.questions.len()];
+                take(quiz, &origins, tui);
+            }
+            Err(candidates) if candidates.is_empty() => {
                 eprintln!("{} {}", "unknown quiz:".bright_red(), name)
             }
+            Err(candidates) => {
+                eprintln!(
+                    "{} {} could be {}",
+                    "ambiguous quiz:".bright_red(),
+                    name,
+                    candidates.join(", ")
+                )
+            }
+        },
+
+        Commands::Scores => match scores::load() {
+            Ok(attempts) => scores::print(&quizzes, &attempts),
+            Err(e) => eprintln!("{} {}", "cannot read scores:".bright_red(), e),
+        },
+
+        Commands::Practice { count, tui } => match scores::load_questions() {
+            Ok(_) if count == 0 => eprintln!("{}", "nothing to practice".bright_red()),
+            Ok(stats) => {
+                let (quiz, origins) = practice::build(&quizzes, &stats, count);
+                take(&quiz, &origins, tui);
+            }
+            Err(e) => eprintln!("{} {}", "cannot read scores:".bright_red(), e),
         },
     }
 }
 
-fn run_quiz(quiz: &Quiz) {
+/// The quiz called `name`, ignoring case, or else the only one whose name
+/// starts with it, or else the only one containing its letters in order
+/// ("gnl" for "general"). Fails with the candidates when several match at
+/// the first step that matches any, or with none.
+fn find_quiz<'a>(quizzes: &'a [Quiz], name: &str) -> Result<&'a Quiz, Vec<&'static str>> {
+    let name = name.to_lowercase();
+    let in_order = |quiz: &str| {
+        let mut letters = quiz.chars();
+        name.chars().all(|c| letters.any(|l| l == c))
+    };
+    let steps: [&dyn Fn(&str) -> bool; 3] = [
+        &|quiz| quiz == name,
+        &|quiz| quiz.starts_with(&name),
+        &in_order,
+    ];
+    for matches in steps {
+        let found: Vec<&Quiz> = quizzes
+            .iter()
+            .filter(|q| matches(&q.name.to_lowercase()))
+            .collect();
+        match found.as_slice() {
+            [] => continue,
+            [quiz] => return Ok(quiz),
+            _ => return Err(found.iter().map(|q| q.name).collect()),
+        }
+    }
+    Err(Vec::new())
+}
+
+/// Runs `quiz` and saves the result, filing each answer under the quiz its
+/// question came from in `origins`.
+fn take(quiz: &Quiz, origins: &[&str], tui: bool) {
+    let answers = if tui {
+        match tui::run(quiz) {
+            Ok(Some(answers)) => answers,
+            Ok(None) => return,
+            Err(e) => {
+                eprintln!("{} {}", "terminal error:".bright_red(), e);
+                return;
+            }
+        }
+    } else {
+        run_quiz(quiz)
+    };
+
+    let correct = answers.iter().filter(|right| **right).count();
+    let keys = origins
+        .iter()
+        .zip(&quiz.questions)
+        .map(|(origin, q)| scores::question_key(origin, q));
+    if let Err(e) =
+        scores::record(quiz, correct).and_then(|_| scores::record_answers(keys.zip(answers)))
+    {
+        eprintln!("{} {}", "cannot save score:".bright_red(), e);
+    }
+}
+
+/// Asks every question on stdin and returns whether each answer was right.
+fn run_quiz(quiz: &Quiz) -> Vec<bool> {
     println!(
         "\n{} {}\n",
         "▶️  Starting quiz:".bold().bright_cyan(),
@@ -144,6 +260,7 @@ fn run_quiz(quiz: &Quiz) {
 
     let start = Instant::now();
     let mut correct: usize = 0;
+    let mut answers = Vec::with_capacity(quiz.questions.len());
 
     for (i, q) in quiz.questions.iter().enumerate() {
         println!(
@@ -163,7 +280,8 @@ fn run_quiz(quiz: &Quiz) {
 
             match input.trim().parse::<usize>() {
                 Ok(num @ 1..=4) => {
-                    if num == q.correct {
+                    answers.push(q.is_correct(num));
+                    if q.is_correct(num) {
                         println!("{}\n", "✓ Correct!\n".bright_green().bold());
                         correct += 1;
                     } else {
@@ -185,7 +303,7 @@ fn run_quiz(quiz: &Quiz) {
     let elapsed = start.elapsed();
     let total = quiz.questions.len();
     let pct = correct as f32 / total as f32;
-    let passed = pct >= quiz.pass_mark;
+    let passed = quiz.passed(correct);
 
     println!(
         "{}\n├── {} {}/{} ({:.0}%)\n└── {} {}s\n",
@@ -208,4 +326,5 @@ fn run_quiz(quiz: &Quiz) {
                 .bright_red()
         );
     }
+    answers
 }
@@ -0,0 +1,44 @@
+use std::collections::BTreeMap;
+
+use rand::seq::IndexedRandom;
+
+use crate::{
+    Question, Quiz,
+    scores::{QuestionStats, question_key},
+};
+
+/// How likely a question is to come up in practice: its error rate, nudged
+/// towards one half so a question asked once isn't settled for good. With
+/// no history every question weighs the same and the pick is plain random.
+fn weight(stats: Option<&QuestionStats>) -> f64 {
+    let (asked, wrong) = stats.map_or((0, 0), |s| (s.asked, s.wrong));
+    (wrong as f64 + 1.0) / (asked as f64 + 2.0)
+}
+
+/// Up to `count` questions from every quiz, favouring the ones most often
+/// answered wrongly, as a quiz of their own. Also returns the name of the
+/// quiz each question came from, to file the answers under.
+pub fn build(
+    quizzes: &[Quiz],
+    stats: &BTreeMap<String, QuestionStats>,
+    count: usize,
+) -> (Quiz, Vec<&'static str>) {
+    let pool: Vec<(&'static str, &Question)> = quizzes
+        .iter()
+        .flat_map(|quiz| quiz.questions.iter().map(|q| (quiz.name, q)))
+        .collect();
+    let picked: Vec<(&'static str, &Question)> = pool
+        .choose_multiple_weighted(&mut rand::rng(), count.min(pool.len()), |(name, q)| {
+            weight(stats.get(&question_key(name, q)))
+        })
+        .map(|picked| picked.copied().collect())
+        .unwrap_or_default();
+
+    let quiz = Quiz {
+        name: "practice",
+        title: "🎯  Practice",
+        questions: picked.iter().map(|(_, q)| (*q).clone()).collect(),
+        pass_mark: 0.7,
+    };
+    (quiz, picked.into_iter().map(|(name, _)| name).collect())
+}
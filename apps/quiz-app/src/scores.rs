@@ -0,0 +1,166 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs, io,
+    path::PathBuf,
+};
+
+use chrono::{DateTime, Duration, Local, NaiveDate};
+use colored::*;
+use directories::ProjectDirs;
+use serde::{Deserialize, Serialize};
+
+use crate::{Question, Quiz};
+
+/// One finished run through a quiz.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Attempt {
+    pub quiz: String,
+    pub taken: DateTime<Local>,
+    pub correct: usize,
+    pub total: usize,
+}
+
+/// How often one question has been answered, and how often wrongly.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct QuestionStats {
+    pub asked: u32,
+    pub wrong: u32,
+}
+
+fn data_path(file: &str) -> io::Result<PathBuf> {
+    let proj = ProjectDirs::from("", "", "quiz-app")
+        .ok_or_else(|| io::Error::other("cannot determine data directory"))?;
+    fs::create_dir_all(proj.data_dir())?;
+    Ok(proj.data_dir().join(file))
+}
+
+fn scores_path() -> io::Result<PathBuf> {
+    data_path("scores.json")
+}
+
+fn questions_path() -> io::Result<PathBuf> {
+    data_path("questions.json")
+}
+
+/// Identifies a question across runs: the quiz's name and an FNV-1a hash of
+/// the text, which unlike `DefaultHasher` never changes between builds.
+pub fn question_key(quiz: &str, question: &Question) -> String {
+    let hash = question
+        .text
+        .bytes()
+        .fold(0xcbf29ce484222325_u64, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+        });
+    format!("{}:{:016x}", quiz, hash)
+}
+
+pub fn load_questions() -> io::Result<BTreeMap<String, QuestionStats>> {
+    let path = questions_path()?;
+    if !path.exists() {
+        return Ok(BTreeMap::new());
+    }
+    let data = fs::read_to_string(path)?;
+    serde_json::from_str(&data).map_err(io::Error::other)
+}
+
+/// Adds answers, as the key of each question and whether it was answered
+/// right, to the per-question stats.
+pub fn record_answers(answers: impl IntoIterator<Item = (String, bool)>) -> io::Result<()> {
+    let mut stats = load_questions()?;
+    for (key, right) in answers {
+        let entry = stats.entry(key).or_default();
+        entry.asked += 1;
+        if !right {
+            entry.wrong += 1;
+        }
+    }
+    let data = serde_json::to_string_pretty(&stats).map_err(io::Error::other)?;
+    fs::write(questions_path()?, data)
+}
+
+pub fn load() -> io::Result<Vec<Attempt>> {
+    let path = scores_path()?;
+    if !path.exists() {
+        return Ok(vec![]);
+    }
+    let data = fs::read_to_string(path)?;
+    serde_json::from_str(&data).map_err(io::Error::other)
+}
+
+/// Appends a finished attempt to the saved scores.
+pub fn record(quiz: &Quiz, correct: usize) -> io::Result<()> {
+    let mut attempts = load()?;
+    attempts.push(Attempt {
+        quiz: quiz.name.to_string(),
+        taken: Local::now(),
+        correct,
+        total: quiz.questions.len(),
+    });
+    let data = serde_json::to_string_pretty(&attempts).map_err(io::Error::other)?;
+    fs::write(scores_path()?, data)
+}
+
+/// Days in a row with at least one attempt, counting back from today, or
+/// from yesterday if nothing has been taken yet today.
+pub fn streak(attempts: &[Attempt], today: NaiveDate) -> usize {
+    let days: BTreeSet<NaiveDate> = attempts.iter().map(|a| a.taken.date_naive()).collect();
+    let mut day = if days.contains(&today) {
+        today
+    } else {
+        today - Duration::days(1)
+    };
+    let mut streak = 0;
+    while days.contains(&day) {
+        streak += 1;
+        day -= Duration::days(1);
+    }
+    streak
+}
+
+fn percent(correct: usize, total: usize) -> f32 {
+    correct as f32 / total.max(1) as f32 * 100.0
+}
+
+pub fn print(quizzes: &[Quiz], attempts: &[Attempt]) {
+    println!("{}\n", "📈  Scores".bold().underline());
+    if attempts.is_empty() {
+        println!("{}", "No quizzes taken yet.".dimmed());
+        return;
+    }
+
+    for quiz in quizzes {
+        let taken: Vec<&Attempt> = attempts.iter().filter(|a| a.quiz == quiz.name).collect();
+        if taken.is_empty() {
+            continue;
+        }
+        let best = taken.iter().map(|a| a.correct).max().unwrap_or(0);
+        let correct: usize = taken.iter().map(|a| a.correct).sum();
+        let total: usize = taken.iter().map(|a| a.total).sum();
+        println!(
+            " • {} {} attempt{} · best {}/{} · accuracy {:.0}%",
+            quiz.name.bold().bright_green(),
+            taken.len(),
+            if taken.len() == 1 { "" } else { "s" },
+            best,
+            quiz.questions.len(),
+            percent(correct, total)
+        );
+    }
+
+    let correct: usize = attempts.iter().map(|a| a.correct).sum();
+    let total: usize = attempts.iter().map(|a| a.total).sum();
+    let streak = streak(attempts, Local::now().date_naive());
+    println!(
+        "\n{} {} day{}",
+        "🔥  Streak:".bold(),
+        streak,
+        if streak == 1 { "" } else { "s" }
+    );
+    println!(
+        "{} {:.0}% ({}/{})",
+        "🎯  Lifetime accuracy:".bold(),
+        percent(correct, total),
+        correct,
+        total
+    );
+}
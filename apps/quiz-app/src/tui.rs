@@ -0,0 +1,224 @@
+use std::{io, time::Instant};
+
+use ratatui::{
+    DefaultTerminal, Frame,
+    crossterm::event::{self, Event, KeyCode, KeyEventKind},
+    layout::{Constraint, Layout, Rect},
+    style::{Color, Style, Stylize},
+    text::{Line, Text},
+    widgets::{Block, BorderType, Gauge, List, ListState, Padding, Paragraph, Wrap},
+};
+
+use crate::Quiz;
+
+enum Screen {
+    Question,
+    /// The player answered; holds whether they got it right.
+    Feedback(bool),
+    Results,
+}
+
+struct QuizTui<'a> {
+    quiz: &'a Quiz,
+    current: usize,
+    options: ListState,
+    correct: usize,
+    /// Whether each question answered so far was right.
+    answers: Vec<bool>,
+    screen: Screen,
+    start: Instant,
+    elapsed_secs: u64,
+}
+
+/// Runs `quiz` full-screen: arrow keys pick an option, Enter answers.
+/// Returns whether each answer was right if the quiz was finished rather
+/// than abandoned.
+pub fn run(quiz: &Quiz) -> io::Result<Option<Vec<bool>>> {
+    let terminal = ratatui::init();
+    let result = QuizTui::new(quiz).run(terminal);
+    ratatui::restore();
+    result
+}
+
+impl<'a> QuizTui<'a> {
+    fn new(quiz: &'a Quiz) -> Self {
+        Self {
+            quiz,
+            current: 0,
+            options: ListState::default().with_selected(Some(0)),
+            correct: 0,
+            answers: Vec::new(),
+            screen: Screen::Question,
+            start: Instant::now(),
+            elapsed_secs: 0,
+        }
+    }
+
+    fn run(mut self, mut terminal: DefaultTerminal) -> io::Result<Option<Vec<bool>>> {
+        loop {
+            terminal.draw(|frame| self.draw(frame))?;
+            let Event::Key(key) = event::read()? else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            if matches!(key.code, KeyCode::Esc | KeyCode::Char('q')) {
+                return Ok(matches!(self.screen, Screen::Results).then_some(self.answers));
+            }
+            match self.screen {
+                Screen::Question => match key.code {
+                    KeyCode::Up | KeyCode::Char('k') => self.options.select_previous(),
+                    KeyCode::Down | KeyCode::Char('j') => self.options.select_next(),
+                    KeyCode::Char(c @ '1'..='4') => {
+                        self.options.select(c.to_digit(10).map(|d| d as usize - 1))
+                    }
+                    KeyCode::Enter => self.answer(),
+                    _ => {}
+                },
+                Screen::Feedback(_) => {
+                    if key.code == KeyCode::Enter {
+                        self.next_question();
+                    }
+                }
+                Screen::Results => {
+                    if key.code == KeyCode::Enter {
+                        return Ok(Some(self.answers));
+                    }
+                }
+            }
+        }
+    }
+
+    fn answer(&mut self) {
+        let Some(selected) = self.options.selected() else {
+            return;
+        };
+        let right = self.quiz.questions[self.current].is_correct(selected + 1);
+        if right {
+            self.correct += 1;
+        }
+        self.answers.push(right);
+        self.screen = Screen::Feedback(right);
+    }
+
+    fn next_question(&mut self) {
+        self.current += 1;
+        self.options.select(Some(0));
+        if self.current < self.quiz.questions.len() {
+            self.screen = Screen::Question;
+        } else {
+            self.elapsed_secs = self.start.elapsed().as_secs();
+            self.screen = Screen::Results;
+        }
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let block = Block::bordered()
+            .border_type(BorderType::Rounded)
+            .title(Line::from(format!(" {} ", self.quiz.title)).centered())
+            .padding(Padding::horizontal(1))
+            .fg(Color::Cyan);
+        let inner = block.inner(frame.area());
+        frame.render_widget(block, frame.area());
+
+        let [progress_area, body_area, hint_area] = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Fill(1),
+            Constraint::Length(1),
+        ])
+        .spacing(1)
+        .areas(inner);
+
+        let total = self.quiz.questions.len();
+        let answered = match self.screen {
+            Screen::Question => self.current,
+            Screen::Feedback(_) | Screen::Results => (self.current + 1).min(total),
+        };
+        frame.render_widget(
+            Gauge::default()
+                .gauge_style(Style::default().fg(Color::Magenta))
+                .ratio(answered as f64 / total as f64)
+                .label(format!("{}/{}", answered, total)),
+            progress_area,
+        );
+
+        let hint = match self.screen {
+            Screen::Results => {
+                self.draw_results(frame, body_area);
+                "Enter/Esc quit"
+            }
+            _ => {
+                self.draw_question(frame, body_area);
+                match self.screen {
+                    Screen::Feedback(_) if self.current + 1 == total => "Enter results · Esc quit",
+                    Screen::Feedback(_) => "Enter next question · Esc quit",
+                    _ => "↑/↓ choose · 1-4 jump · Enter answer · Esc quit",
+                }
+            }
+        };
+        frame.render_widget(Line::from(hint).dark_gray(), hint_area);
+    }
+
+    fn draw_question(&mut self, frame: &mut Frame, area: Rect) {
+        let q = &self.quiz.questions[self.current];
+        let [text_area, options_area, feedback_area] = Layout::vertical([
+            Constraint::Length(2),
+            Constraint::Length(q.options.len() as u16),
+            Constraint::Fill(1),
+        ])
+        .spacing(1)
+        .areas(area);
+
+        frame.render_widget(
+            Paragraph::new(Line::from(vec![
+                format!("Q{}: ", self.current + 1).magenta().bold(),
+                q.text.white().bold(),
+            ]))
+            .wrap(Wrap { trim: true }),
+            text_area,
+        );
+
+        let items = q
+            .options
+            .iter()
+            .enumerate()
+            .map(|(i, opt)| Line::from(format!("{}. {}", i + 1, opt)).white());
+        frame.render_stateful_widget(
+            List::new(items)
+                .highlight_symbol("> ")
+                .highlight_style(Style::default().fg(Color::Yellow).bold()),
+            options_area,
+            &mut self.options,
+        );
+
+        let feedback = match self.screen {
+            Screen::Feedback(true) => Line::from("✓ Correct!".green().bold()),
+            Screen::Feedback(false) => Line::from(vec![
+                "✗ Wrong! ".red().bold(),
+                format!("(correct: {})", q.options[q.correct - 1]).dark_gray(),
+            ]),
+            _ => return,
+        };
+        frame.render_widget(feedback, feedback_area);
+    }
+
+    fn draw_results(&self, frame: &mut Frame, area: Rect) {
+        let total = self.quiz.questions.len();
+        let pct = self.correct as f32 / total as f32 * 100.0;
+        let verdict = if self.quiz.passed(self.correct) {
+            "🎉  You passed!".green().bold()
+        } else {
+            "😞  You did not pass. Better luck next time!".red().bold()
+        };
+        let text = Text::from(vec![
+            Line::from("📊  Results".white().bold().underlined()),
+            Line::from(""),
+            Line::from(format!("Score: {}/{} ({:.0}%)", self.correct, total, pct)).white(),
+            Line::from(format!("Time:  {}s", self.elapsed_secs)).white(),
+            Line::from(""),
+            Line::from(verdict),
+        ]);
+        frame.render_widget(Paragraph::new(text), area);
+    }
+}
@@ -1,32 +1,103 @@
-use std::time::Duration;
+use std::{
+    fmt,
+    time::{Duration, Instant},
+};
 
 use color_eyre::Result;
 use ratatui::{
     DefaultTerminal, Frame,
     crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
-    layout::{Constraint, Layout, Rect},
+    layout::{Constraint, Flex, Layout, Margin, Rect},
     style::{Color, Style, Stylize},
     symbols,
-    widgets::{Axis, Block, Chart, Clear, Dataset, GraphType, Row, Table, TableState},
+    widgets::{Axis, Block, Chart, Clear, Dataset, GraphType, Paragraph, Row, Table, TableState},
 };
-use sysinfo::System;
+use sysinfo::{Pid, ProcessRefreshKind, ProcessesToUpdate, System, UpdateKind, Users};
 use tui_textarea::TextArea;
+use unicode_width::UnicodeWidthChar;
+
+use crate::{info::MachineInfo, memory};
+
+/// The limit `t` switches on when none was given with `--top`.
+const DEFAULT_TOP: usize = 10;
+/// How much `+` and `-` change the limit by.
+const TOP_STEP: usize = 5;
+/// Samples averaged when `m` turns smoothing on without `--smooth`.
+const DEFAULT_SMOOTH: usize = 5;
+/// The CPU chart's y-axis tops out at the next multiple of this above the
+/// busiest sample shown, zooming in while usage stays low.
+const CPU_SCALE_STEP: f64 = 20.0;
+/// How far `←` and `→` scroll the full command lines.
+const COMMAND_STEP: usize = 8;
+/// How long a message stays under the process table.
+const MESSAGE_FOR: Duration = Duration::from_secs(3);
+const MIB: f64 = 1024.0 * 1024.0;
 
 #[derive(Debug, Default)]
 pub struct App {
     running: bool,
     system: sysinfo::System,
+    users: Users,
+    machine: MachineInfo,
     cpu: Vec<(f64, f64)>,
     table_state: TableState,
     textarea: TextArea<'static>,
     search: bool,
+    /// Only this user's processes are listed when set.
+    user: Option<String>,
+    /// Only the busiest processes are listed when set.
+    top: Option<usize>,
+    /// The limit to restore when `t` turns it back on.
+    last_top: usize,
+    /// Asking "Quit?" after `q` or Esc outside the search box.
+    confirm_quit: bool,
+    /// Rows in the process table as last drawn, to keep the selection in range.
+    row_count: usize,
+    /// Whether the CPU chart plots a moving average instead of raw samples.
+    smoothing: bool,
+    /// How many samples the moving average spans.
+    smooth_window: usize,
+    /// Whether the name column shows each process's full command line.
+    show_command: bool,
+    /// Characters scrolled off the left of the command lines.
+    command_offset: usize,
+    /// The process on each row of the table as last drawn, for `y`.
+    pids: Vec<Pid>,
+    /// A note shown under the process table, and when it was posted.
+    message: Option<(String, Instant)>,
+    clipboard: Clipboard,
+}
+
+/// The system clipboard, opened by the first `y` and kept for the rest of
+/// the session rather than reconnecting on every copy.
+#[derive(Default)]
+struct Clipboard(Option<arboard::Clipboard>);
+
+impl Clipboard {
+    fn get(&mut self) -> Result<&mut arboard::Clipboard, arboard::Error> {
+        match &mut self.0 {
+            Some(clipboard) => Ok(clipboard),
+            slot @ None => Ok(slot.insert(arboard::Clipboard::new()?)),
+        }
+    }
+}
+
+impl fmt::Debug for Clipboard {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Clipboard")
+            .field(&if self.0.is_some() { "open" } else { "closed" })
+            .finish()
+    }
 }
 
 impl App {
-    pub fn new() -> Self {
+    pub fn new(top: Option<usize>, user: Option<String>, smooth: Option<usize>) -> Self {
+        let system = System::new_all();
         Self {
             running: false,
-            system: System::new_all(),
+            machine: MachineInfo::new(&system),
+            system,
+            users: Users::new_with_refreshed_list(),
             cpu: Vec::new(),
             table_state: TableState::default(),
             textarea: {
@@ -35,6 +106,18 @@ impl App {
                 textarea
             },
             search: false,
+            user,
+            top,
+            last_top: top.unwrap_or(DEFAULT_TOP),
+            confirm_quit: false,
+            row_count: 0,
+            smoothing: smooth.is_some(),
+            smooth_window: smooth.unwrap_or(DEFAULT_SMOOTH),
+            show_command: false,
+            command_offset: 0,
+            pids: Vec::new(),
+            message: None,
+            clipboard: Clipboard::default(),
         }
     }
 
@@ -44,8 +127,20 @@ impl App {
         while self.running {
             terminal.draw(|frame| {
                 if frame.count() % 60 == 0 {
-                    self.system
-                        .refresh_processes(sysinfo::ProcessesToUpdate::All, true);
+                    self.system.refresh_processes_specifics(
+                        ProcessesToUpdate::All,
+                        true,
+                        ProcessRefreshKind::nothing()
+                            .with_memory()
+                            .with_cpu()
+                            .with_disk_usage()
+                            .with_exe(UpdateKind::OnlyIfNotSet)
+                            .with_cmd(UpdateKind::OnlyIfNotSet)
+                            .with_tasks()
+                            .with_user(UpdateKind::OnlyIfNotSet),
+                    );
+                    self.users.refresh();
+                    self.system.refresh_memory();
                 }
                 self.system.refresh_cpu_all();
 
@@ -71,28 +166,43 @@ impl App {
             Layout::horizontal([Constraint::Percentage(50), Constraint::Percentage(50)])
                 .areas(second);
 
+        let cpu = if self.smoothing {
+            moving_average(&self.cpu, self.smooth_window)
+        } else {
+            self.cpu.clone()
+        };
+        let busiest = cpu.iter().map(|&(_, usage)| usage).fold(0_f64, f64::max);
+        let y_max =
+            ((busiest / CPU_SCALE_STEP).ceil() * CPU_SCALE_STEP).clamp(CPU_SCALE_STEP, 100.0);
+
         let datasets = vec![
             Dataset::default()
                 .marker(symbols::Marker::Braille)
                 .graph_type(GraphType::Line)
                 .style(Style::default().cyan())
-                .data(&self.cpu),
+                .data(&cpu),
         ];
 
         let x_axis = Axis::default()
             .bounds([0_f64, self.cpu.len() as f64])
             .style(Style::default().cyan());
         let y_axis = Axis::default()
-            .bounds([0_f64, 100_f64])
+            .bounds([0_f64, y_max])
+            .labels(["0%".to_string(), format!("{}%", y_max)])
             .style(Style::default().cyan());
 
+        let title = if self.smoothing {
+            format!("CPU · average of {}", self.smooth_window)
+        } else {
+            "CPU".to_string()
+        };
         let chart = Chart::new(datasets)
-            .block(Block::bordered().title("CPU"))
+            .block(Block::bordered().title(title))
             .x_axis(x_axis)
             .y_axis(y_axis);
 
-        frame.render_widget(Block::bordered(), left);
-        frame.render_widget(Block::bordered(), right);
+        self.machine.render(frame, left);
+        memory::render(&self.system, frame, right);
 
         frame.render_widget(chart, top);
         // frame.render_widget(Block::bordered(), bottom);
@@ -101,23 +211,37 @@ impl App {
         if self.search {
             self.render_search(frame, bottom);
         }
+        if self.confirm_quit {
+            self.render_quit_confirm(frame);
+        }
     }
 
     fn render_processes(&mut self, frame: &mut Frame, area: Rect) {
         let mut rows: Vec<_> = Vec::new();
         for (pid, process) in self.system.processes() {
-            let name = process.name().to_string_lossy().to_string();
+            let name = match process.cmd() {
+                cmd if self.show_command && !cmd.is_empty() => cmd
+                    .iter()
+                    .map(|arg| arg.to_string_lossy())
+                    .collect::<Vec<_>>()
+                    .join(" "),
+                _ => process.name().to_string_lossy().to_string(),
+            };
             let cpu = process.cpu_usage();
-            let row = vec![pid.to_string(), name, cpu.to_string()];
+            let row = vec![pid.to_string(), self.owner(process), name, cpu.to_string()];
             rows.push(row);
         }
 
         rows.sort_by(|a, b| {
-            let a = a[2].parse::<f32>().unwrap_or(0.0);
-            let b = b[2].parse::<f32>().unwrap_or(0.0);
+            let a = a[3].parse::<f32>().unwrap_or(0.0);
+            let b = b[3].parse::<f32>().unwrap_or(0.0);
             b.partial_cmp(&a).unwrap()
         });
 
+        if let Some(user) = &self.user {
+            rows.retain(|row| row[1] == *user);
+        }
+
         let text = self.textarea.lines().first().unwrap();
 
         rows.retain(|row| {
@@ -125,40 +249,158 @@ impl App {
                 .any(|cell| cell.to_lowercase().contains(&text.to_lowercase()))
         });
 
-        let table = Table::new(
-            rows.into_iter().map(Row::new).collect::<Vec<Row>>(),
-            [
-                Constraint::Max(10),
-                Constraint::Fill(1),
-                Constraint::Fill(1),
-            ],
-        )
-        .block(Block::bordered().title("Processes"))
-        .row_highlight_style(Style::default().bg(Color::DarkGray))
-        .highlight_symbol(">>")
-        .header(Row::new(vec!["PID", "Name", "CPU"]).style(Style::default().bold()));
+        let mut title = match self.top {
+            Some(top) if top < rows.len() => format!("Processes (top {} of {})", top, rows.len()),
+            _ => format!("Processes ({})", rows.len()),
+        };
+        if let Some(user) = &self.user {
+            title.push_str(&format!(" · user {}", user));
+        }
+        if let Some(top) = self.top {
+            rows.truncate(top);
+        }
+
+        if self.show_command {
+            title.push_str(" · full commands (←/→ scroll)");
+        }
+
+        self.row_count = rows.len();
+        self.pids = rows.iter().filter_map(|row| row[0].parse().ok()).collect();
+        self.clamp_selection();
+
+        let widths = [
+            Constraint::Max(10),
+            Constraint::Max(12),
+            Constraint::Fill(1),
+            Constraint::Fill(1),
+        ];
+        // The name column's width once the border and the highlight symbol
+        // are taken off, laid out as the table will lay it out.
+        let inner = area.inner(Margin::new(1, 1));
+        let columns = Layout::horizontal(widths).spacing(1).split(Rect {
+            width: inner.width.saturating_sub(2),
+            ..inner
+        });
+        let skip = if self.show_command {
+            self.command_offset
+        } else {
+            0
+        };
+        for row in &mut rows {
+            row[2] = fit(&row[2], skip, columns[2].width as usize);
+        }
+
+        let mut block = Block::bordered().title(title);
+        if let Some((message, _)) = self
+            .message
+            .as_ref()
+            .filter(|(_, posted)| posted.elapsed() < MESSAGE_FOR)
+        {
+            block = block.title_bottom(format!(" {} ", message));
+        }
+        let table = Table::new(rows.into_iter().map(Row::new).collect::<Vec<Row>>(), widths)
+            .block(block)
+            .row_highlight_style(Style::default().bg(Color::DarkGray))
+            .highlight_symbol(">>")
+            .header(Row::new(vec!["PID", "User", "Name", "CPU"]).style(Style::default().bold()));
 
         frame.render_stateful_widget(table, area, &mut self.table_state);
     }
 
+    /// The owning user's name, or their UID when it isn't in the user list,
+    /// or "-" where the platform reports no owner.
+    fn owner(&self, process: &sysinfo::Process) -> String {
+        match process.user_id() {
+            Some(uid) => match self.users.get_user_by_id(uid) {
+                Some(user) => user.name().to_string(),
+                None => uid.to_string(),
+            },
+            None => "-".to_string(),
+        }
+    }
+
+    /// Copies the selected process's PID, name, CPU and memory use to the
+    /// clipboard, saying under the table how that went. There may be no
+    /// clipboard to copy to, as over SSH or without a display.
+    fn copy_selected(&mut self) {
+        let Some(process) = self
+            .table_state
+            .selected()
+            .and_then(|row| self.pids.get(row))
+            .and_then(|pid| self.system.process(*pid))
+        else {
+            return;
+        };
+        let line = format!(
+            "PID {} · {} · CPU {:.1}% · MEM {:.1} MiB",
+            process.pid(),
+            process.name().to_string_lossy(),
+            process.cpu_usage(),
+            process.memory() as f64 / MIB
+        );
+        let copied = self
+            .clipboard
+            .get()
+            .and_then(|clipboard| clipboard.set_text(&line));
+        let message = match copied {
+            Ok(()) => format!("Copied: {}", line),
+            Err(e) => format!("Couldn't copy: {}", e),
+        };
+        self.message = Some((message, Instant::now()));
+    }
+
+    /// Moves the user filter on to the next owner, in name order, and back to
+    /// everyone after the last.
+    fn cycle_user(&mut self) {
+        let mut owners: Vec<String> = self
+            .system
+            .processes()
+            .values()
+            .map(|p| self.owner(p))
+            .collect();
+        owners.sort();
+        owners.dedup();
+        self.user = match &self.user {
+            None => owners.into_iter().next(),
+            Some(current) => owners.into_iter().find(|o| o > current),
+        };
+    }
+
     fn render_search(&mut self, frame: &mut Frame, area: Rect) {
+        // Stay inside the process table's border, however small it gets.
+        let inner = area.inner(Margin::new(1, 1));
         let search_area = Rect {
-            x: area.x + 1,
-            y: area.y + 1,
-            width: area.width - 2,
-            height: 3,
+            height: inner.height.min(3),
+            ..inner
         };
 
         frame.render_widget(Clear, search_area);
         frame.render_widget(&self.textarea, search_area);
     }
 
+    fn render_quit_confirm(&self, frame: &mut Frame) {
+        let [area] = Layout::vertical([Constraint::Length(3)])
+            .flex(Flex::Center)
+            .areas(frame.area());
+        let [area] = Layout::horizontal([Constraint::Length(24)])
+            .flex(Flex::Center)
+            .areas(area);
+
+        frame.render_widget(Clear, area);
+        frame.render_widget(
+            Paragraph::new("Quit? (y/n)")
+                .centered()
+                .block(Block::bordered().yellow()),
+            area,
+        );
+    }
+
     fn handle_crossterm_events(&mut self) -> Result<()> {
         if event::poll(Duration::from_millis(60))? {
             match event::read()? {
                 Event::Key(key) if key.kind == KeyEventKind::Press => self.on_key_event(key),
                 Event::Mouse(_) => {}
-                Event::Resize(_, _) => {}
+                Event::Resize(_, _) => self.clamp_selection(),
                 _ => {}
             }
         }
@@ -167,15 +409,61 @@ impl App {
     }
 
     fn on_key_event(&mut self, key: KeyEvent) {
+        if let (KeyModifiers::CONTROL, KeyCode::Char('c') | KeyCode::Char('C')) =
+            (key.modifiers, key.code)
+        {
+            self.quit();
+            return;
+        }
+        // `y` or a second `q` confirms; anything else cancels.
+        if self.confirm_quit {
+            self.confirm_quit = false;
+            if let KeyCode::Char('y' | 'Y' | 'q') = key.code {
+                self.quit();
+            }
+            return;
+        }
+        // While searching, keys only go to the search box; Esc or Enter
+        // closes it and keeps the filter.
         if self.search {
-            self.textarea.input(key);
+            match key.code {
+                KeyCode::Esc | KeyCode::Enter => self.search = false,
+                _ => {
+                    self.textarea.input(key);
+                }
+            }
+            return;
         }
         match (key.modifiers, key.code) {
-            (_, KeyCode::Esc | KeyCode::Char('q'))
-            | (KeyModifiers::CONTROL, KeyCode::Char('c') | KeyCode::Char('C')) => self.quit(),
+            (_, KeyCode::Esc | KeyCode::Char('q')) => {
+                self.confirm_quit = true;
+            }
             (_, KeyCode::Char('s')) => {
                 self.search = !self.search;
             }
+            (_, KeyCode::Char('u')) => self.cycle_user(),
+            (_, KeyCode::Char('y')) => self.copy_selected(),
+            (_, KeyCode::Char('m')) => self.smoothing = !self.smoothing,
+            (_, KeyCode::Char('c')) => {
+                self.show_command = !self.show_command;
+                self.command_offset = 0;
+            }
+            (_, KeyCode::Right) if self.show_command => self.command_offset += COMMAND_STEP,
+            (_, KeyCode::Left) if self.show_command => {
+                self.command_offset = self.command_offset.saturating_sub(COMMAND_STEP)
+            }
+            (_, KeyCode::Char('t')) => {
+                self.top = match self.top {
+                    Some(_) => None,
+                    None => Some(self.last_top),
+                };
+            }
+            (_, KeyCode::Char('+') | KeyCode::Char('=')) => {
+                self.resize_top(self.last_top + TOP_STEP)
+            }
+            (_, KeyCode::Char('-')) => {
+                self.resize_top(self.last_top.saturating_sub(TOP_STEP).max(1))
+            }
             (_, KeyCode::Char('j')) => {
                 self.table_state.select_next();
             }
@@ -186,7 +474,66 @@ impl App {
         }
     }
 
+    /// Keeps a row selected and within the table; the table itself scrolls it
+    /// into view when drawn at the new size.
+    fn clamp_selection(&mut self) {
+        let last = self.row_count.checked_sub(1);
+        let selected = self.table_state.selected().unwrap_or(0);
+        self.table_state.select(last.map(|last| selected.min(last)));
+    }
+
+    /// Sets the limit and switches it on.
+    fn resize_top(&mut self, top: usize) {
+        self.last_top = top;
+        self.top = Some(top);
+    }
+
     fn quit(&mut self) {
         self.running = false
     }
 }
+
+/// `text` from its `skip`th character on, cut to `width` columns, with an
+/// ellipsis wherever some of it was left out.
+fn fit(text: &str, skip: usize, width: usize) -> String {
+    let mut fitted = String::new();
+    let mut used = 0;
+    let mut chars = text.chars().skip(skip).peekable();
+    if skip > 0 && chars.peek().is_some() {
+        fitted.push('…');
+        used += 1;
+    }
+    while let Some(c) = chars.next() {
+        let w = c.width().unwrap_or(0);
+        // Leave room for the ellipsis unless this is the last character.
+        let room = if chars.peek().is_some() {
+            width.saturating_sub(1)
+        } else {
+            width
+        };
+        if used + w > room {
+            fitted.push('…');
+            break;
+        }
+        fitted.push(c);
+        used += w;
+    }
+    fitted
+}
+
+/// Each sample replaced by the mean of it and up to `window - 1` before it.
+fn moving_average(samples: &[(f64, f64)], window: usize) -> Vec<(f64, f64)> {
+    let window = window.max(1);
+    let mut sum = 0.0;
+    samples
+        .iter()
+        .enumerate()
+        .map(|(i, &(x, y))| {
+            sum += y;
+            if i >= window {
+                sum -= samples[i - window].1;
+            }
+            (x, sum / (i + 1).min(window) as f64)
+        })
+        .collect()
+}
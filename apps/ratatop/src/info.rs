@@ -0,0 +1,82 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::Stylize,
+    text::{Line, Span},
+    widgets::{Block, Paragraph},
+};
+use sysinfo::System;
+
+/// Facts about the machine that don't change while ratatop runs, read once
+/// at startup.
+#[derive(Debug, Default)]
+pub struct MachineInfo {
+    host: String,
+    os: String,
+    kernel: String,
+    boot_time: u64,
+    physical_cores: Option<usize>,
+    logical_cores: usize,
+}
+
+impl MachineInfo {
+    pub fn new(system: &System) -> Self {
+        let unknown = || "unknown".to_string();
+        Self {
+            host: System::host_name().unwrap_or_else(unknown),
+            os: System::long_os_version().unwrap_or_else(unknown),
+            kernel: System::kernel_version().unwrap_or_else(unknown),
+            boot_time: System::boot_time(),
+            physical_cores: System::physical_core_count(),
+            logical_cores: system.cpus().len(),
+        }
+    }
+
+    /// Seconds since boot, from the boot time read at startup.
+    fn uptime(&self) -> u64 {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs());
+        now.saturating_sub(self.boot_time)
+    }
+
+    pub fn render(&self, frame: &mut Frame, area: Rect) {
+        let cores = match self.physical_cores {
+            Some(physical) => format!("{} physical / {} logical", physical, self.logical_cores),
+            None => format!("{} logical", self.logical_cores),
+        };
+        let rows = [
+            ("Host", self.host.clone()),
+            ("OS", self.os.clone()),
+            ("Kernel", self.kernel.clone()),
+            ("Uptime", format_uptime(self.uptime())),
+            ("Cores", cores),
+        ];
+        let lines: Vec<Line> = rows
+            .into_iter()
+            .map(|(label, value)| {
+                Line::from(vec![
+                    Span::from(format!("{:<8}", label)).bold(),
+                    Span::from(value),
+                ])
+            })
+            .collect();
+
+        frame.render_widget(
+            Paragraph::new(lines).block(Block::bordered().title("System")),
+            area,
+        );
+    }
+}
+
+/// "3d 4h 05m", dropping the days when there are none.
+fn format_uptime(secs: u64) -> String {
+    let (days, hours, minutes) = (secs / 86_400, secs / 3_600 % 24, secs / 60 % 60);
+    if days > 0 {
+        format!("{}d {}h {:02}m", days, hours, minutes)
+    } else {
+        format!("{}h {:02}m", hours, minutes)
+    }
+}
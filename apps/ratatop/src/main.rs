@@ -1,12 +1,40 @@
 use app::App;
+use clap::Parser;
 
 pub mod app;
+pub mod info;
+pub mod memory;
+
+#[derive(Parser, Debug)]
+#[command(
+    name = "ratatop",
+    version,
+    about = "A small process monitor for the terminal."
+)]
+struct Cli {
+    /// Only show the N busiest processes (toggle with `t`, adjust with `+`/`-`)
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u16).range(1..))]
+    top: Option<u16>,
+
+    /// Only show processes owned by this user (cycle through owners with `u`)
+    #[arg(long, value_name = "NAME")]
+    user: Option<String>,
+
+    /// Plot the CPU chart as a moving average over the last N samples (toggle with `m`)
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u16).range(1..))]
+    smooth: Option<u16>,
+}
 
 fn main() -> color_eyre::Result<()> {
     color_eyre::install()?;
+    let cli = Cli::parse();
     let terminal = ratatui::init();
 
-    let app = App::new();
+    let app = App::new(
+        cli.top.map(usize::from),
+        cli.user,
+        cli.smooth.map(usize::from),
+    );
 
     let result = app.run(terminal);
 
@@ -0,0 +1,68 @@
+use ratatui::{
+    Frame,
+    layout::{Constraint, Layout, Rect},
+    style::{Color, Stylize},
+    text::{Line, Span},
+    widgets::{Block, Gauge},
+};
+use sysinfo::System;
+
+const GIB: f64 = 1024.0 * 1024.0 * 1024.0;
+
+/// Memory and swap gauges over the 1, 5 and 15 minute load averages.
+pub fn render(system: &System, frame: &mut Frame, area: Rect) {
+    let block = Block::bordered().title("Memory & Load");
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+    let [memory, swap, load] = Layout::vertical([Constraint::Length(1); 3]).areas(inner);
+
+    render_gauge(
+        frame,
+        memory,
+        "Mem ",
+        system.used_memory(),
+        system.total_memory(),
+        Color::Green,
+    );
+    render_gauge(
+        frame,
+        swap,
+        "Swap",
+        system.used_swap(),
+        system.total_swap(),
+        Color::Magenta,
+    );
+
+    // sysinfo reports zeros where there is no load average, as on Windows.
+    let averages = if cfg!(windows) {
+        "n/a".to_string()
+    } else {
+        let load = System::load_average();
+        format!("{:.2} {:.2} {:.2}", load.one, load.five, load.fifteen)
+    };
+    frame.render_widget(
+        Line::from(vec![Span::from("Load ").bold(), Span::from(averages)]),
+        load,
+    );
+}
+
+fn render_gauge(frame: &mut Frame, area: Rect, label: &str, used: u64, total: u64, color: Color) {
+    let [label_area, gauge_area] =
+        Layout::horizontal([Constraint::Length(5), Constraint::Fill(1)]).areas(area);
+    frame.render_widget(Span::from(label).bold(), label_area);
+
+    let text = if total == 0 {
+        "none".to_string()
+    } else {
+        format!("{:.1} / {:.1} GiB", used as f64 / GIB, total as f64 / GIB)
+    };
+    let ratio = if total == 0 {
+        0.0
+    } else {
+        (used as f64 / total as f64).min(1.0)
+    };
+    frame.render_widget(
+        Gauge::default().gauge_style(color).ratio(ratio).label(text),
+        gauge_area,
+    );
+}
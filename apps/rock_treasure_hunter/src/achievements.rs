@@ -0,0 +1,167 @@
+use chrono::Local;
+use colored::*;
+
+use crate::{
+    Player,
+    display::{icon, say},
+    encyclopedia,
+    treasure::{Rarity, catalog},
+};
+
+pub struct Achievement {
+    pub id: &'static str,
+    pub name: &'static str,
+    pub description: &'static str,
+    pub unlocked: fn(&Player) -> bool,
+}
+
+pub const ACHIEVEMENTS: &[Achievement] = &[
+    Achievement {
+        id: "first_chest",
+        name: "Treasure Seeker",
+        description: "Open your first chest",
+        unlocked: |p| p.chests_opened >= 1,
+    },
+    Achievement {
+        id: "first_legendary",
+        name: "Legend Born",
+        description: "Find a Legendary treasure",
+        unlocked: |p| p.collection.iter().any(|t| t.rarity >= Rarity::Legendary),
+    },
+    Achievement {
+        id: "coins_1000",
+        name: "Coin Hoarder",
+        description: "Earn 1,000 coins in total",
+        unlocked: |p| p.total_coins_earned >= 1000,
+    },
+    Achievement {
+        id: "rocks_100_day",
+        name: "Rockbreaker",
+        description: "Hit 100 rocks in a single day",
+        unlocked: |p| p.today.swings >= 100,
+    },
+    Achievement {
+        id: "common_set",
+        name: "Humble Beginnings",
+        description: "Collect every Common treasure",
+        unlocked: |p| {
+            catalog()
+                .iter()
+                .filter(|d| d.rarity == Rarity::Common)
+                .all(|d| p.collection.iter().any(|t| t.name == d.name))
+        },
+    },
+    Achievement {
+        id: "encyclopedia",
+        name: "Encyclopedist",
+        description: "Discover every treasure in the encyclopedia",
+        unlocked: encyclopedia::is_complete,
+    },
+];
+
+/// Unlocks any newly earned achievements and celebrates each one.
+pub fn check(player: &mut Player) {
+    for a in ACHIEVEMENTS {
+        if player.achievements.contains_key(a.id) || !(a.unlocked)(player) {
+            continue;
+        }
+        player.achievements.insert(a.id.to_string(), Local::now());
+        say!(
+            "\n{} Achievement unlocked: {} — {}",
+            icon("🏆", "[TROPHY]").yellow(),
+            a.name.bright_yellow().bold(),
+            a.description
+        );
+    }
+}
+
+pub fn view(player: &Player) {
+    say!(
+        "\n{} Achievements:",
+        icon("🏆", "[TROPHY]").bright_white().bold()
+    );
+    for a in ACHIEVEMENTS {
+        match player.achievements.get(a.id) {
+            Some(at) => say!(
+                "  {} {} — {} {}",
+                icon("✔", "[x]").green(),
+                a.name.bold(),
+                a.description,
+                at.format("(%Y-%m-%d %H:%M)").to_string().dimmed()
+            ),
+            None => say!(
+                "  {}",
+                format!("{} {} — {}", icon("✘", "[ ]"), a.name, a.description).dimmed()
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{difficulty::Difficulty, treasure::Treasure};
+
+    fn owning(player: &mut Player, rarity: Option<Rarity>) {
+        for def in catalog()
+            .iter()
+            .filter(|d| rarity.as_ref().is_none_or(|r| d.rarity == *r))
+        {
+            player.collection.push(Treasure {
+                name: def.name.clone(),
+                rarity: def.rarity.clone(),
+            });
+        }
+    }
+
+    /// Gives `player` just enough to earn the achievement `id`.
+    fn earn(player: &mut Player, id: &str) {
+        match id {
+            "first_chest" => player.chests_opened = 1,
+            "first_legendary" => owning(player, Some(Rarity::Legendary)),
+            "coins_1000" => player.total_coins_earned = 1000,
+            "rocks_100_day" => player.today.swings = 100,
+            "common_set" => owning(player, Some(Rarity::Common)),
+            "encyclopedia" => owning(player, None),
+            _ => panic!("no way to earn achievement '{}' in the test", id),
+        }
+    }
+
+    #[test]
+    fn new_player_has_no_achievements() {
+        let mut player = Player::new("Tester", Difficulty::Normal);
+        check(&mut player);
+        assert!(player.achievements.is_empty());
+    }
+
+    #[test]
+    fn every_achievement_can_trigger() {
+        for a in ACHIEVEMENTS {
+            let mut player = Player::new("Tester", Difficulty::Normal);
+            assert!(
+                !(a.unlocked)(&player),
+                "{} is unlocked from the start",
+                a.id
+            );
+            earn(&mut player, a.id);
+            assert!((a.unlocked)(&player), "{} didn't unlock", a.id);
+            check(&mut player);
+            assert!(player.achievements.contains_key(a.id));
+        }
+    }
+
+    #[test]
+    fn one_short_does_not_trigger() {
+        let mut player = Player::new("Tester", Difficulty::Normal);
+        player.total_coins_earned = 999;
+        player.today.swings = 99;
+        check(&mut player);
+        assert!(player.achievements.is_empty());
+    }
+
+    #[test]
+    fn ids_are_unique() {
+        let ids: std::collections::BTreeSet<_> = ACHIEVEMENTS.iter().map(|a| a.id).collect();
+        assert_eq!(ids.len(), ACHIEVEMENTS.len());
+    }
+}
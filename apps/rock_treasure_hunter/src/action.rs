@@ -0,0 +1,134 @@
+use std::{path::PathBuf, str::FromStr};
+
+use colored::*;
+
+use crate::{
+    Game, achievements,
+    chest::ChestTier,
+    display::{icon, say},
+    encyclopedia, export,
+    gamble::{self, Bet},
+    history,
+    location::Location,
+    regen, stats,
+    treasure::catalog,
+};
+
+/// A game action, shared by the interactive menu and `--script` runs.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Action {
+    HitRock,
+    OpenChest(ChestTier),
+    Gamble(Bet),
+    ChallengeBoss,
+    HitBoss,
+    ViewCollection,
+    Encyclopedia,
+    Craft(String),
+    Repair,
+    Achievements,
+    Stats,
+    History,
+    Export(PathBuf),
+    Travel(Location),
+    EndDay,
+}
+
+/// Parses one script command: `hit`, `chest[:<tier>]` (Bronze by default),
+/// `gamble:<wager>[:<dice target>]` (a coin flip without a target), `boss`,
+/// `boss-hit`, `collection`, `encyclopedia`, `craft:<name>`, `repair`,
+/// `achievements`, `stats`, `history`, `export:<path>`, `travel:<location>`
+/// or `end-day`.
+impl FromStr for Action {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (command, arg) = match s.split_once(':') {
+            Some((command, arg)) => (command.trim(), Some(arg.trim())),
+            None => (s.trim(), None),
+        };
+        match (command, arg) {
+            ("hit", None) => Ok(Action::HitRock),
+            ("chest", None) => Ok(Action::OpenChest(ChestTier::Bronze)),
+            ("chest", Some(tier)) => ChestTier::all()
+                .into_iter()
+                .find(|t| t.slug() == tier)
+                .map(Action::OpenChest)
+                .ok_or_else(|| format!("unknown chest tier '{}'", tier)),
+            ("gamble", Some(bet)) => bet.parse().map(Action::Gamble),
+            ("boss", None) => Ok(Action::ChallengeBoss),
+            ("boss-hit", None) => Ok(Action::HitBoss),
+            ("collection", None) => Ok(Action::ViewCollection),
+            ("encyclopedia", None) => Ok(Action::Encyclopedia),
+            ("craft", Some(name)) if !name.is_empty() => Ok(Action::Craft(name.to_string())),
+            ("repair", None) => Ok(Action::Repair),
+            ("achievements", None) => Ok(Action::Achievements),
+            ("stats", None) => Ok(Action::Stats),
+            ("history", None) => Ok(Action::History),
+            ("export", Some(path)) if !path.is_empty() => Ok(Action::Export(path.into())),
+            ("travel", Some(to)) => Location::all()
+                .into_iter()
+                .find(|l| l.slug() == to)
+                .map(Action::Travel)
+                .ok_or_else(|| format!("unknown location '{}'", to)),
+            ("end-day", None) => Ok(Action::EndDay),
+            _ => Err(format!("unknown command '{}'", s.trim())),
+        }
+    }
+}
+
+pub fn apply_action(game: &mut Game, action: Action) {
+    if game.regen {
+        regen::catch_up(&mut game.player);
+    }
+    let Game {
+        player,
+        rng,
+        coin_roll,
+        ..
+    } = game;
+    match action {
+        Action::HitRock => player.hit_rock(rng, *coin_roll),
+        Action::OpenChest(tier) => player.open_chest(rng, tier),
+        Action::Gamble(bet) => gamble::play(player, rng, bet),
+        Action::ChallengeBoss => player.challenge_boss(rng),
+        Action::HitBoss => player.hit_boss(rng),
+        Action::ViewCollection => player.view_collection(),
+        Action::Encyclopedia => encyclopedia::view(player),
+        Action::Craft(name) => match player.craft(&name, catalog(), rng) {
+            Ok(crafted) => {
+                say!("{} You crafted:", icon("⚒️", "[CRAFT]").green());
+                crafted.display();
+            }
+            Err(e) => say!("{} {}", icon("🚫", "[X]").red(), e),
+        },
+        Action::Repair => player.repair_pickaxe(),
+        Action::Achievements => achievements::view(player),
+        Action::Stats => stats::view(player),
+        Action::History => history::view(player),
+        Action::Export(path) => match export::export(player, &path) {
+            Ok(()) => say!(
+                "{} Exported collection to {}.",
+                icon("💾", "[SAVE]").green(),
+                path.display()
+            ),
+            Err(e) => say!("{} {:#}", icon("🚫", "[X]").red(), e),
+        },
+        Action::Travel(to) => {
+            if player.travel(to) {
+                say!(
+                    "{} You arrive at the {}.",
+                    icon("🧭", "[MAP]").green(),
+                    to.params().name.bold()
+                );
+            } else {
+                say!(
+                    "{} The {} is still locked.",
+                    icon("🚫", "[X]").red(),
+                    to.params().name
+                );
+            }
+        }
+        Action::EndDay => player.new_day(),
+    }
+}
@@ -0,0 +1,46 @@
+use std::ops::RangeInclusive;
+
+use colored::*;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::display::{bar, icon, say};
+
+/// Strength spent to call out a Boss Rock on purpose.
+pub const CHALLENGE_COST: u32 = 10;
+/// Chance, in percent, that an ordinary swing uncovers a Boss Rock.
+pub const SPAWN_CHANCE: u8 = 2;
+const HP: RangeInclusive<u32> = 10..=20;
+const DAMAGE: RangeInclusive<u32> = 1..=3;
+pub const BONUS_COINS: RangeInclusive<u32> = 50..=100;
+
+/// A rock that takes several swings to crack. It only lasts for the day.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BossRock {
+    pub hp: u32,
+    pub max_hp: u32,
+}
+
+impl BossRock {
+    pub fn spawn(rng: &mut impl Rng) -> Self {
+        let hp = rng.random_range(HP);
+        Self { hp, max_hp: hp }
+    }
+
+    /// Deals a random amount of damage and returns it.
+    pub fn take_hit(&mut self, rng: &mut impl Rng) -> u32 {
+        let damage = rng.random_range(DAMAGE).min(self.hp);
+        self.hp -= damage;
+        damage
+    }
+
+    pub fn print_hp(&self) {
+        say!(
+            "{} Boss Rock HP {} {}/{}",
+            icon("👹", "[BOSS]").red(),
+            bar(self.hp, self.max_hp, 10).red(),
+            self.hp,
+            self.max_hp
+        );
+    }
+}
@@ -0,0 +1,108 @@
+use colored::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{location::Location, treasure::Rarity};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum ChestTier {
+    Bronze,
+    Silver,
+    Gold,
+}
+
+pub struct ChestParams {
+    pub name: &'static str,
+    pub cost: u32,
+    /// Rarity weights, in `Rarity::all()` order; they sum to 100. They scale
+    /// the location's weights, so Silver keeps the location's own odds.
+    pub weights: [u8; 5],
+}
+
+impl ChestTier {
+    pub fn all() -> [Self; 3] {
+        [Self::Bronze, Self::Silver, Self::Gold]
+    }
+
+    pub fn params(&self) -> ChestParams {
+        match self {
+            ChestTier::Bronze => ChestParams {
+                name: "Bronze",
+                cost: 30,
+                weights: [40, 30, 18, 10, 2],
+            },
+            ChestTier::Silver => ChestParams {
+                name: "Silver",
+                cost: 75,
+                weights: [20, 20, 20, 20, 20],
+            },
+            ChestTier::Gold => ChestParams {
+                name: "Gold",
+                cost: 200,
+                weights: [10, 20, 30, 30, 10],
+            },
+        }
+    }
+
+    /// The name used for this tier in scripts, e.g. `chest:gold`.
+    pub fn slug(&self) -> String {
+        self.params().name.to_lowercase()
+    }
+
+    /// Rarity weights for this chest opened at `location`.
+    pub fn weights_at(&self, location: Location) -> [u32; 5] {
+        let here = location.params().weights;
+        let tier = self.params().weights;
+        std::array::from_fn(|i| here[i] as u32 * tier[i] as u32)
+    }
+
+    /// The chance of each rarity at `location`, e.g. "Common 71% · Rare 22% · …".
+    pub fn odds(&self, location: Location) -> String {
+        let weights = self.weights_at(location);
+        let total: u32 = weights.iter().sum();
+        Rarity::all()
+            .iter()
+            .zip(weights)
+            .map(|(r, w)| {
+                let percent = w as f32 / total as f32 * 100.0;
+                let percent = if percent > 0.0 && percent < 1.0 {
+                    "<1%".to_string()
+                } else {
+                    format!("{:.0}%", percent)
+                };
+                format!("{:?} {}", r, percent).color(r.color()).to_string()
+            })
+            .collect::<Vec<_>>()
+            .join(" · ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn weights_sum_to_100() {
+        for tier in ChestTier::all() {
+            let params = tier.params();
+            let sum: u32 = params.weights.iter().map(|&w| w as u32).sum();
+            assert_eq!(sum, 100, "{} weights sum to {}", params.name, sum);
+        }
+    }
+
+    #[test]
+    fn silver_keeps_the_location_odds() {
+        for location in Location::all() {
+            let here = location.params().weights.map(|w| w as u32 * 20);
+            assert_eq!(ChestTier::Silver.weights_at(location), here);
+        }
+    }
+
+    #[test]
+    fn every_tier_can_drop_every_rarity_everywhere() {
+        for tier in ChestTier::all() {
+            for location in Location::all() {
+                assert!(tier.weights_at(location).iter().all(|&w| w > 0));
+            }
+        }
+    }
+}
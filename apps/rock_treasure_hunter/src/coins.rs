@@ -0,0 +1,46 @@
+use std::ops::RangeInclusive;
+
+use clap::ValueEnum;
+use rand::Rng;
+
+/// Chance in percent that a swing with `--coin-roll jackpot` hits the jackpot.
+pub const JACKPOT_CHANCE: u32 = 1;
+/// A jackpot pays this many times the top of the location's range.
+pub const JACKPOT_MULTIPLIER: u32 = 10;
+
+/// How the coins from a swing are picked from the location's range.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum CoinRoll {
+    /// Every amount in the range is as likely
+    #[default]
+    Uniform,
+    /// Small amounts are common and the top of the range is rare
+    Triangular,
+    /// Uniform, with a small chance of a jackpot many times the usual most
+    Jackpot,
+}
+
+/// Coins found by one swing.
+pub enum Haul {
+    Coins(u32),
+    Jackpot(u32),
+}
+
+impl CoinRoll {
+    pub fn roll(self, range: RangeInclusive<u32>, rng: &mut impl Rng) -> Haul {
+        match self {
+            CoinRoll::Uniform => Haul::Coins(rng.random_range(range)),
+            // The lower of two uniform rolls falls off linearly towards the
+            // top of the range.
+            CoinRoll::Triangular => {
+                let a = rng.random_range(range.clone());
+                let b = rng.random_range(range);
+                Haul::Coins(a.min(b))
+            }
+            CoinRoll::Jackpot if rng.random_range(0..100) < JACKPOT_CHANCE => {
+                Haul::Jackpot(range.end().max(&1) * JACKPOT_MULTIPLIER)
+            }
+            CoinRoll::Jackpot => Haul::Coins(rng.random_range(range)),
+        }
+    }
+}
@@ -0,0 +1,103 @@
+use std::ops::RangeInclusive;
+
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+/// Chosen when a game starts and kept in the save from then on.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
+pub enum Difficulty {
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+}
+
+pub struct DifficultyParams {
+    pub name: &'static str,
+    pub strength_per_day: u32,
+    /// Chest and repair prices, in percent of the normal price.
+    pub price_percent: u32,
+    /// Coins found per swing, in percent of the location's range.
+    pub coin_percent: u32,
+}
+
+impl Difficulty {
+    pub fn params(&self) -> DifficultyParams {
+        match self {
+            Difficulty::Easy => DifficultyParams {
+                name: "Easy",
+                strength_per_day: 150,
+                price_percent: 80,
+                coin_percent: 100,
+            },
+            Difficulty::Normal => DifficultyParams {
+                name: "Normal",
+                strength_per_day: 100,
+                price_percent: 100,
+                coin_percent: 100,
+            },
+            Difficulty::Hard => DifficultyParams {
+                name: "Hard",
+                strength_per_day: 75,
+                price_percent: 125,
+                coin_percent: 75,
+            },
+        }
+    }
+}
+
+impl DifficultyParams {
+    pub fn price(&self, normal: u32) -> u32 {
+        normal * self.price_percent / 100
+    }
+
+    pub fn coins_per_swing(&self, normal: &RangeInclusive<u32>) -> RangeInclusive<u32> {
+        normal.start() * self.coin_percent / 100..=normal.end() * self.coin_percent / 100
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn params() {
+        let easy = Difficulty::Easy.params();
+        assert_eq!(
+            (easy.strength_per_day, easy.price_percent, easy.coin_percent),
+            (150, 80, 100)
+        );
+        let normal = Difficulty::Normal.params();
+        assert_eq!(
+            (
+                normal.strength_per_day,
+                normal.price_percent,
+                normal.coin_percent
+            ),
+            (100, 100, 100)
+        );
+        let hard = Difficulty::Hard.params();
+        assert_eq!(
+            (hard.strength_per_day, hard.price_percent, hard.coin_percent),
+            (75, 125, 75)
+        );
+    }
+
+    #[test]
+    fn normal_is_the_default() {
+        assert_eq!(Difficulty::default(), Difficulty::Normal);
+    }
+
+    #[test]
+    fn prices_scale_by_difficulty() {
+        assert_eq!(Difficulty::Easy.params().price(75), 60);
+        assert_eq!(Difficulty::Normal.params().price(75), 75);
+        assert_eq!(Difficulty::Hard.params().price(75), 93);
+    }
+
+    #[test]
+    fn coin_ranges_scale_by_difficulty() {
+        assert_eq!(Difficulty::Easy.params().coins_per_swing(&(5..=25)), 5..=25);
+        assert_eq!(Difficulty::Hard.params().coins_per_swing(&(5..=25)), 3..=18);
+    }
+}
@@ -0,0 +1,96 @@
+use std::sync::{
+    Mutex,
+    atomic::{AtomicBool, Ordering},
+};
+
+use unicode_width::UnicodeWidthStr;
+
+static ASCII: AtomicBool = AtomicBool::new(false);
+/// Game output held back while the TUI owns the screen, waiting to be drawn.
+static CAPTURED: Mutex<Option<Vec<String>>> = Mutex::new(None);
+
+pub fn set_ascii(enabled: bool) {
+    ASCII.store(enabled, Ordering::Relaxed);
+}
+
+pub fn is_ascii() -> bool {
+    ASCII.load(Ordering::Relaxed)
+}
+
+/// Plain output for logs and dumb terminals: ASCII labels and no color codes.
+pub fn set_plain() {
+    set_ascii(true);
+    colored::control::set_override(false);
+}
+
+/// Whether the `NO_COLOR` convention (set and non-empty) asks for plain output.
+pub fn no_color_env() -> bool {
+    std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty())
+}
+
+/// Sends game output to `take_captured` instead of stdout, or back again.
+/// Turning it on twice keeps what was captured so far.
+pub fn capture_output(enabled: bool) {
+    let mut captured = CAPTURED.lock().unwrap();
+    if enabled {
+        captured.get_or_insert_with(Vec::new);
+    } else {
+        *captured = None;
+    }
+}
+
+/// The lines captured since the last call.
+pub fn take_captured() -> Vec<String> {
+    CAPTURED
+        .lock()
+        .unwrap()
+        .as_mut()
+        .map(std::mem::take)
+        .unwrap_or_default()
+}
+
+/// Prints a line of game output, or keeps it for the TUI while it's capturing.
+pub fn say_line(text: String) {
+    match CAPTURED.lock().unwrap().as_mut() {
+        Some(lines) => lines.extend(text.lines().map(str::to_string)),
+        None => println!("{}", text),
+    }
+}
+
+/// `println!` for anything an action reports, so both frontends can show it.
+macro_rules! say {
+    ($($arg:tt)*) => {
+        $crate::display::say_line(format!($($arg)*))
+    };
+}
+pub(crate) use say;
+
+/// Picks the emoji, or its ASCII label when emoji are disabled.
+pub fn icon(emoji: &'static str, label: &'static str) -> &'static str {
+    if is_ascii() { label } else { emoji }
+}
+
+/// Pads `s` with trailing spaces up to `cols` terminal columns.
+pub fn pad(s: &str, cols: usize) -> String {
+    format!("{}{}", s, " ".repeat(cols.saturating_sub(s.width())))
+}
+
+/// The label shown next to menu entry `n`.
+pub fn menu_key(n: usize) -> String {
+    const KEYCAPS: [&str; 9] = ["1️⃣", "2️⃣", "3️⃣", "4️⃣", "5️⃣", "6️⃣", "7️⃣", "8️⃣", "9️⃣"];
+    match KEYCAPS.get(n.wrapping_sub(1)) {
+        Some(cap) if !is_ascii() => pad(cap, 4),
+        _ => format!("{:>2}) ", n),
+    }
+}
+
+/// A `width`-cell bar filled in proportion to `done` out of `total`.
+pub fn bar(done: u32, total: u32, width: usize) -> String {
+    let filled = (done.min(total) as usize * width) / total.max(1) as usize;
+    let (full, empty) = if is_ascii() {
+        ("#", "-")
+    } else {
+        ("█", "░")
+    };
+    format!("[{}{}]", full.repeat(filled), empty.repeat(width - filled))
+}
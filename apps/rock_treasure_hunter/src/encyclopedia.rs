@@ -0,0 +1,74 @@
+use colored::*;
+
+use crate::{
+    Player,
+    display::{bar, icon, say},
+    treasure::{Rarity, catalog},
+};
+
+/// How many treasures of `rarity` (or of any rarity) have been discovered,
+/// out of how many the loot table defines.
+pub fn completion(player: &Player, rarity: Option<&Rarity>) -> (u32, u32) {
+    let defs = catalog()
+        .iter()
+        .filter(|d| rarity.is_none_or(|r| d.rarity == *r));
+    let (mut found, mut total) = (0, 0);
+    for def in defs {
+        total += 1;
+        if player.has_discovered(&def.name) {
+            found += 1;
+        }
+    }
+    (found, total)
+}
+
+pub fn is_complete(player: &Player) -> bool {
+    let (found, total) = completion(player, None);
+    found == total
+}
+
+fn percent(found: u32, total: u32) -> f32 {
+    found as f32 / total.max(1) as f32 * 100.0
+}
+
+/// Every treasure in the loot table by rarity, with undiscovered ones
+/// hidden as "???".
+pub fn view(player: &Player) {
+    let (found, total) = completion(player, None);
+    say!(
+        "\n{} Encyclopedia — {}/{} discovered ({:.0}%)",
+        icon("📖", "[BOOK]").bright_white().bold(),
+        found,
+        total,
+        percent(found, total)
+    );
+    for rarity in Rarity::all() {
+        let (found, total) = completion(player, Some(&rarity));
+        if total == 0 {
+            continue;
+        }
+        say!(
+            "\n{} {} {}/{} ({:.0}%)",
+            format!("{:?}", rarity).color(rarity.color()).bold(),
+            bar(found, total, 10).color(rarity.color()),
+            found,
+            total,
+            percent(found, total)
+        );
+        for def in catalog().iter().filter(|d| d.rarity == rarity) {
+            if player.has_discovered(&def.name) {
+                say!("  {}", def.name.color(rarity.color()));
+            } else {
+                say!("  {}", "???".dimmed());
+            }
+        }
+    }
+}
+
+pub fn celebrate() {
+    say!(
+        "\n{} {} You've discovered every treasure there is!",
+        icon("🌟", "[STAR]").bright_yellow(),
+        "Encyclopedia complete!".bright_yellow().bold()
+    );
+}
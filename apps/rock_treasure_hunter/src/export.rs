@@ -0,0 +1,56 @@
+use std::{fs, path::Path};
+
+use color_eyre::eyre::{Result, WrapErr};
+use serde::Serialize;
+
+use crate::{Player, treasure::Rarity};
+
+#[derive(Serialize)]
+struct ExportRow<'a> {
+    name: &'a str,
+    rarity: &'a Rarity,
+    count: usize,
+}
+
+/// Writes the stacked collection to `path`: JSON for a `.json` extension,
+/// CSV otherwise. An empty collection gives a header-only CSV or `[]`.
+pub fn export(player: &Player, path: &Path) -> Result<()> {
+    let rows: Vec<ExportRow> = player
+        .stacked_collection()
+        .into_iter()
+        .map(|(t, count)| ExportRow {
+            name: &t.name,
+            rarity: &t.rarity,
+            count,
+        })
+        .collect();
+
+    let data = if path.extension().is_some_and(|e| e == "json") {
+        serde_json::to_string_pretty(&rows)?
+    } else {
+        let mut out = String::from("name,rarity,count\n");
+        for row in &rows {
+            let rarity = format!("{:?}", row.rarity);
+            let count = row.count.to_string();
+            out.push_str(&csv_record(&[row.name, &rarity, &count]));
+        }
+        out
+    };
+    fs::write(path, data).wrap_err_with(|| format!("Failed to export to '{}'", path.display()))
+}
+
+/// One CSV line, quoting fields that contain commas, quotes or newlines and
+/// doubling any embedded quotes.
+fn csv_record(fields: &[&str]) -> String {
+    let quoted: Vec<String> = fields
+        .iter()
+        .map(|f| {
+            if f.contains([',', '"', '\n', '\r']) {
+                format!("\"{}\"", f.replace('"', "\"\""))
+            } else {
+                f.to_string()
+            }
+        })
+        .collect();
+    format!("{}\n", quoted.join(","))
+}
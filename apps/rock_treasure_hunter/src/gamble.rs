@@ -0,0 +1,149 @@
+use std::str::FromStr;
+
+use colored::*;
+use rand::Rng;
+
+use crate::{
+    Player,
+    display::{icon, say},
+};
+
+/// Most coins that can be wagered in total on a single day.
+pub const DAILY_WAGER_CAP: u32 = 200;
+const DIE_SIDES: u32 = 6;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BetKind {
+    /// Double or nothing on a coin flip.
+    CoinFlip,
+    /// Roll at least this on a six-sided die; riskier targets pay more.
+    Dice(u32),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Bet {
+    pub wager: u32,
+    pub kind: BetKind,
+}
+
+impl BetKind {
+    /// Chance of winning, as (favourable outcomes, possible outcomes).
+    fn odds(&self) -> (u32, u32) {
+        match self {
+            BetKind::CoinFlip => (1, 2),
+            BetKind::Dice(target) => (DIE_SIDES + 1 - target, DIE_SIDES),
+        }
+    }
+
+    /// Coins won on top of the returned wager; fair odds, rounded down.
+    pub fn profit(&self, wager: u32) -> u32 {
+        let (wins, outcomes) = self.odds();
+        wager * (outcomes - wins) / wins
+    }
+
+    pub fn describe(&self) -> String {
+        match self {
+            BetKind::CoinFlip => "coin flip".to_string(),
+            BetKind::Dice(target) => format!("roll {}+ on a d{}", target, DIE_SIDES),
+        }
+    }
+}
+
+/// Parses a wager typed by the player: a whole, non-zero number of coins.
+pub fn parse_wager(input: &str) -> Result<u32, String> {
+    match input.trim().parse::<u32>() {
+        Ok(0) => Err("the wager must be at least 1 coin".to_string()),
+        Ok(wager) => Ok(wager),
+        Err(_) => Err(format!("'{}' is not a whole number of coins", input.trim())),
+    }
+}
+
+/// Parses a dice target from 2 to 6; 1 would be a sure thing.
+pub fn parse_target(input: &str) -> Result<u32, String> {
+    match input.trim().parse::<u32>() {
+        Ok(target @ 2..=DIE_SIDES) => Ok(target),
+        _ => Err(format!("the dice target must be from 2 to {}", DIE_SIDES)),
+    }
+}
+
+/// `<wager>` for a coin flip or `<wager>:<target>` for a dice roll, as in
+/// the `gamble:50` and `gamble:50:5` script commands.
+impl FromStr for Bet {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (wager, target) = match s.split_once(':') {
+            Some((wager, target)) => (wager, Some(target)),
+            None => (s, None),
+        };
+        let kind = match target {
+            Some(target) => BetKind::Dice(parse_target(target)?),
+            None => BetKind::CoinFlip,
+        };
+        Ok(Bet {
+            wager: parse_wager(wager)?,
+            kind,
+        })
+    }
+}
+
+/// Plays `bet` if the player can cover it and it fits under today's cap.
+pub fn play(player: &mut Player, rng: &mut impl Rng, bet: Bet) {
+    let Bet { wager, kind } = bet;
+    if wager > player.coins {
+        say!(
+            "{} You only have {} coins to wager.",
+            icon("🚫", "[X]").red(),
+            player.coins
+        );
+        return;
+    }
+    if kind.profit(wager) == 0 {
+        say!(
+            "{} A win would pay nothing at these odds; wager more.",
+            icon("🚫", "[X]").red()
+        );
+        return;
+    }
+    let left_today = DAILY_WAGER_CAP.saturating_sub(player.today.wagered);
+    if wager > left_today {
+        say!(
+            "{} You can only wager {} more coins today (limit {}).",
+            icon("🚫", "[X]").red(),
+            left_today,
+            DAILY_WAGER_CAP
+        );
+        return;
+    }
+
+    player.today.wagered += wager;
+    let (wins, outcomes) = kind.odds();
+    let won = match kind {
+        BetKind::CoinFlip => rng.random_range(0..outcomes) < wins,
+        BetKind::Dice(target) => {
+            let roll = rng.random_range(1..=DIE_SIDES);
+            say!("{} You roll a {}.", icon("🎲", "[DICE]"), roll);
+            roll >= target
+        }
+    };
+    if won {
+        let profit = kind.profit(wager);
+        player.coins += profit;
+        player.gamble_won += profit;
+        say!(
+            "{} You win the {}! +{} coins",
+            icon("🎉", "[WIN]").bright_yellow().bold(),
+            kind.describe(),
+            profit
+        );
+    } else {
+        player.coins -= wager;
+        player.gamble_lost += wager;
+        say!(
+            "{} You lose the {}. -{} coins",
+            icon("💸", "[LOSS]").red(),
+            kind.describe(),
+            wager
+        );
+    }
+}
@@ -0,0 +1,99 @@
+use colored::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    Player,
+    display::{icon, say},
+};
+
+/// One day's counters. The current day's live on the player and are filed
+/// into its history, with `day` filled in, when the day ends.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DaySummary {
+    pub day: u32,
+    pub swings: u32,
+    pub coins: u32,
+    pub chests: u32,
+    pub treasures: u32,
+    /// Coins put on gambles; not shown, only capped.
+    pub wagered: u32,
+}
+
+impl DaySummary {
+    pub fn print(&self) {
+        say!(
+            "\n{} Day {} summary: {} swings · {} coins · {} chests · {} treasures",
+            icon("📅", "[DAY]").cyan(),
+            self.day,
+            self.swings.to_string().bold(),
+            self.coins.to_string().yellow().bold(),
+            self.chests.to_string().bold(),
+            self.treasures.to_string().bold()
+        );
+    }
+}
+
+pub fn view(player: &Player) {
+    if player.history.is_empty() {
+        say!("{} No finished days yet.", icon("📭", "[EMPTY]").dimmed());
+        return;
+    }
+    say!("\n{} History:", icon("📅", "[DAY]").bright_white().bold());
+    say!(
+        "{}",
+        format!(
+            "  {:>4} {:>7} {:>7} {:>7} {:>10}",
+            "Day", "Swings", "Coins", "Chests", "Treasures"
+        )
+        .bold()
+    );
+    for day in &player.history {
+        say!(
+            "  {:>4} {:>7} {:>7} {:>7} {:>10}",
+            day.day,
+            day.swings,
+            day.coins,
+            day.chests,
+            day.treasures
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::{play, seeded_game};
+
+    #[test]
+    fn scripted_days_are_each_summarized() {
+        let mut game = seeded_game(9);
+        game.player.coins = 100;
+
+        play(
+            &mut game,
+            &[vec!["hit"; 10], vec!["chest"]].concat().join(","),
+        );
+        let day_one_coins = game.player.total_coins_earned;
+        play(&mut game, "end-day");
+        play(
+            &mut game,
+            &[vec!["hit"; 5], vec!["end-day"]].concat().join(","),
+        );
+
+        let player = &game.player;
+        assert_eq!(player.day, 3);
+        let [one, two] = player.history.as_slice() else {
+            panic!("expected two days, got {:?}", player.history);
+        };
+        assert_eq!(
+            (one.day, one.swings, one.chests, one.treasures),
+            (1, 10, 1, 1)
+        );
+        assert_eq!(one.coins, day_one_coins);
+        assert_eq!(
+            (two.day, two.swings, two.chests, two.treasures),
+            (2, 5, 0, 0)
+        );
+        assert_eq!(two.coins, player.total_coins_earned - day_one_coins);
+        assert_eq!(player.today.swings, 0);
+    }
+}
@@ -0,0 +1,132 @@
+use std::{collections::BTreeMap, fs, path::PathBuf};
+
+use color_eyre::eyre::{Result, WrapErr};
+use colored::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{Player, saves, treasure::Rarity};
+
+/// One save's standing, as of its last write.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Entry {
+    pub name: String,
+    pub slot: u32,
+    pub coins: u32,
+    pub day: u32,
+    /// Legendary or better treasures in the collection.
+    pub legendaries: u32,
+}
+
+/// Entries keyed by the save's file name, so each slot has one row.
+type Board = BTreeMap<String, Entry>;
+
+fn board_path() -> Result<PathBuf> {
+    Ok(saves::save_dir()?.join("leaderboard.json"))
+}
+
+/// The board on disk. It is rebuilt from saves as they're written, so an
+/// unreadable one just starts over.
+fn load() -> Result<Board> {
+    let path = board_path()?;
+    if !path.exists() {
+        return Ok(Board::new());
+    }
+    let data = fs::read_to_string(&path)
+        .wrap_err_with(|| format!("Failed to read leaderboard '{}'", path.display()))?;
+    Ok(serde_json::from_str(&data).unwrap_or_default())
+}
+
+/// Applies `change` to the board as it is on disk right now and writes it
+/// back through a temporary file, so another running game's rows survive.
+fn update(change: impl FnOnce(&mut Board)) -> Result<()> {
+    let mut board = load()?;
+    change(&mut board);
+    let path = board_path()?;
+    let tmp = path.with_extension(format!("json.{}.tmp", std::process::id()));
+    fs::write(&tmp, serde_json::to_string_pretty(&board)?)
+        .wrap_err("Failed to update leaderboard")?;
+    fs::rename(&tmp, &path).wrap_err("Failed to update leaderboard")?;
+    Ok(())
+}
+
+/// Records `player` for the save in `file_name`.
+pub fn record(player: &Player, file_name: &str) -> Result<()> {
+    let Some((_, slot)) = saves::parse_file_name(file_name) else {
+        return Ok(());
+    };
+    let entry = Entry {
+        name: player.name.clone(),
+        slot,
+        coins: player.coins,
+        day: player.day,
+        legendaries: player
+            .collection
+            .iter()
+            .filter(|t| t.rarity >= Rarity::Legendary)
+            .count() as u32,
+    };
+    update(|board| {
+        board.insert(file_name.to_string(), entry);
+    })
+}
+
+/// Drops the row for a save that was moved or deleted.
+pub fn forget(file_name: &str) -> Result<()> {
+    update(|board| {
+        board.remove(file_name);
+    })
+}
+
+/// Drops rows whose save no longer exists and returns how many went.
+pub fn prune() -> Result<usize> {
+    let dir = saves::save_dir()?;
+    let mut pruned = 0;
+    update(|board| {
+        let before = board.len();
+        board.retain(|file_name, _| dir.join(file_name).exists());
+        pruned = before - board.len();
+    })?;
+    Ok(pruned)
+}
+
+/// Everyone's saves ranked by coins, then legendaries, with `current`'s
+/// rows highlighted.
+pub fn print(current: &str) -> Result<()> {
+    let mut entries: Vec<Entry> = load()?.into_values().collect();
+    if entries.is_empty() {
+        println!("{}", "No one is on the leaderboard yet.".dimmed());
+        return Ok(());
+    }
+    entries.sort_by(|a, b| {
+        b.coins
+            .cmp(&a.coins)
+            .then(b.legendaries.cmp(&a.legendaries))
+            .then(a.day.cmp(&b.day))
+    });
+
+    println!(
+        "{}",
+        format!(
+            "{:>4}  {:<16} {:>4} {:>5} {:>7} {:>11}",
+            "Rank", "Name", "Slot", "Day", "Coins", "Legendaries"
+        )
+        .bold()
+    );
+    for (i, e) in entries.iter().enumerate() {
+        let line = format!(
+            "{:>4}  {:<16} {:>4} {:>5} {:>7} {:>11}",
+            i + 1,
+            e.name,
+            e.slot,
+            e.day,
+            e.coins,
+            e.legendaries
+        );
+        if e.name.eq_ignore_ascii_case(current) {
+            println!("{}", line.bright_yellow().bold());
+        } else {
+            println!("{}", line);
+        }
+    }
+    Ok(())
+}
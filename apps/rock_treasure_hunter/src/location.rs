@@ -0,0 +1,101 @@
+use std::ops::RangeInclusive;
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum Location {
+    #[default]
+    Quarry,
+    CrystalCave,
+    DragonsHollow,
+}
+
+pub struct LocationParams {
+    pub name: &'static str,
+    /// Lifetime coins earned needed before you can travel here.
+    pub unlock_at: u32,
+    pub coins_per_swing: RangeInclusive<u32>,
+    /// Chest rarity weights, in `Rarity::all()` order; they sum to 100.
+    pub weights: [u8; 5],
+}
+
+impl Location {
+    pub fn all() -> [Self; 3] {
+        [Self::Quarry, Self::CrystalCave, Self::DragonsHollow]
+    }
+
+    pub fn params(&self) -> LocationParams {
+        match self {
+            Location::Quarry => LocationParams {
+                name: "Quarry",
+                unlock_at: 0,
+                coins_per_swing: 0..=10,
+                weights: [59, 25, 10, 5, 1],
+            },
+            Location::CrystalCave => LocationParams {
+                name: "Crystal Cave",
+                unlock_at: 500,
+                coins_per_swing: 3..=15,
+                weights: [44, 33, 15, 7, 1],
+            },
+            Location::DragonsHollow => LocationParams {
+                name: "Dragon's Hollow",
+                unlock_at: 2000,
+                coins_per_swing: 5..=25,
+                weights: [28, 35, 23, 12, 2],
+            },
+        }
+    }
+
+    /// The name used for this location in scripts, e.g. `dragons-hollow`.
+    pub fn slug(&self) -> String {
+        self.params()
+            .name
+            .to_lowercase()
+            .replace('\'', "")
+            .replace(' ', "-")
+    }
+
+    pub fn is_unlocked(&self, lifetime_coins: u32) -> bool {
+        lifetime_coins >= self.params().unlock_at
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Player, difficulty::Difficulty};
+
+    #[test]
+    fn weights_sum_to_100() {
+        for location in Location::all() {
+            let params = location.params();
+            let sum: u32 = params.weights.iter().map(|&w| w as u32).sum();
+            assert_eq!(sum, 100, "{} weights sum to {}", params.name, sum);
+        }
+    }
+
+    #[test]
+    fn slugs() {
+        let slugs: Vec<String> = Location::all().iter().map(Location::slug).collect();
+        assert_eq!(slugs, ["quarry", "crystal-cave", "dragons-hollow"]);
+    }
+
+    #[test]
+    fn travel_to_a_locked_location_is_refused() {
+        let mut player = Player::new("Tester", Difficulty::Normal);
+        player.total_coins_earned = Location::CrystalCave.params().unlock_at - 1;
+        assert!(!player.travel(Location::CrystalCave));
+        assert_eq!(player.location, Location::Quarry);
+    }
+
+    #[test]
+    fn travel_once_unlocked() {
+        let mut player = Player::new("Tester", Difficulty::Normal);
+        player.total_coins_earned = Location::CrystalCave.params().unlock_at;
+        assert!(player.travel(Location::CrystalCave));
+        assert_eq!(player.location, Location::CrystalCave);
+        assert!(!player.travel(Location::DragonsHollow));
+        assert_eq!(player.location, Location::CrystalCave);
+    }
+}
@@ -1,16 +1,49 @@
-use clap::Parser;
-use color_eyre::{eyre::Result, eyre::WrapErr};
+use chrono::{DateTime, Local};
+use clap::{Parser, Subcommand};
+use color_eyre::{Report, eyre::Result, eyre::eyre};
 use colored::*;
-use rand::Rng;
+use rand::{Rng, SeedableRng, rngs::StdRng};
 use serde::{Deserialize, Serialize};
 use std::{
-    fs,
-    io::{self, Write},
-    path::Path,
+    collections::{BTreeMap, BTreeSet},
+    io::{self, BufRead, IsTerminal, Write},
+    ops::RangeInclusive,
+    path::{Path, PathBuf},
+    time::Duration,
 };
+use unicode_width::UnicodeWidthStr;
 
-const STRENGTH_PER_DAY: u32 = 100;
-const CHEST_COST: u32 = 50;
+mod achievements;
+mod action;
+mod boss;
+mod chest;
+mod coins;
+mod difficulty;
+mod display;
+mod encyclopedia;
+mod export;
+mod gamble;
+mod history;
+mod leaderboard;
+mod location;
+mod pickaxe;
+mod quests;
+mod regen;
+mod saves;
+mod stats;
+mod treasure;
+mod tui;
+
+use action::{Action, apply_action};
+use chest::ChestTier;
+use coins::{CoinRoll, Haul};
+use difficulty::Difficulty;
+use display::{icon, menu_key, pad, say};
+use location::Location;
+use treasure::{Rarity, Treasure, TreasureDef, catalog, random_treasure};
+
+/// Consecutive non-legendary chests after which the next one is guaranteed legendary.
+const PITY_THRESHOLD: u32 = 20;
 
 #[derive(Parser, Debug)]
 #[command(
@@ -19,255 +52,1422 @@ const CHEST_COST: u32 = 50;
     about = "Bust rocks, earn coins, and discover treasures!"
 )]
 struct Cli {
+    #[command(subcommand)]
+    command: Option<Commands>,
+
     /// Your adventurer name
-    #[arg(short, long, default_value = "Adventurer")]
+    #[arg(short, long, default_value = "Adventurer", global = true)]
     name: String,
 
     /// Load previous save if it exists
     #[arg(short, long)]
     load: bool,
-}
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
-enum Rarity {
-    Common,
-    Rare,
-    Epic,
-    Legendary,
-}
+    /// Save slot to play in (defaults to a new slot, or the only one with --load)
+    #[arg(long, value_name = "N")]
+    slot: Option<u32>,
 
-impl Rarity {
-    fn color(&self) -> Color {
-        match self {
-            Rarity::Common => Color::White,
-            Rarity::Rare => Color::Cyan,
-            Rarity::Epic => Color::Magenta,
-            Rarity::Legendary => Color::Yellow,
-        }
-    }
+    /// Only save when choosing "Save & Quit"
+    #[arg(long)]
+    no_autosave: bool,
 
-    fn all() -> [Self; 4] {
-        [Self::Common, Self::Rare, Self::Epic, Self::Legendary]
-    }
+    /// Use ASCII labels instead of emoji
+    #[arg(long)]
+    ascii: bool,
 
-    fn weight(&self) -> u8 {
-        match self {
-            Rarity::Common => 60,
-            Rarity::Rare => 25,
-            Rarity::Epic => 10,
-            Rarity::Legendary => 5,
-        }
-    }
-}
+    /// Plain output: ASCII labels and no colors (also enabled by NO_COLOR)
+    #[arg(long, alias = "no-color")]
+    plain: bool,
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-struct Treasure {
-    name: String,
-    rarity: Rarity,
+    /// Load treasure definitions from a TOML or JSON file, or an http(s) URL
+    #[arg(long, value_name = "FILE")]
+    treasures: Option<PathBuf>,
+
+    /// Seconds to wait for a --treasures URL before giving up
+    #[arg(long, value_name = "SECS", default_value_t = 10)]
+    timeout: u64,
+
+    /// Run comma-separated commands without prompting, e.g. "hit,hit,chest,end-day,quit"
+    /// (commands are read line by line from stdin when it isn't a terminal)
+    #[arg(long, value_name = "COMMANDS")]
+    script: Option<String>,
+
+    /// Seed the random number generator to make a run reproducible
+    #[arg(long, value_name = "N")]
+    seed: Option<u64>,
+
+    /// Write the saved collection to a CSV or JSON file and exit (implies --load)
+    #[arg(long, value_name = "PATH")]
+    export: Option<PathBuf>,
+
+    /// Difficulty for a new game; loaded games keep the one they started with
+    #[arg(long, value_enum)]
+    difficulty: Option<Difficulty>,
+
+    /// Rename the saved adventurer to NAME, moving the save file, and exit (implies --load)
+    #[arg(long, value_name = "NAME")]
+    rename_to: Option<String>,
+
+    /// Load backup N (1 is the newest) of the save in --slot, or slot 1 (implies --load)
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u32).range(1..=saves::BACKUPS as i64))]
+    restore_backup: Option<u32>,
+
+    /// Regenerate 1 strength per real minute, up to the daily maximum
+    #[arg(long)]
+    regen: bool,
+
+    /// How the coins from each swing are rolled
+    #[arg(long, value_enum, value_name = "DIST", default_value_t = CoinRoll::Uniform)]
+    coin_roll: CoinRoll,
+
+    /// Play full-screen: j/k and Enter pick actions, Tab scrolls the collection
+    #[arg(long, conflicts_with_all = ["script", "export", "rename_to"])]
+    tui: bool,
 }
 
-impl Treasure {
-    fn display(&self) {
-        println!(
-            "{} {}",
-            self.name.color(self.rarity.color()).bold(),
-            format!("({:?})", self.rarity).color(self.rarity.color())
-        );
+impl Cli {
+    /// Whether the run must not prompt: scripts, exports and piped stdin.
+    fn headless(&self) -> bool {
+        self.script.is_some() || self.export.is_some() || !io::stdin().is_terminal()
+    }
+
+    /// Whether an existing save is needed rather than a fresh adventurer.
+    fn wants_save(&self) -> bool {
+        self.load
+            || self.export.is_some()
+            || self.rename_to.is_some()
+            || self.restore_backup.is_some()
     }
 }
 
+#[derive(Subcommand, Debug)]
+enum Commands {
+    /// List saved games
+    Saves,
+    /// Rank every player's saves on this machine (--name is highlighted)
+    Leaderboard {
+        /// Remove rows whose save no longer exists
+        #[arg(long)]
+        prune: bool,
+    },
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 struct Player {
     name: String,
+    #[serde(default)]
+    difficulty: Difficulty,
     strength: u32,
     coins: u32,
     collection: Vec<Treasure>,
+    /// Today's counters, filed into `history` by `new_day`.
+    #[serde(default)]
+    today: history::DaySummary,
+    #[serde(default)]
+    history: Vec<history::DaySummary>,
+    #[serde(default)]
+    total_swings: u32,
+    #[serde(default)]
+    total_coins_earned: u32,
+    #[serde(default)]
+    chests_opened: u32,
+    #[serde(default)]
+    achievements: BTreeMap<String, DateTime<Local>>,
+    #[serde(default = "first_day")]
+    day: u32,
+    #[serde(default)]
+    last_played: Option<DateTime<Local>>,
+    /// Chests opened since the last legendary drop.
+    #[serde(default)]
+    pity: u32,
+    #[serde(default)]
+    location: Location,
+    #[serde(default)]
+    coins_spent: u32,
+    #[serde(default)]
+    best_day_coins: u32,
+    /// Chest drops per rarity; crafted treasures aren't counted.
+    #[serde(default)]
+    treasures_found: BTreeMap<Rarity, u32>,
+    #[serde(default)]
+    chests_by_tier: BTreeMap<ChestTier, u32>,
+    #[serde(default)]
+    gamble_won: u32,
+    #[serde(default)]
+    gamble_lost: u32,
+    /// Today's quests, rolled when `quests_day` falls behind `day`.
+    #[serde(default)]
+    quests: Vec<quests::Quest>,
+    #[serde(default)]
+    quests_day: u32,
+    /// Today's Boss Rock, if one has been found and not yet cracked.
+    #[serde(default)]
+    current_boss: Option<boss::BossRock>,
+    #[serde(default)]
+    pickaxe: pickaxe::Pickaxe,
+    /// Names of every treasure ever owned, even if since crafted away.
+    #[serde(default)]
+    discovered: BTreeSet<String>,
+    /// When `--regen` last credited a point of strength.
+    #[serde(default)]
+    regen_since: Option<DateTime<Local>>,
+}
+
+/// Everything an action can touch: the player and the run's single RNG, so
+/// a seed plus a sequence of actions always plays out the same way.
+struct Game {
+    player: Player,
+    rng: StdRng,
+    /// Whether strength regenerates in real time (`--regen`).
+    regen: bool,
+    coin_roll: CoinRoll,
+    /// Lifetime counters when this run started, for the summary at quit.
+    session: stats::Tally,
+}
+
+fn first_day() -> u32 {
+    1
 }
 
 impl Player {
-    fn new(name: &str) -> Self {
+    fn new(name: &str, difficulty: Difficulty) -> Self {
         Self {
             name: name.to_string(),
-            strength: STRENGTH_PER_DAY,
+            difficulty,
+            strength: difficulty.params().strength_per_day,
             coins: 0,
             collection: vec![],
+            today: history::DaySummary::default(),
+            history: vec![],
+            total_swings: 0,
+            total_coins_earned: 0,
+            chests_opened: 0,
+            achievements: BTreeMap::new(),
+            day: first_day(),
+            last_played: None,
+            pity: 0,
+            location: Location::default(),
+            coins_spent: 0,
+            best_day_coins: 0,
+            treasures_found: BTreeMap::new(),
+            chests_by_tier: BTreeMap::new(),
+            gamble_won: 0,
+            gamble_lost: 0,
+            quests: vec![],
+            quests_day: 0,
+            current_boss: None,
+            pickaxe: pickaxe::Pickaxe::default(),
+            discovered: BTreeSet::new(),
+            regen_since: None,
         }
     }
 
     fn new_day(&mut self) {
-        self.strength = STRENGTH_PER_DAY;
-        println!(
+        let mut summary = std::mem::take(&mut self.today);
+        summary.day = self.day;
+        summary.print();
+        self.history.push(summary);
+
+        self.day += 1;
+        self.strength = self.max_strength();
+        self.current_boss = None;
+        say!(
             "\n{} It's a new day! Your strength is full ({}).",
-            "☀️".yellow(),
+            icon("☀️", "[SUN]").yellow(),
             self.strength
         );
     }
 
-    fn hit_rock(&mut self) {
+    fn hit_rock(&mut self, rng: &mut impl Rng, coin_roll: CoinRoll) {
         if self.strength == 0 {
-            println!("{} You are out of strength for today!", "⚠️".yellow());
+            say!(
+                "{} You are out of strength for today!",
+                icon("⚠️", "[!]").yellow()
+            );
             return;
         }
+        self.swing();
+        // A broken pickaxe only manages what the starting quarry would yield.
+        let location = if self.pickaxe.is_broken() {
+            Location::Quarry
+        } else {
+            self.location
+        };
+        match coin_roll.roll(self.coins_per_swing(location), rng) {
+            Haul::Coins(coins_found) => {
+                self.earn(coins_found);
+                say!(
+                    "{} You swing your pickaxe... {} coins fly out! (+{})",
+                    rock_art().dimmed(),
+                    icon("💰", "[COIN]").yellow(),
+                    coins_found
+                );
+            }
+            Haul::Jackpot(coins_found) => {
+                self.earn(coins_found);
+                say!(
+                    "{} The rock splits open on a hidden hoard... {} {} (+{})",
+                    rock_art().dimmed(),
+                    icon("🎰", "[JACKPOT]").bright_yellow().bold(),
+                    "JACKPOT!".bright_yellow().bold(),
+                    coins_found.to_string().bright_yellow().bold()
+                );
+            }
+        }
+        if self.current_boss.is_none() && rng.random_range(0..100) < boss::SPAWN_CHANCE {
+            let boss = boss::BossRock::spawn(rng);
+            say!(
+                "{} The ground shakes... a Boss Rock appears!",
+                icon("👹", "[BOSS]").red().bold()
+            );
+            boss.print_hp();
+            self.current_boss = Some(boss);
+        }
+    }
+
+    fn max_strength(&self) -> u32 {
+        self.difficulty.params().strength_per_day
+    }
+
+    fn coins_per_swing(&self, location: Location) -> RangeInclusive<u32> {
+        self.difficulty
+            .params()
+            .coins_per_swing(&location.params().coins_per_swing)
+    }
+
+    fn chest_cost(&self, tier: ChestTier) -> u32 {
+        self.difficulty.params().price(tier.params().cost)
+    }
+
+    fn repair_cost(&self) -> u32 {
+        self.difficulty.params().price(self.pickaxe.repair_cost())
+    }
+
+    fn swing(&mut self) {
         self.strength -= 1;
-        let coins_found: u32 = rand::rng().random_range(0..=10);
-        self.coins += coins_found;
-        println!(
-            "{} You swing your pickaxe... {} coins fly out! (+{})",
+        self.today.swings += 1;
+        self.total_swings += 1;
+        if self.pickaxe.wear() {
+            say!(
+                "{} Your pickaxe breaks! Coin yield drops until you repair it.",
+                icon("💥", "[BROKEN]").red().bold()
+            );
+        }
+    }
+
+    fn repair_pickaxe(&mut self) {
+        let cost = self.repair_cost();
+        if cost == 0 {
+            say!("Your pickaxe is already in perfect shape.");
+            return;
+        }
+        if self.coins < cost {
+            say!(
+                "{} Repairs cost {} coins. You have {}.",
+                icon("🚫", "[X]").red(),
+                cost,
+                self.coins
+            );
+            return;
+        }
+        self.coins -= cost;
+        self.coins_spent += cost;
+        self.pickaxe.repair();
+        say!(
+            "{} Pickaxe repaired for {} coins.",
+            icon("🔧", "[FIX]").green(),
+            cost
+        );
+    }
+
+    fn earn(&mut self, coins: u32) {
+        self.coins += coins;
+        self.total_coins_earned += coins;
+        self.today.coins += coins;
+        self.best_day_coins = self.best_day_coins.max(self.today.coins);
+    }
+
+    /// Spends strength to call out a Boss Rock, unless one is already waiting.
+    fn challenge_boss(&mut self, rng: &mut impl Rng) {
+        if let Some(boss) = &self.current_boss {
+            say!("A Boss Rock is already waiting for you.");
+            boss.print_hp();
+            return;
+        }
+        if self.strength < boss::CHALLENGE_COST {
+            say!(
+                "{} You need {} strength to challenge a Boss Rock.",
+                icon("⚠️", "[!]").yellow(),
+                boss::CHALLENGE_COST
+            );
+            return;
+        }
+        self.strength -= boss::CHALLENGE_COST;
+        let boss = boss::BossRock::spawn(rng);
+        say!(
+            "{} You challenge a Boss Rock!",
+            icon("👹", "[BOSS]").red().bold()
+        );
+        boss.print_hp();
+        self.current_boss = Some(boss);
+    }
+
+    /// One swing at today's Boss Rock; cracking it drops a free chest and
+    /// bonus coins.
+    fn hit_boss(&mut self, rng: &mut impl Rng) {
+        let Some(boss) = &mut self.current_boss else {
+            say!("{} There is no Boss Rock to hit.", icon("❓", "[?]").red());
+            return;
+        };
+        if self.strength == 0 {
+            say!(
+                "{} You are out of strength for today!",
+                icon("⚠️", "[!]").yellow()
+            );
+            return;
+        }
+        let damage = boss.take_hit(rng);
+        say!(
+            "{} You strike the Boss Rock for {} damage!",
             rock_art().dimmed(),
-            "💰".yellow(),
-            coins_found
+            damage
+        );
+        boss.print_hp();
+        let cracked = boss.hp == 0;
+        self.swing();
+        if !cracked {
+            return;
+        }
+
+        self.current_boss = None;
+        let bonus = rng.random_range(boss::BONUS_COINS);
+        self.earn(bonus);
+        say!(
+            "{} The Boss Rock cracks open! {} +{} bonus coins and a chest!",
+            icon("🎉", "[WIN]").bright_yellow().bold(),
+            icon("💰", "[COIN]").yellow(),
+            bonus
         );
+        self.award_chest(rng, ChestTier::Silver);
     }
 
-    fn open_chest(&mut self) {
-        if self.coins < CHEST_COST {
-            println!(
+    fn open_chest(&mut self, rng: &mut impl Rng, tier: ChestTier) {
+        let cost = self.chest_cost(tier);
+        if self.coins < cost {
+            say!(
                 "{} Not enough coins ({} needed). You have {}.",
-                "🚫".red(),
-                CHEST_COST,
+                icon("🚫", "[X]").red(),
+                cost,
                 self.coins
             );
             return;
         }
-        self.coins -= CHEST_COST;
-        println!("{} Opening chest...", chest_art().yellow());
-        let treasure = random_treasure();
+        self.coins -= cost;
+        self.coins_spent += cost;
+        self.award_chest(rng, tier);
+    }
+
+    fn award_chest(&mut self, rng: &mut impl Rng, tier: ChestTier) {
+        self.chests_opened += 1;
+        self.today.chests += 1;
+        *self.chests_by_tier.entry(tier).or_insert(0) += 1;
+        say!(
+            "{} Opening {} chest...",
+            chest_art().yellow(),
+            tier.params().name.bold()
+        );
+        let weights = tier.weights_at(self.location);
+        let treasure = random_treasure(rng, catalog(), &weights, self.pity);
         treasure.display();
+        *self
+            .treasures_found
+            .entry(treasure.rarity.clone())
+            .or_insert(0) += 1;
+        if treasure.rarity >= Rarity::Legendary {
+            self.pity = 0;
+        } else {
+            self.pity += 1;
+            say!(
+                "{}",
+                format!("Legendary pity: {}/{}", self.pity, PITY_THRESHOLD).dimmed()
+            );
+        }
+        self.add_treasure(treasure);
+    }
+
+    /// Adds `treasure` to the collection, celebrating if it was the last
+    /// undiscovered entry in the encyclopedia.
+    fn add_treasure(&mut self, treasure: Treasure) {
+        let new = !self.has_discovered(&treasure.name);
+        self.discovered.insert(treasure.name.clone());
+        self.today.treasures += 1;
         self.collection.push(treasure);
+        if new && encyclopedia::is_complete(self) {
+            encyclopedia::celebrate();
+        }
+    }
+
+    /// Older saves have no `discovered` list, so owned treasures count too.
+    fn has_discovered(&self, name: &str) -> bool {
+        self.discovered.contains(name) || self.collection.iter().any(|t| t.name == name)
+    }
+
+    /// Consumes identical copies of `name` per its rarity's recipe and adds a
+    /// random treasure of the next rarity from `table`.
+    fn craft(
+        &mut self,
+        name: &str,
+        table: &[TreasureDef],
+        rng: &mut impl Rng,
+    ) -> Result<Treasure, String> {
+        let Some(rarity) = self
+            .collection
+            .iter()
+            .find(|t| t.name == name)
+            .map(|t| t.rarity.clone())
+        else {
+            return Err(format!("You don't own any {}.", name));
+        };
+        let Some((cost, target)) = rarity.craft_recipe() else {
+            return Err(format!("{:?} treasures can't be crafted further.", rarity));
+        };
+        let owned = self.collection.iter().filter(|t| t.name == name).count();
+        if owned < cost {
+            return Err(format!("Need {} {} but you have {}.", cost, name, owned));
+        }
+        let candidates: Vec<_> = table.iter().filter(|d| d.rarity == target).collect();
+        if candidates.is_empty() {
+            return Err(format!("No {:?} treasures exist to craft into.", target));
+        }
+
+        let mut to_remove = cost;
+        self.collection.retain(|t| {
+            if to_remove > 0 && t.name == name {
+                to_remove -= 1;
+                false
+            } else {
+                true
+            }
+        });
+        let def = candidates[rng.random_range(0..candidates.len())];
+        let crafted = Treasure {
+            name: def.name.clone(),
+            rarity: target,
+        };
+        self.add_treasure(crafted.clone());
+        Ok(crafted)
+    }
+
+    /// Moves to `to` if it is unlocked, returning whether the trip happened.
+    fn travel(&mut self, to: Location) -> bool {
+        if !to.is_unlocked(self.total_coins_earned) {
+            return false;
+        }
+        self.location = to;
+        true
     }
 
     fn view_collection(&self) {
         if self.collection.is_empty() {
-            println!("{} Your collection is empty!", "📭".dimmed());
+            say!(
+                "{} Your collection is empty!",
+                icon("📭", "[EMPTY]").dimmed()
+            );
             return;
         }
-        println!("\n{} Treasure Collection:", "📜".bright_white().bold());
-        for (i, t) in self.collection.iter().enumerate() {
-            print!("{:3}. ", i + 1);
-            t.display();
+        say!(
+            "\n{} Treasure Collection:",
+            icon("📜", "[LIST]").bright_white().bold()
+        );
+        let stacks = self.stacked_collection();
+        let name_width = stacks
+            .iter()
+            .map(|(t, _)| t.name.width())
+            .max()
+            .unwrap_or(0);
+        for (i, (t, count)) in stacks.into_iter().enumerate() {
+            say!("{:3}. {}", i + 1, t.stack_line(count, name_width));
         }
+
+        let total_value: u32 = self.collection.iter().map(Treasure::value).sum();
+        let per_rarity: Vec<String> = Rarity::all()
+            .iter()
+            .map(|r| {
+                let n = self.collection.iter().filter(|t| t.rarity == *r).count();
+                format!("{:?}: {}", r, n).color(r.color()).to_string()
+            })
+            .collect();
+        let (found, total) = encyclopedia::completion(self, None);
+        say!(
+            "\n{} Total value: {} coins\n{} {}\n{} Completion: {}/{} ({:.0}%)",
+            icon("💰", "[COIN]").yellow(),
+            total_value.to_string().yellow().bold(),
+            icon("📊", "[RARITY]"),
+            per_rarity.join(" | "),
+            icon("📖", "[BOOK]"),
+            found,
+            total,
+            found as f32 / total.max(1) as f32 * 100.0
+        );
     }
-}
 
-fn random_treasure() -> Treasure {
-    let treasures = vec![
-        ("Rusty Dagger", Rarity::Common),
-        ("Old Boots", Rarity::Common),
-        ("Silver Ring", Rarity::Rare),
-        ("Emerald Amulet", Rarity::Rare),
-        ("Phoenix Feather", Rarity::Epic),
-        ("Dragon Scale", Rarity::Epic),
-        ("Excalibur", Rarity::Legendary),
-        ("Philosopher's Stone", Rarity::Legendary),
-    ];
-
-    let mut rng = rand::rng();
-    let roll: u8 = rng.random_range(0..100);
-
-    let rarity = {
-        let mut cumulative = 0;
-        let mut selected = Rarity::Common;
-        for r in Rarity::all() {
-            cumulative += r.weight();
-            if roll < cumulative {
-                selected = r;
-                break;
-            }
+    /// Groups duplicate treasures, sorted by rarity then name.
+    fn stacked_collection(&self) -> Vec<(&Treasure, usize)> {
+        let mut stacks: BTreeMap<(&Rarity, &str), (&Treasure, usize)> = BTreeMap::new();
+        for t in &self.collection {
+            stacks
+                .entry((&t.rarity, t.name.as_str()))
+                .or_insert((t, 0))
+                .1 += 1;
         }
-        selected.clone()
-    };
-
-    let candidates: Vec<_> = treasures
-        .into_iter()
-        .filter(|(_, r)| *r == rarity)
-        .collect();
-
-    let (name, _rar) = &candidates[rng.random_range(0..candidates.len())];
-    Treasure {
-        name: name.to_string(),
-        rarity,
+        stacks.into_values().collect()
     }
 }
 
 fn rock_art() -> &'static str {
-    "🪨"
+    icon("🪨", "[ROCK]")
 }
 
 fn chest_art() -> &'static str {
-    "📦"
+    icon("📦", "[CHEST]")
 }
 
 fn prompt(input: &str) -> Result<String> {
     print!("{} ", input.green().bold());
     io::stdout().flush()?;
     let mut buf = String::new();
-    io::stdin().read_line(&mut buf)?;
+    if io::stdin().read_line(&mut buf)? == 0 {
+        return Err(eyre!("Input closed"));
+    }
     Ok(buf.trim().to_string())
 }
 
-fn save_path(name: &str) -> String {
-    format!("{}.json", name.to_lowercase())
+fn chest_menu(player: &Player) -> Result<Option<Action>> {
+    println!("\n{} Chests", chest_art().bright_white().bold());
+    for (i, tier) in ChestTier::all().iter().enumerate() {
+        println!(
+            "{}{} Chest ({} coins): {}",
+            menu_key(i + 1),
+            tier.params().name,
+            player.chest_cost(*tier),
+            tier.odds(player.location)
+        );
+    }
+
+    let answer = prompt("Open which?")?;
+    let tier = answer
+        .parse::<usize>()
+        .ok()
+        .and_then(|n| ChestTier::all().get(n.wrapping_sub(1)).copied());
+    if tier.is_none() {
+        println!("{} Invalid choice!", icon("❓", "[?]").red());
+    }
+    Ok(tier.map(Action::OpenChest))
 }
 
-fn load_player(path: &str) -> Option<Player> {
-    if Path::new(path).exists() {
-        let data = fs::read_to_string(path).ok()?;
-        serde_json::from_str(&data).ok()
+fn gamble_menu(player: &Player) -> Result<Option<Action>> {
+    println!("\n{} Gamble", icon("🎲", "[DICE]").bright_white().bold());
+    println!("{}Coin flip: double or nothing", menu_key(1));
+    println!(
+        "{}Dice: roll a target or higher on a d6, riskier targets pay more",
+        menu_key(2)
+    );
+    let kind = match prompt("Which game?")?.as_str() {
+        "1" => gamble::BetKind::CoinFlip,
+        "2" => match gamble::parse_target(&prompt("Target (2-6)?")?) {
+            Ok(target) => gamble::BetKind::Dice(target),
+            Err(e) => {
+                println!("{} Invalid bet: {}.", icon("❓", "[?]").red(), e);
+                return Ok(None);
+            }
+        },
+        _ => {
+            println!("{} Invalid choice!", icon("❓", "[?]").red());
+            return Ok(None);
+        }
+    };
+    let wager = match gamble::parse_wager(&prompt(&format!("Wager (you have {})?", player.coins))?)
+    {
+        Ok(wager) => wager,
+        Err(e) => {
+            println!("{} Invalid bet: {}.", icon("❓", "[?]").red(), e);
+            return Ok(None);
+        }
+    };
+    Ok(Some(Action::Gamble(gamble::Bet { wager, kind })))
+}
+
+fn craft_menu(player: &Player) -> Result<Option<Action>> {
+    let craftable: Vec<_> = player
+        .stacked_collection()
+        .into_iter()
+        .filter_map(|(t, count)| {
+            let (cost, target) = t.rarity.craft_recipe()?;
+            (count >= cost).then(|| (t.name.clone(), cost, target))
+        })
+        .collect();
+
+    println!("\n{} Crafting", icon("⚒️", "[CRAFT]").bright_white().bold());
+    if craftable.is_empty() {
+        println!(
+            "{}",
+            "Nothing to craft yet. Recipes: 5 Common → Rare, 4 Rare → Epic, 3 Epic → Legendary (identical items)."
+                .dimmed()
+        );
+        return Ok(None);
+    }
+    for (i, (name, cost, target)) in craftable.iter().enumerate() {
+        println!(
+            "{}{}× {} → random {}",
+            menu_key(i + 1),
+            cost,
+            name,
+            format!("{:?}", target).color(target.color())
+        );
+    }
+
+    let answer = prompt("Craft which?")?;
+    let Some((name, cost, _)) = answer
+        .parse::<usize>()
+        .ok()
+        .and_then(|n| craftable.get(n.wrapping_sub(1)))
+    else {
+        println!("{} Invalid choice!", icon("❓", "[?]").red());
+        return Ok(None);
+    };
+    if !confirm(&format!("Use {} {}?", cost, name))? {
+        return Ok(None);
+    }
+    Ok(Some(Action::Craft(name.clone())))
+}
+
+fn travel_menu(player: &Player) -> Result<Option<Action>> {
+    println!("\n{} Where to?", icon("🧭", "[MAP]").bright_white().bold());
+    for (i, loc) in Location::all().iter().enumerate() {
+        let params = loc.params();
+        let coins = player.coins_per_swing(*loc);
+        let line = format!(
+            "{}{} (coins {}-{} per swing)",
+            menu_key(i + 1),
+            params.name,
+            coins.start(),
+            coins.end()
+        );
+        if loc.is_unlocked(player.total_coins_earned) {
+            println!("{}", line);
+        } else {
+            println!(
+                "{}",
+                format!(
+                    "{} — locked until {} lifetime coins",
+                    line, params.unlock_at
+                )
+                .dimmed()
+            );
+        }
+    }
+
+    let answer = prompt("Travel to?")?;
+    let to = answer
+        .parse::<usize>()
+        .ok()
+        .and_then(|n| Location::all().get(n.wrapping_sub(1)).copied());
+    if to.is_none() {
+        println!("{} Invalid choice!", icon("❓", "[?]").red());
+    }
+    Ok(to.map(Action::Travel))
+}
+
+fn export_menu() -> Result<Option<Action>> {
+    let answer = prompt("Export to (.csv or .json) [collection.csv]:")?;
+    let path = if answer.is_empty() {
+        "collection.csv".into()
+    } else {
+        answer
+    };
+    Ok(Some(Action::Export(PathBuf::from(path))))
+}
+
+fn confirm(question: &str) -> Result<bool> {
+    Ok(prompt(&format!("{} [y/N]", question))?.eq_ignore_ascii_case("y"))
+}
+
+/// Resolves which slot to play in and loads it when `--load` was given.
+fn open_slot(cli: &Cli) -> Result<(u32, Player)> {
+    if !cli.wants_save() {
+        let slot = match cli.slot {
+            Some(slot) => slot,
+            None => saves::next_free_slot(&cli.name)?,
+        };
+        // The first autosave would replace the save already in the slot.
+        if saves::save_path(&cli.name, slot)?.exists() {
+            let message = format!(
+                "{} already has a save in slot {} (use --load to continue it)",
+                cli.name, slot
+            );
+            if cli.headless() || !confirm(&format!("{}. Start over and replace it?", message))? {
+                return Err(eyre!("{}", message));
+            }
+        }
+        return Ok((
+            slot,
+            Player::new(&cli.name, cli.difficulty.unwrap_or_default()),
+        ));
+    }
+
+    if let Some(n) = cli.restore_backup {
+        let slot = cli.slot.unwrap_or(1);
+        let path = saves::backup_path(&saves::save_path(&cli.name, slot)?, n);
+        let player = saves::load_player(&path)?
+            .ok_or_else(|| eyre!("{} has no backup {} for slot {}", cli.name, n, slot))?;
+        println!("Restored slot {} from backup {}.", slot, n);
+        return Ok((slot, player));
+    }
+
+    if let Some(slot) = cli.slot {
+        let path = saves::save_path(&cli.name, slot)?;
+        let player = match saves::load_player(&path) {
+            Ok(Some(player)) => player,
+            Ok(None) => no_save(cli, &format!("slot {}", slot))?,
+            Err(e) => corrupt_save(cli, &path, e, slot)?,
+        };
+        return Ok((slot, player));
+    }
+
+    let mut slots = saves::list_saves(Some(&cli.name))?;
+    match slots.len() {
+        0 => {
+            let legacy = saves::legacy_save_path(&cli.name);
+            let player = match saves::load_player(&legacy) {
+                Ok(Some(player)) => {
+                    println!("Imported save from {}.", legacy.display());
+                    player
+                }
+                Ok(None) => no_save(cli, "any slot")?,
+                Err(e) => corrupt_save(cli, &legacy, e, 1)?,
+            };
+            Ok((1, player))
+        }
+        1 => {
+            let save = slots.remove(0);
+            let player = match save.player {
+                Ok(player) => player,
+                Err(e) => corrupt_save(cli, &save.path, e, save.slot)?,
+            };
+            Ok((save.slot, player))
+        }
+        _ if cli.headless() => Err(eyre!(
+            "{} has several save slots; pick one with --slot",
+            cli.name
+        )),
+        _ => {
+            saves::print_saves(&slots);
+            loop {
+                let answer = prompt("Which slot?")?;
+                let chosen = answer
+                    .parse::<u32>()
+                    .ok()
+                    .and_then(|n| slots.iter().position(|s| s.slot == n));
+                match chosen {
+                    Some(i) => {
+                        let save = slots.remove(i);
+                        let player = match save.player {
+                            Ok(player) => player,
+                            Err(e) => corrupt_save(cli, &save.path, e, save.slot)?,
+                        };
+                        return Ok((save.slot, player));
+                    }
+                    None => println!("{} No such slot.", icon("❓", "[?]").red()),
+                }
+            }
+        }
+    }
+}
+
+/// Asked for a save that doesn't exist: offers a fresh start when
+/// playing interactively, and fails otherwise.
+fn no_save(cli: &Cli, which: &str) -> Result<Player> {
+    let message = format!("{} has no save in {}", cli.name, which);
+    if cli.headless() || cli.rename_to.is_some() {
+        return Err(eyre!("{}", message));
+    }
+    if confirm(&format!("{}. Start a new game?", message))? {
+        Ok(Player::new(&cli.name, cli.difficulty.unwrap_or_default()))
     } else {
-        None
+        Err(eyre!("{}", message))
     }
 }
 
-fn save_player(player: &Player, path: &str) -> Result<()> {
-    let json = serde_json::to_string_pretty(player)?;
-    fs::write(path, json).wrap_err("Failed to save game")?;
+/// A save that failed to load: keeps it as `.corrupt-<timestamp>` so
+/// nothing overwrites it, then offers a fresh start as for a missing save.
+fn corrupt_save(cli: &Cli, path: &Path, error: Report, slot: u32) -> Result<Player> {
+    let kept = saves::set_aside_corrupt(path)?;
+    println!(
+        "{} {:#}. It was kept as {}; backups are restored with --restore-backup.",
+        icon("⚠️", "[!]").red(),
+        error,
+        kept.display()
+    );
+    no_save(cli, &format!("slot {}", slot))
+}
+
+/// Moves the save in `slot` over to `new_name`, asking before replacing a
+/// save that already exists under that name. Returns whether it happened.
+fn rename(player: &mut Player, slot: u32, new_name: &str, can_prompt: bool) -> Result<bool> {
+    let new_name = new_name.trim();
+    if new_name.is_empty() {
+        println!("{} The name can't be empty.", icon("❓", "[?]").red());
+        return Ok(false);
+    }
+    let old_path = saves::save_path(&player.name, slot)?;
+    let new_path = saves::save_path(new_name, slot)?;
+    if new_path != old_path && new_path.exists() {
+        if !can_prompt {
+            return Err(eyre!("{} already has a save in slot {}", new_name, slot));
+        }
+        if !confirm(&format!(
+            "{} already has a save in slot {}. Overwrite it?",
+            new_name, slot
+        ))? {
+            return Ok(false);
+        }
+    }
+
+    player.name = new_name.to_string();
+    saves::save_player(player, &new_path)?;
+    if new_path != old_path && old_path.exists() {
+        std::fs::remove_file(&old_path)?;
+        if let Some(file_name) = old_path.file_name().and_then(|f| f.to_str()) {
+            leaderboard::forget(file_name)?;
+        }
+    }
+    println!(
+        "{} You are now known as {}.",
+        icon("✨", "[*]").bright_yellow(),
+        player.name.bold()
+    );
+    Ok(true)
+}
+
+fn save_as(player: &mut Player, slot: &mut u32) -> Result<()> {
+    let suggested = saves::next_free_slot(&player.name)?;
+    let answer = prompt(&format!("Save to slot [{}]:", suggested))?;
+    let target = if answer.is_empty() {
+        suggested
+    } else {
+        match answer.parse::<u32>() {
+            Ok(n) => n,
+            Err(_) => {
+                println!("{} Not a slot number.", icon("❓", "[?]").red());
+                return Ok(());
+            }
+        }
+    };
+    let path = saves::save_path(&player.name, target)?;
+    if target != *slot && path.exists() && !confirm(&format!("Overwrite slot {}?", target))? {
+        return Ok(());
+    }
+    saves::save_player(player, &path)?;
+    *slot = target;
+    println!(
+        "{} Saved to slot {}. Now playing in it.",
+        icon("💾", "[SAVE]").green(),
+        target
+    );
+    Ok(())
+}
+
+fn delete_slot(player: &Player, current: u32) -> Result<()> {
+    let others: Vec<_> = saves::list_saves(Some(&player.name))?
+        .into_iter()
+        .filter(|s| s.slot != current)
+        .collect();
+    if others.is_empty() {
+        println!(
+            "{} No other slots to delete.",
+            icon("📭", "[EMPTY]").dimmed()
+        );
+        return Ok(());
+    }
+    saves::print_saves(&others);
+    let answer = prompt("Delete which slot?")?;
+    let Some(save) = answer
+        .parse::<u32>()
+        .ok()
+        .and_then(|n| others.iter().find(|s| s.slot == n))
+    else {
+        println!("{} No such slot.", icon("❓", "[?]").red());
+        return Ok(());
+    };
+    if confirm(&format!("Really delete slot {}?", save.slot))? {
+        std::fs::remove_file(&save.path)?;
+        if let Some(file_name) = save.path.file_name().and_then(|f| f.to_str()) {
+            leaderboard::forget(file_name)?;
+        }
+        println!("{} Slot {} deleted.", icon("🗑️", "[DEL]").red(), save.slot);
+    }
     Ok(())
 }
 
 fn main() -> Result<()> {
     color_backtrace::install();
     let cli = Cli::parse();
+    display::set_ascii(cli.ascii);
+    if cli.plain || display::no_color_env() {
+        display::set_plain();
+    }
+    treasure::init_catalog(cli.treasures.as_deref(), Duration::from_secs(cli.timeout))?;
 
-    let save_file = save_path(&cli.name);
-    let mut player = if cli.load {
-        load_player(&save_file).unwrap_or_else(|| Player::new(&cli.name))
-    } else {
-        Player::new(&cli.name)
+    match cli.command {
+        Some(Commands::Saves) => {
+            saves::print_saves(&saves::list_saves(None)?);
+            return Ok(());
+        }
+        Some(Commands::Leaderboard { prune }) => {
+            if prune {
+                let pruned = leaderboard::prune()?;
+                println!("Removed {} stale leaderboard rows.", pruned);
+            }
+            leaderboard::print(&cli.name)?;
+            return Ok(());
+        }
+        None => {}
+    }
+
+    let (mut slot, mut player) = open_slot(&cli)?;
+    if let Some(new_name) = &cli.rename_to {
+        rename(&mut player, slot, new_name, !cli.headless())?;
+        return Ok(());
+    }
+    if let Some(path) = &cli.export {
+        export::export(&player, path)?;
+        println!("Exported collection to {}.", path.display());
+        return Ok(());
+    }
+
+    if cli.wants_save()
+        && let Some(asked) = cli.difficulty
+        && asked != player.difficulty
+    {
+        println!(
+            "{}",
+            format!(
+                "This save is on {} difficulty; --difficulty only applies to new games.",
+                player.difficulty.params().name
+            )
+            .dimmed()
+        );
+    }
+
+    let seed = cli.seed.unwrap_or_else(|| rand::rng().random());
+    println!("{}", format!("Seed: {}", seed).dimmed());
+    let mut game = Game {
+        session: stats::Tally::of(&player),
+        player,
+        rng: StdRng::seed_from_u64(seed),
+        regen: cli.regen,
+        coin_roll: cli.coin_roll,
     };
+    if cli.tui && !cli.headless() {
+        // Keep the startup messages for the TUI's log.
+        display::capture_output(true);
+    }
+    if game.regen {
+        regen::catch_up(&mut game.player);
+    }
+    quests::update(&mut game);
 
-    println!("{} Welcome, {}!", "✨".bright_yellow(), player.name.bold());
+    if cli.headless() {
+        return run_script(&cli, slot, game);
+    }
+    if cli.tui {
+        return tui::run(&cli, slot, game);
+    }
+
+    println!(
+        "{} Welcome, {}!",
+        icon("✨", "[*]").bright_yellow(),
+        game.player.name.bold()
+    );
     println!("Type the number of an action and press Enter.\n");
 
-    let mut day = 1;
     loop {
+        let next_point = if game.regen {
+            regen::catch_up(&mut game.player);
+            regen::next_point_in(&game.player, Local::now())
+                .map(|secs| format!(" (next point in {}s)", secs).dimmed().to_string())
+                .unwrap_or_default()
+        } else {
+            String::new()
+        };
         println!(
-            "\n{} Day {} | Strength: {} | Coins: {}",
-            "🗓️".cyan(),
-            day,
-            player.strength.to_string().blue(),
-            player.coins.to_string().yellow()
-        );
-        println!(
-            "1️⃣  Hit Rock\n2️⃣  Open Chest (cost {})\n3️⃣  View Collection\n4️⃣  End Day\n5️⃣  Save & Quit",
-            CHEST_COST
+            "\n{} Day {:<3} | {} {} | {} Strength: {}{} | {} Coins: {} | {}",
+            pad(icon("🗓️", "[DAY]"), 2).cyan(),
+            game.player.day,
+            pad(icon("📍", "[AT]"), 2).green(),
+            game.player.location.params().name.green(),
+            pad(icon("💪", "[STR]"), 2).blue(),
+            format!("{:>3}", game.player.strength).blue(),
+            next_point,
+            pad(icon("💰", "[COIN]"), 2).yellow(),
+            format!("{:>5}", game.player.coins).yellow(),
+            game.player.pickaxe.status()
         );
+        quests::print_status(&game.player);
+        if let Some(boss) = &game.player.current_boss {
+            boss.print_hp();
+        }
+        let menu = [
+            "Hit Rock".to_string(),
+            format!(
+                "Open Chest (from {})",
+                game.player.chest_cost(ChestTier::Bronze)
+            ),
+            format!(
+                "Gamble ({} of {} coins left to wager today)",
+                gamble::DAILY_WAGER_CAP.saturating_sub(game.player.today.wagered),
+                gamble::DAILY_WAGER_CAP
+            ),
+            match &game.player.current_boss {
+                Some(boss) => format!("Fight Boss Rock (HP {}/{})", boss.hp, boss.max_hp),
+                None => format!(
+                    "Challenge Boss Rock (cost {} strength)",
+                    boss::CHALLENGE_COST
+                ),
+            },
+            "View Collection".to_string(),
+            "Encyclopedia".to_string(),
+            "Craft".to_string(),
+            format!("Repair Pickaxe (cost {})", game.player.repair_cost()),
+            "Achievements".to_string(),
+            "Stats".to_string(),
+            "History".to_string(),
+            "Export Collection".to_string(),
+            "Travel".to_string(),
+            "End Day".to_string(),
+            format!("Save As… (playing slot {})", slot),
+            "Rename".to_string(),
+            "Delete Slot".to_string(),
+            "Save & Quit".to_string(),
+        ];
+        for (i, item) in menu.iter().enumerate() {
+            println!("{}{}", menu_key(i + 1), item);
+        }
 
-        match prompt("Your choice?")?.as_str() {
-            "1" => player.hit_rock(),
-            "2" => player.open_chest(),
-            "3" => player.view_collection(),
+        let action = match prompt("Your choice?")?.as_str() {
+            "1" => Some(Action::HitRock),
+            "2" => chest_menu(&game.player)?,
+            "3" => gamble_menu(&game.player)?,
             "4" => {
-                day += 1;
-                player.new_day();
+                boss_fight(&cli, &mut game, slot)?;
+                None
+            }
+            "5" => Some(Action::ViewCollection),
+            "6" => Some(Action::Encyclopedia),
+            "7" => craft_menu(&game.player)?,
+            "8" => Some(Action::Repair),
+            "9" => Some(Action::Achievements),
+            "10" => Some(Action::Stats),
+            "11" => Some(Action::History),
+            "12" => export_menu()?,
+            "13" => travel_menu(&game.player)?,
+            "14" => Some(Action::EndDay),
+            "15" => {
+                save_as(&mut game.player, &mut slot)?;
+                None
             }
-            "5" => {
-                save_player(&player, &save_file)?;
-                println!("{} Game saved. Goodbye!", "💾".green());
+            "16" => {
+                let new_name = prompt("New name:")?;
+                rename(&mut game.player, slot, &new_name, true)?;
+                None
+            }
+            "17" => {
+                delete_slot(&game.player, slot)?;
+                None
+            }
+            "18" => return quit(&cli, &mut game, slot),
+            _ => {
+                println!("{} Invalid choice!", icon("❓", "[?]").red());
+                None
+            }
+        };
+        if let Some(action) = action {
+            apply_action(&mut game, action);
+        }
+        after_action(&cli, &mut game, slot)?;
+    }
+}
+
+/// Challenges a Boss Rock if none is waiting, then trades swings with it
+/// until it cracks, strength runs out, or the player backs off.
+fn boss_fight(cli: &Cli, game: &mut Game, slot: u32) -> Result<()> {
+    if game.player.current_boss.is_none() {
+        apply_action(game, Action::ChallengeBoss);
+        after_action(cli, game, slot)?;
+    }
+    while game.player.current_boss.is_some() && game.player.strength > 0 {
+        match prompt("[H]it or [A]bandon?")?.to_lowercase().as_str() {
+            "" | "h" => {
+                apply_action(game, Action::HitBoss);
+                after_action(cli, game, slot)?;
+            }
+            "a" => {
+                println!("You back off. The Boss Rock will still be here today.");
                 break;
             }
-            _ => println!("{} Invalid choice!", "❓".red()),
+            _ => println!("{} Invalid choice!", icon("❓", "[?]").red()),
         }
     }
+    Ok(())
+}
+
+/// Runs the `--script` commands, or stdin lines, as if chosen from the menu.
+fn run_script(cli: &Cli, slot: u32, mut game: Game) -> Result<()> {
+    let commands: Vec<String> = match &cli.script {
+        Some(script) => script.split(',').map(str::to_string).collect(),
+        None => io::stdin().lock().lines().collect::<io::Result<_>>()?,
+    };
 
+    for (i, command) in commands.iter().enumerate() {
+        let command = command.trim();
+        if command.is_empty() {
+            continue;
+        }
+        println!("{}", format!("> {}", command).dimmed());
+        if command == "quit" {
+            return quit(cli, &mut game, slot);
+        }
+        let action = command
+            .parse::<Action>()
+            .map_err(|e| eyre!("{} at position {} of the script", e, i + 1))?;
+        apply_action(&mut game, action);
+        after_action(cli, &mut game, slot)?;
+    }
     Ok(())
 }
+
+/// Pays out finished quests and unlocks achievements earned by the last action.
+fn settle(game: &mut Game) {
+    quests::update(game);
+    achievements::check(&mut game.player);
+}
+
+fn after_action(cli: &Cli, game: &mut Game, slot: u32) -> Result<()> {
+    settle(game);
+    if !cli.no_autosave {
+        let path = saves::save_path(&game.player.name, slot)?;
+        saves::save_player(&mut game.player, &path)?;
+    }
+    Ok(())
+}
+
+fn quit(cli: &Cli, game: &mut Game, slot: u32) -> Result<()> {
+    let player = &mut game.player;
+    game.session.print_session(player);
+    if cli.no_autosave {
+        let path = saves::save_path(&player.name, slot)?;
+        saves::save_player(player, &path)?;
+    }
+    println!("{} Game saved. Goodbye!", icon("💾", "[SAVE]").green());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A new Normal game seeded with `seed`, as `main` starts one.
+    pub(crate) fn seeded_game(seed: u64) -> Game {
+        let player = Player::new("Tester", Difficulty::Normal);
+        let mut game = Game {
+            session: stats::Tally::of(&player),
+            player,
+            rng: StdRng::seed_from_u64(seed),
+            regen: false,
+            coin_roll: CoinRoll::Uniform,
+        };
+        quests::update(&mut game);
+        game
+    }
+
+    /// Runs comma-separated `--script` commands without saving.
+    pub(crate) fn play(game: &mut Game, script: &str) {
+        for command in script.split(',') {
+            apply_action(game, command.parse().unwrap());
+            settle(game);
+        }
+    }
+
+    /// The save, minus the wall-clock times that differ between runs.
+    fn outcome(player: &Player) -> serde_json::Value {
+        let mut json = serde_json::to_value(player).unwrap();
+        let achievements = json["achievements"].as_object_mut().unwrap();
+        for at in achievements.values_mut() {
+            *at = serde_json::Value::Null;
+        }
+        json
+    }
+
+    fn treasure(name: &str, rarity: Rarity) -> Treasure {
+        Treasure {
+            name: name.to_string(),
+            rarity,
+        }
+    }
+
+    fn def(name: &str, rarity: Rarity) -> TreasureDef {
+        TreasureDef {
+            name: name.to_string(),
+            rarity,
+            value: 1,
+            flavor: None,
+        }
+    }
+
+    fn crafting_table() -> Vec<TreasureDef> {
+        vec![def("Pebble", Rarity::Common), def("Ruby", Rarity::Rare)]
+    }
+
+    fn count(player: &Player, name: &str) -> usize {
+        player.collection.iter().filter(|t| t.name == name).count()
+    }
+
+    #[test]
+    fn stacked_collection_counts_duplicates() {
+        let mut player = Player::new("Tester", Difficulty::Normal);
+        player.collection = vec![
+            treasure("Ruby", Rarity::Rare),
+            treasure("Pebble", Rarity::Common),
+            treasure("Ruby", Rarity::Rare),
+            treasure("Amber", Rarity::Common),
+            treasure("Pebble", Rarity::Common),
+            treasure("Ruby", Rarity::Rare),
+        ];
+        let stacks: Vec<(&str, usize)> = player
+            .stacked_collection()
+            .into_iter()
+            .map(|(t, n)| (t.name.as_str(), n))
+            .collect();
+        assert_eq!(stacks, [("Amber", 1), ("Pebble", 2), ("Ruby", 3)]);
+    }
+
+    #[test]
+    fn stacked_collection_of_empty_collection_is_empty() {
+        let player = Player::new("Tester", Difficulty::Normal);
+        assert!(player.stacked_collection().is_empty());
+    }
+
+    #[test]
+    fn loads_save_from_before_stacking() {
+        let json = r#"{
+            "name": "Old Timer",
+            "strength": 40,
+            "coins": 120,
+            "collection": [
+                {"name": "Pebble", "rarity": "Common"},
+                {"name": "Pebble", "rarity": "Common"},
+                {"name": "Ruby", "rarity": "Rare"}
+            ]
+        }"#;
+        let player: Player = serde_json::from_str(json).unwrap();
+        assert_eq!(player.collection.len(), 3);
+        assert_eq!(player.day, first_day());
+        let counts: Vec<usize> = player
+            .stacked_collection()
+            .into_iter()
+            .map(|(_, n)| n)
+            .collect();
+        assert_eq!(counts, [2, 1]);
+        assert!(player.has_discovered("Ruby"));
+    }
+
+    #[test]
+    fn craft_with_exactly_enough() {
+        let mut player = Player::new("Tester", Difficulty::Normal);
+        let (cost, target) = Rarity::Common.craft_recipe().unwrap();
+        player.collection = vec![treasure("Pebble", Rarity::Common); cost];
+        let mut rng = StdRng::seed_from_u64(1);
+
+        let crafted = player.craft("Pebble", &crafting_table(), &mut rng).unwrap();
+        assert_eq!((crafted.name.as_str(), crafted.rarity), ("Ruby", target));
+        assert_eq!(count(&player, "Pebble"), 0);
+        assert_eq!(count(&player, "Ruby"), 1);
+    }
+
+    #[test]
+    fn craft_consumes_only_the_recipe() {
+        let mut player = Player::new("Tester", Difficulty::Normal);
+        let (cost, _) = Rarity::Common.craft_recipe().unwrap();
+        player.collection = vec![treasure("Pebble", Rarity::Common); cost + 1];
+        let mut rng = StdRng::seed_from_u64(1);
+
+        player.craft("Pebble", &crafting_table(), &mut rng).unwrap();
+        assert_eq!(count(&player, "Pebble"), 1);
+    }
+
+    #[test]
+    fn craft_with_one_too_few_changes_nothing() {
+        let mut player = Player::new("Tester", Difficulty::Normal);
+        let (cost, _) = Rarity::Common.craft_recipe().unwrap();
+        player.collection = vec![treasure("Pebble", Rarity::Common); cost - 1];
+        let mut rng = StdRng::seed_from_u64(1);
+
+        let error = player
+            .craft("Pebble", &crafting_table(), &mut rng)
+            .unwrap_err();
+        assert_eq!(
+            error,
+            format!("Need {} Pebble but you have {}.", cost, cost - 1)
+        );
+        assert_eq!(count(&player, "Pebble"), cost - 1);
+        assert_eq!(count(&player, "Ruby"), 0);
+    }
+
+    #[test]
+    fn craft_refuses_unowned_and_top_rarities() {
+        let mut player = Player::new("Tester", Difficulty::Normal);
+        player.collection = vec![treasure("Crown", Rarity::Legendary); 10];
+        let mut rng = StdRng::seed_from_u64(1);
+
+        let table = crafting_table();
+        assert!(player.craft("Pebble", &table, &mut rng).is_err());
+        assert!(player.craft("Crown", &table, &mut rng).is_err());
+        assert_eq!(count(&player, "Crown"), 10);
+    }
+
+    #[test]
+    fn craft_without_a_target_in_the_table_keeps_the_materials() {
+        let mut player = Player::new("Tester", Difficulty::Normal);
+        let (cost, _) = Rarity::Rare.craft_recipe().unwrap();
+        player.collection = vec![treasure("Ruby", Rarity::Rare); cost];
+        let mut rng = StdRng::seed_from_u64(1);
+
+        let error = player
+            .craft("Ruby", &crafting_table(), &mut rng)
+            .unwrap_err();
+        assert_eq!(error, "No Epic treasures exist to craft into.");
+        assert_eq!(count(&player, "Ruby"), cost);
+    }
+
+    #[test]
+    fn same_seed_and_script_play_out_the_same() {
+        let script = [
+            "hit,".repeat(40),
+            "chest,chest:silver,gamble:10,boss,boss-hit,boss-hit,repair,end-day,".to_string(),
+            "hit,".repeat(30),
+            "chest,end-day".to_string(),
+        ]
+        .concat();
+
+        let mut first = seeded_game(42);
+        play(&mut first, &script);
+        let mut second = seeded_game(42);
+        play(&mut second, &script);
+        assert!(first.player.total_swings > 0 && first.player.history.len() == 2);
+        assert_eq!(outcome(&first.player), outcome(&second.player));
+
+        let mut other = seeded_game(43);
+        play(&mut other, &script);
+        assert_ne!(outcome(&first.player), outcome(&other.player));
+    }
+}
@@ -0,0 +1,112 @@
+use colored::*;
+use serde::{Deserialize, Serialize};
+
+use crate::display::{icon, pad};
+
+const DURABILITY_PER_TIER: u32 = 150;
+const REPAIR_COST_PER_POINT: u32 = 1;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Pickaxe {
+    /// Higher tiers last longer and cost more to repair.
+    pub tier: u32,
+    pub durability: u32,
+}
+
+impl Default for Pickaxe {
+    fn default() -> Self {
+        Self {
+            tier: 1,
+            durability: DURABILITY_PER_TIER,
+        }
+    }
+}
+
+impl Pickaxe {
+    pub fn max_durability(&self) -> u32 {
+        DURABILITY_PER_TIER * self.tier
+    }
+
+    pub fn is_broken(&self) -> bool {
+        self.durability == 0
+    }
+
+    /// Uses up one point for a swing and returns whether this swing broke it.
+    pub fn wear(&mut self) -> bool {
+        if self.is_broken() {
+            return false;
+        }
+        self.durability -= 1;
+        self.is_broken()
+    }
+
+    /// Coins to restore full durability: a fixed price per missing point,
+    /// scaled by tier.
+    pub fn repair_cost(&self) -> u32 {
+        (self.max_durability() - self.durability) * REPAIR_COST_PER_POINT * self.tier
+    }
+
+    pub fn repair(&mut self) {
+        self.durability = self.max_durability();
+    }
+
+    /// The status-line segment, colored by how worn the pickaxe is.
+    pub fn status(&self) -> String {
+        let text = format!("Pickaxe: {:>3}/{}", self.durability, self.max_durability());
+        let percent = self.durability * 100 / self.max_durability();
+        let colored = match percent {
+            51.. => text.green(),
+            21..=50 => text.yellow(),
+            _ => text.red(),
+        };
+        format!("{} {}", pad(icon("⛏️", "[PICK]"), 2), colored)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn breaks_on_the_swing_that_reaches_zero() {
+        let mut pickaxe = Pickaxe {
+            tier: 1,
+            durability: 2,
+        };
+        assert!(!pickaxe.wear());
+        assert!(!pickaxe.is_broken());
+        assert!(pickaxe.wear());
+        assert!(pickaxe.is_broken());
+        // Already broken: no further wear, and no second "breaks" report.
+        assert!(!pickaxe.wear());
+        assert_eq!(pickaxe.durability, 0);
+    }
+
+    #[test]
+    fn full_repair_is_free() {
+        assert_eq!(Pickaxe::default().repair_cost(), 0);
+    }
+
+    #[test]
+    fn partial_repair_costs_the_missing_points() {
+        let mut pickaxe = Pickaxe::default();
+        for _ in 0..40 {
+            pickaxe.wear();
+        }
+        assert!(!pickaxe.is_broken());
+        assert_eq!(pickaxe.repair_cost(), 40 * REPAIR_COST_PER_POINT);
+
+        pickaxe.repair();
+        assert_eq!(pickaxe.durability, pickaxe.max_durability());
+        assert_eq!(pickaxe.repair_cost(), 0);
+    }
+
+    #[test]
+    fn higher_tiers_cost_more_per_point() {
+        let pickaxe = Pickaxe {
+            tier: 2,
+            durability: 2 * DURABILITY_PER_TIER - 10,
+        };
+        assert_eq!(pickaxe.repair_cost(), 10 * REPAIR_COST_PER_POINT * 2);
+    }
+}
@@ -0,0 +1,257 @@
+use colored::*;
+use rand::{Rng, seq::SliceRandom};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    Game, Player,
+    display::{bar, icon, say},
+    treasure::{Rarity, Treasure, catalog},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum QuestKind {
+    Swings,
+    Coins,
+    Chests,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum Reward {
+    Coins(u32),
+    /// A random treasure of this rarity.
+    Treasure(Rarity),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Quest {
+    pub kind: QuestKind,
+    pub target: u32,
+    pub reward: Reward,
+    pub done: bool,
+}
+
+struct QuestDef {
+    kind: QuestKind,
+    target: u32,
+    reward: Reward,
+}
+
+const QUESTS: &[QuestDef] = &[
+    QuestDef {
+        kind: QuestKind::Swings,
+        target: 30,
+        reward: Reward::Coins(40),
+    },
+    QuestDef {
+        kind: QuestKind::Swings,
+        target: 60,
+        reward: Reward::Treasure(Rarity::Common),
+    },
+    QuestDef {
+        kind: QuestKind::Coins,
+        target: 200,
+        reward: Reward::Coins(75),
+    },
+    QuestDef {
+        kind: QuestKind::Coins,
+        target: 400,
+        reward: Reward::Treasure(Rarity::Rare),
+    },
+    QuestDef {
+        kind: QuestKind::Chests,
+        target: 2,
+        reward: Reward::Coins(60),
+    },
+    QuestDef {
+        kind: QuestKind::Chests,
+        target: 4,
+        reward: Reward::Treasure(Rarity::Rare),
+    },
+];
+
+impl Quest {
+    pub fn description(&self) -> String {
+        match self.kind {
+            QuestKind::Swings => format!("Swing {} times", self.target),
+            QuestKind::Coins => format!("Earn {} coins", self.target),
+            QuestKind::Chests => format!("Open {} chests", self.target),
+        }
+    }
+
+    /// Today's count towards this quest, capped at the target.
+    pub fn progress(&self, player: &Player) -> u32 {
+        let count = match self.kind {
+            QuestKind::Swings => player.today.swings,
+            QuestKind::Coins => player.today.coins,
+            QuestKind::Chests => player.today.chests,
+        };
+        count.min(self.target)
+    }
+}
+
+impl Reward {
+    fn describe(&self) -> String {
+        match self {
+            Reward::Coins(n) => format!("{} coins", n),
+            Reward::Treasure(r) => format!("a {:?} treasure", r),
+        }
+    }
+}
+
+/// Picks 1–3 quests of different kinds from the definitions table.
+pub fn generate(rng: &mut impl Rng) -> Vec<Quest> {
+    let count = rng.random_range(1..=3);
+    let mut defs: Vec<&QuestDef> = QUESTS.iter().collect();
+    defs.shuffle(rng);
+
+    let mut quests: Vec<Quest> = Vec::new();
+    for def in defs {
+        if quests.len() == count {
+            break;
+        }
+        if quests.iter().any(|q| q.kind == def.kind) {
+            continue;
+        }
+        quests.push(Quest {
+            kind: def.kind,
+            target: def.target,
+            reward: def.reward.clone(),
+            done: false,
+        });
+    }
+    quests
+}
+
+/// Rolls the day's quests if they're from another day, then pays out any
+/// that were just completed. Each quest pays out once.
+pub fn update(game: &mut Game) {
+    let Game { player, rng, .. } = game;
+    if player.quests_day != player.day {
+        player.quests = generate(rng);
+        player.quests_day = player.day;
+    }
+
+    for i in 0..player.quests.len() {
+        let quest = &player.quests[i];
+        if quest.done || quest.progress(player) < quest.target {
+            continue;
+        }
+        let reward = quest.reward.clone();
+        say!(
+            "\n{} Quest complete: {} — reward: {}",
+            icon("📜", "[QUEST]").green(),
+            quest.description().bold(),
+            reward.describe()
+        );
+        player.quests[i].done = true;
+        match reward {
+            Reward::Coins(n) => player.coins += n,
+            Reward::Treasure(rarity) => {
+                let candidates: Vec<_> = catalog().iter().filter(|d| d.rarity == rarity).collect();
+                let def = candidates[rng.random_range(0..candidates.len())];
+                let treasure = Treasure {
+                    name: def.name.clone(),
+                    rarity,
+                };
+                treasure.display();
+                player.add_treasure(treasure);
+            }
+        }
+    }
+}
+
+pub fn print_status(player: &Player) {
+    for quest in &player.quests {
+        let progress = quest.progress(player);
+        let line = format!(
+            "  {} {} {}/{}",
+            bar(progress, quest.target, 10),
+            quest.description(),
+            progress,
+            quest.target
+        );
+        if quest.done {
+            println!("{}", line.green());
+        } else {
+            println!("{}", line);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{SeedableRng, rngs::StdRng};
+
+    use super::*;
+    use crate::tests::{play, seeded_game};
+
+    fn quest(kind: QuestKind, target: u32, coins: u32) -> Quest {
+        Quest {
+            kind,
+            target,
+            reward: Reward::Coins(coins),
+            done: false,
+        }
+    }
+
+    #[test]
+    fn generated_quests_have_different_kinds() {
+        let mut rng = StdRng::seed_from_u64(3);
+        for _ in 0..20 {
+            let quests = generate(&mut rng);
+            assert!((1..=3).contains(&quests.len()));
+            for (i, q) in quests.iter().enumerate() {
+                assert!(quests[i + 1..].iter().all(|other| other.kind != q.kind));
+            }
+        }
+    }
+
+    #[test]
+    fn scripted_day_completes_each_quest_and_pays_once() {
+        let mut game = seeded_game(5);
+        game.player.quests = vec![
+            quest(QuestKind::Swings, 30, 40),
+            quest(QuestKind::Coins, 200, 75),
+            quest(QuestKind::Chests, 2, 60),
+        ];
+        let script = ["hit,".repeat(80), "chest,chest,chest".to_string()].concat();
+        play(&mut game, &script);
+        // Nothing new happened, so nothing more is paid.
+        update(&mut game);
+        update(&mut game);
+
+        let player = &game.player;
+        assert!(player.quests.iter().all(|q| q.done), "{:?}", player.quests);
+        assert_eq!(
+            player.coins,
+            player.total_coins_earned - player.coins_spent + 40 + 75 + 60
+        );
+    }
+
+    #[test]
+    fn unfinished_quest_pays_nothing() {
+        let mut game = seeded_game(5);
+        game.player.quests = vec![quest(QuestKind::Swings, 30, 40)];
+        play(&mut game, &["hit"; 29].join(","));
+
+        let player = &game.player;
+        assert!(!player.quests[0].done);
+        assert_eq!(player.quests[0].progress(player), 29);
+        assert_eq!(player.coins, player.total_coins_earned);
+    }
+
+    #[test]
+    fn treasure_reward_adds_one_treasure() {
+        let mut game = seeded_game(5);
+        game.player.quests = vec![Quest {
+            reward: Reward::Treasure(Rarity::Rare),
+            ..quest(QuestKind::Swings, 1, 0)
+        }];
+        play(&mut game, "hit");
+        update(&mut game);
+
+        let collection = &game.player.collection;
+        assert_eq!(collection.len(), 1);
+        assert_eq!(collection[0].rarity, Rarity::Rare);
+    }
+}
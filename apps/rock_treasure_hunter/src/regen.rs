@@ -0,0 +1,121 @@
+use chrono::{DateTime, Duration, Local};
+use colored::*;
+
+use crate::{
+    Player,
+    display::{icon, say},
+};
+
+/// Real seconds it takes to regenerate one point of strength with `--regen`.
+pub const SECONDS_PER_POINT: i64 = 60;
+
+/// Where regeneration is counted from: the last credited point, or when the
+/// save was last played for saves that predate `--regen`. A time in the
+/// future (the clock moved backwards) counts as now, so skew never pays out.
+fn since(player: &Player, now: DateTime<Local>) -> DateTime<Local> {
+    player
+        .regen_since
+        .or(player.last_played)
+        .filter(|t| *t <= now)
+        .unwrap_or(now)
+}
+
+/// Credits one strength per full minute since the last credited point, up
+/// to the daily maximum, and returns the points gained. The leftover part
+/// of a minute carries over; time spent at full strength doesn't.
+pub fn regenerate(player: &mut Player, now: DateTime<Local>) -> u32 {
+    let since = since(player, now);
+    let missing = player.max_strength().saturating_sub(player.strength);
+    let elapsed = (now - since).num_seconds() / SECONDS_PER_POINT;
+    let gained = elapsed.clamp(0, missing as i64) as u32;
+
+    player.strength += gained;
+    player.regen_since = Some(if player.strength >= player.max_strength() {
+        now
+    } else {
+        since + Duration::seconds(gained as i64 * SECONDS_PER_POINT)
+    });
+    gained
+}
+
+/// Seconds until the next point, or `None` at full strength.
+pub fn next_point_in(player: &Player, now: DateTime<Local>) -> Option<i64> {
+    if player.strength >= player.max_strength() {
+        return None;
+    }
+    let elapsed = (now - since(player, now)).num_seconds();
+    Some((SECONDS_PER_POINT - elapsed).clamp(1, SECONDS_PER_POINT))
+}
+
+/// Regenerates up to now and says so when anything was gained.
+pub fn catch_up(player: &mut Player) {
+    let gained = regenerate(player, Local::now());
+    if gained > 0 {
+        say!(
+            "{} You feel rested: +{} strength.",
+            icon("💪", "[STR]").blue(),
+            gained
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::difficulty::Difficulty;
+
+    fn tired_player(strength: u32) -> Player {
+        let mut player = Player::new("Tester", Difficulty::Normal);
+        player.strength = strength;
+        player
+    }
+
+    #[test]
+    fn credits_whole_minutes_and_carries_the_rest() {
+        let now = Local::now();
+        let mut player = tired_player(10);
+        player.regen_since = Some(now - Duration::seconds(3 * SECONDS_PER_POINT + 20));
+
+        assert_eq!(regenerate(&mut player, now), 3);
+        assert_eq!(player.strength, 13);
+        assert_eq!(player.regen_since, Some(now - Duration::seconds(20)));
+        assert_eq!(next_point_in(&player, now), Some(SECONDS_PER_POINT - 20));
+    }
+
+    #[test]
+    fn stops_at_max_strength() {
+        let now = Local::now();
+        let mut player = tired_player(98);
+        player.regen_since = Some(now - Duration::hours(5));
+
+        assert_eq!(regenerate(&mut player, now), 2);
+        assert_eq!(player.strength, player.max_strength());
+        assert_eq!(player.regen_since, Some(now));
+        assert_eq!(next_point_in(&player, now), None);
+    }
+
+    #[test]
+    fn future_regen_since_is_clamped_to_now() {
+        let now = Local::now();
+        let mut player = tired_player(10);
+        player.regen_since = Some(now + Duration::hours(2));
+
+        assert_eq!(regenerate(&mut player, now), 0);
+        assert_eq!(player.strength, 10);
+        assert_eq!(player.regen_since, Some(now));
+        assert_eq!(next_point_in(&player, now), Some(SECONDS_PER_POINT));
+
+        // Time counts from now again, not from the skewed future time.
+        let later = now + Duration::seconds(SECONDS_PER_POINT);
+        assert_eq!(regenerate(&mut player, later), 1);
+    }
+
+    #[test]
+    fn old_saves_count_from_last_played() {
+        let now = Local::now();
+        let mut player = tired_player(10);
+        player.last_played = Some(now - Duration::seconds(2 * SECONDS_PER_POINT));
+
+        assert_eq!(regenerate(&mut player, now), 2);
+    }
+}
@@ -0,0 +1,268 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use chrono::Local;
+use color_eyre::eyre::{Result, WrapErr, eyre};
+use colored::*;
+use directories::ProjectDirs;
+
+use crate::{Player, leaderboard};
+
+/// How many earlier versions of each save are kept as `.bak1` (newest) to `.bakN`.
+pub const BACKUPS: u32 = 3;
+
+pub struct SaveSlot {
+    pub path: PathBuf,
+    pub slot: u32,
+    pub player: Result<Player>,
+}
+
+pub fn save_dir() -> Result<PathBuf> {
+    let proj = ProjectDirs::from("", "", "rock_treasure_hunter")
+        .ok_or_else(|| eyre!("cannot determine data directory"))?;
+    let dir = proj.data_dir().to_path_buf();
+    fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
+
+pub fn save_path(name: &str, slot: u32) -> Result<PathBuf> {
+    Ok(save_dir()?.join(format!("{}.slot{}.json", name.to_lowercase(), slot)))
+}
+
+pub fn backup_path(path: &Path, n: u32) -> PathBuf {
+    path.with_extension(format!("json.bak{}", n))
+}
+
+/// Where saves lived before slots existed: `<name>.json` in the working directory.
+pub fn legacy_save_path(name: &str) -> PathBuf {
+    PathBuf::from(format!("{}.json", name.to_lowercase()))
+}
+
+pub fn parse_file_name(file_name: &str) -> Option<(&str, u32)> {
+    let stem = file_name.strip_suffix(".json")?;
+    let (name, slot) = stem.rsplit_once(".slot")?;
+    Some((name, slot.parse().ok()?))
+}
+
+/// Every save in the save directory, optionally only those for `name`,
+/// ordered by name then slot.
+pub fn list_saves(name: Option<&str>) -> Result<Vec<SaveSlot>> {
+    let mut saves = Vec::new();
+    for entry in fs::read_dir(save_dir()?)? {
+        let path = entry?.path();
+        let Some(file_name) = path.file_name().and_then(|f| f.to_str()) else {
+            continue;
+        };
+        let Some((save_name, slot)) = parse_file_name(file_name) else {
+            continue;
+        };
+        if name.is_some_and(|n| n.to_lowercase() != save_name) {
+            continue;
+        }
+        let player = load_player(&path)
+            .and_then(|p| p.ok_or_else(|| eyre!("save disappeared while listing")));
+        saves.push((save_name.to_string(), SaveSlot { path, slot, player }));
+    }
+    saves.sort_by(|(a, x), (b, y)| a.cmp(b).then(x.slot.cmp(&y.slot)));
+    Ok(saves.into_iter().map(|(_, s)| s).collect())
+}
+
+pub fn next_free_slot(name: &str) -> Result<u32> {
+    let used = list_saves(Some(name))?;
+    Ok((1..)
+        .find(|n| used.iter().all(|s| s.slot != *n))
+        .unwrap_or(1))
+}
+
+pub fn load_player(path: &Path) -> Result<Option<Player>> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    let data = fs::read_to_string(path)
+        .wrap_err_with(|| format!("Failed to read save '{}'", path.display()))?;
+    let player = serde_json::from_str(&data)
+        .wrap_err_with(|| format!("Save '{}' is corrupted or incomplete", path.display()))?;
+    Ok(Some(player))
+}
+
+/// Moves a save that failed to load out of the way, so the next write can't
+/// replace it, and returns where it went.
+pub fn set_aside_corrupt(path: &Path) -> Result<PathBuf> {
+    let stamp = Local::now().format("%Y%m%d-%H%M%S");
+    let mut kept = path.with_extension(format!("json.corrupt-{}", stamp));
+    for n in 2.. {
+        if !kept.exists() {
+            break;
+        }
+        kept = path.with_extension(format!("json.corrupt-{}-{}", stamp, n));
+    }
+    fs::rename(path, &kept)
+        .wrap_err_with(|| format!("Failed to move aside '{}'", path.display()))?;
+    Ok(kept)
+}
+
+/// Shifts `.bak1..` down by one, dropping the oldest, and copies the
+/// current save into `.bak1`.
+fn rotate_backups(path: &Path) -> Result<()> {
+    if !path.exists() {
+        return Ok(());
+    }
+    for n in (1..BACKUPS).rev() {
+        let from = backup_path(path, n);
+        if from.exists() {
+            fs::rename(&from, backup_path(path, n + 1))?;
+        }
+    }
+    fs::copy(path, backup_path(path, 1))?;
+    Ok(())
+}
+
+/// Writes to a temporary file first and renames it over the save, so an
+/// interrupted write never leaves a truncated save behind. The save being
+/// replaced becomes the newest backup.
+pub fn save_player(player: &mut Player, path: &Path) -> Result<()> {
+    player.last_played = Some(Local::now());
+    let json = serde_json::to_string_pretty(player)?;
+    let tmp = path.with_extension("json.tmp");
+    fs::write(&tmp, json).wrap_err("Failed to save game")?;
+    rotate_backups(path).wrap_err("Failed to back up the previous save")?;
+    fs::rename(&tmp, path).wrap_err("Failed to save game")?;
+    if let Some(file_name) = path.file_name().and_then(|f| f.to_str()) {
+        leaderboard::record(player, file_name)?;
+    }
+    Ok(())
+}
+
+pub fn print_saves(saves: &[SaveSlot]) {
+    if saves.is_empty() {
+        println!("{}", "No saves yet.".dimmed());
+        return;
+    }
+    println!(
+        "{}",
+        format!(
+            "{:>4}  {:<16} {:>5} {:>7} {:>9}  {}",
+            "Slot", "Name", "Day", "Coins", "Treasures", "Last played"
+        )
+        .bold()
+    );
+    for save in saves {
+        match &save.player {
+            Ok(p) => println!(
+                "{:>4}  {:<16} {:>5} {:>7} {:>9}  {}",
+                save.slot,
+                p.name,
+                p.day,
+                p.coins,
+                p.collection.len(),
+                p.last_played
+                    .map(|t| t.format("%Y-%m-%d %H:%M").to_string())
+                    .unwrap_or_else(|| "-".into())
+                    .dimmed()
+            ),
+            Err(e) => println!(
+                "{:>4}  {}",
+                save.slot,
+                format!("{} ({})", save.path.display(), e).red()
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::difficulty::Difficulty;
+
+    #[test]
+    fn half_written_save_is_reported_not_panicked_on() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("tester.slot1.json");
+        let json =
+            serde_json::to_string_pretty(&Player::new("Tester", Difficulty::Normal)).unwrap();
+        fs::write(&path, &json[..json.len() / 2]).unwrap();
+
+        let error = load_player(&path).unwrap_err();
+        assert!(
+            error.to_string().contains("is corrupted or incomplete"),
+            "unexpected error: {}",
+            error
+        );
+        assert!(error.to_string().contains("tester.slot1.json"));
+    }
+
+    #[test]
+    fn missing_save_is_none() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("nobody.slot1.json");
+        assert!(load_player(&path).unwrap().is_none());
+    }
+
+    #[test]
+    fn empty_save_is_reported() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("tester.slot1.json");
+        fs::write(&path, "").unwrap();
+        assert!(load_player(&path).is_err());
+    }
+
+    #[test]
+    fn wrong_shape_is_reported() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("tester.slot1.json");
+        for json in [
+            "[1, 2, 3]",
+            r#"{"name": "Tester"}"#,
+            r#"{"name": "Tester", "strength": "lots", "coins": 0, "collection": []}"#,
+        ] {
+            fs::write(&path, json).unwrap();
+            let error = load_player(&path).unwrap_err();
+            assert!(
+                error.to_string().contains("is corrupted or incomplete"),
+                "{}: {}",
+                json,
+                error
+            );
+        }
+    }
+
+    #[test]
+    fn backups_rotate_and_drop_the_oldest() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("tester.slot1.json");
+        rotate_backups(&path).unwrap();
+        assert!(!backup_path(&path, 1).exists());
+
+        for version in 1..=BACKUPS + 2 {
+            fs::write(&path, version.to_string()).unwrap();
+            rotate_backups(&path).unwrap();
+        }
+        let newest = BACKUPS + 2;
+        for n in 1..=BACKUPS {
+            let kept = fs::read_to_string(backup_path(&path, n)).unwrap();
+            assert_eq!(kept, (newest + 1 - n).to_string(), "backup {}", n);
+        }
+        assert!(!backup_path(&path, BACKUPS + 1).exists());
+        assert_eq!(fs::read_to_string(&path).unwrap(), newest.to_string());
+    }
+
+    #[test]
+    fn parses_slot_file_names() {
+        assert_eq!(parse_file_name("tester.slot2.json"), Some(("tester", 2)));
+        assert_eq!(parse_file_name("tester.slot2.json.bak1"), None);
+        assert_eq!(parse_file_name("leaderboard.json"), None);
+    }
+
+    #[test]
+    fn corrupt_save_is_set_aside() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("tester.slot1.json");
+        fs::write(&path, "{\"name\": \"Tes").unwrap();
+
+        let kept = set_aside_corrupt(&path).unwrap();
+        assert!(!path.exists());
+        assert_eq!(fs::read_to_string(kept).unwrap(), "{\"name\": \"Tes");
+    }
+}
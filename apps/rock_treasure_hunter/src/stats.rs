@@ -0,0 +1,94 @@
+use colored::*;
+
+use crate::{
+    PITY_THRESHOLD, Player,
+    chest::ChestTier,
+    display::{icon, pad, say},
+    treasure::Rarity,
+};
+
+pub fn view(player: &Player) {
+    say!(
+        "\n{} Lifetime Stats:",
+        icon("📈", "[STATS]").bright_white().bold()
+    );
+    let rows = [
+        ("Difficulty", player.difficulty.params().name.to_string()),
+        ("Days played", player.day.to_string()),
+        ("Total swings", player.total_swings.to_string()),
+        ("Coins earned", player.total_coins_earned.to_string()),
+        ("Coins spent", player.coins_spent.to_string()),
+        ("Chests opened", player.chests_opened.to_string()),
+        ("Gambling won", player.gamble_won.to_string()),
+        ("Gambling lost", player.gamble_lost.to_string()),
+        (
+            "Best day's haul",
+            format!("{} coins", player.best_day_coins),
+        ),
+        (
+            "Legendary pity",
+            format!("{}/{}", player.pity, PITY_THRESHOLD),
+        ),
+    ];
+    for (label, value) in rows {
+        say!("  {:<16} {}", format!("{}:", label), value.bold());
+    }
+
+    say!("  Chests by tier:");
+    for tier in ChestTier::all() {
+        let opened = player.chests_by_tier.get(&tier).copied().unwrap_or(0);
+        say!(
+            "    {} {}",
+            pad(&format!("{}:", tier.params().name), 14),
+            opened.to_string().bold()
+        );
+    }
+
+    say!("  Treasures found:");
+    for rarity in Rarity::all() {
+        let found = player.treasures_found.get(&rarity).copied().unwrap_or(0);
+        say!(
+            "    {} {}",
+            pad(&format!("{:?}:", rarity), 14).color(rarity.color()),
+            found.to_string().bold()
+        );
+    }
+}
+
+/// Lifetime counters at one moment; the difference between two is what
+/// happened in between.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Tally {
+    swings: u32,
+    coins: u32,
+    chests: u32,
+    treasures: u32,
+    discovered: usize,
+}
+
+impl Tally {
+    pub fn of(player: &Player) -> Self {
+        Self {
+            swings: player.total_swings,
+            coins: player.total_coins_earned,
+            chests: player.chests_opened,
+            treasures: player.history.iter().map(|d| d.treasures).sum::<u32>()
+                + player.today.treasures,
+            discovered: player.discovered.len(),
+        }
+    }
+
+    /// Prints what changed since `self`, taken when the session started.
+    pub fn print_session(&self, player: &Player) {
+        let now = Tally::of(player);
+        say!(
+            "\n{} This session: {} rocks hit · {} coins earned · {} chests opened · {} treasures found ({} new)",
+            icon("🏁", "[END]").cyan(),
+            (now.swings - self.swings).to_string().bold(),
+            (now.coins - self.coins).to_string().yellow().bold(),
+            (now.chests - self.chests).to_string().bold(),
+            (now.treasures - self.treasures).to_string().bold(),
+            now.discovered - self.discovered
+        );
+    }
+}
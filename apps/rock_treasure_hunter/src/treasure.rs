@@ -0,0 +1,342 @@
+use std::{collections::BTreeSet, fs, path::Path, sync::OnceLock, time::Duration};
+
+use color_eyre::eyre::{Result, WrapErr, bail};
+use colored::*;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    PITY_THRESHOLD,
+    display::{pad, say},
+};
+
+const BUNDLED_TREASURES: &str = include_str!("../assets/treasures.toml");
+
+static CATALOG: OnceLock<Vec<TreasureDef>> = OnceLock::new();
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Rarity {
+    Common,
+    Rare,
+    Epic,
+    Legendary,
+    /// Rarer than Legendary and never forced by pity or crafting; treasure
+    /// files may leave it out.
+    Mythic,
+}
+
+impl Rarity {
+    pub fn color(&self) -> Color {
+        match self {
+            Rarity::Common => Color::White,
+            Rarity::Rare => Color::Cyan,
+            Rarity::Epic => Color::Magenta,
+            Rarity::Legendary => Color::Yellow,
+            Rarity::Mythic => Color::BrightRed,
+        }
+    }
+
+    pub fn all() -> [Self; 5] {
+        [
+            Self::Common,
+            Self::Rare,
+            Self::Epic,
+            Self::Legendary,
+            Self::Mythic,
+        ]
+    }
+
+    /// How many identical treasures of this rarity craft into one of the next.
+    pub fn craft_recipe(&self) -> Option<(usize, Rarity)> {
+        match self {
+            Rarity::Common => Some((5, Rarity::Rare)),
+            Rarity::Rare => Some((4, Rarity::Epic)),
+            Rarity::Epic => Some((3, Rarity::Legendary)),
+            Rarity::Legendary | Rarity::Mythic => None,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TreasureDef {
+    pub name: String,
+    pub rarity: Rarity,
+    pub value: u32,
+    #[serde(default)]
+    pub flavor: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct TreasureFile {
+    treasure: Vec<TreasureDef>,
+}
+
+/// Parses a treasure table and rejects ones the game can't draw from.
+fn parse_treasures(data: &str, json: bool) -> Result<Vec<TreasureDef>> {
+    let file: TreasureFile = if json {
+        serde_json::from_str(data)?
+    } else {
+        toml::from_str(data)?
+    };
+
+    let mut names = BTreeSet::new();
+    for def in &file.treasure {
+        if !names.insert(def.name.as_str()) {
+            bail!("duplicate treasure name '{}'", def.name);
+        }
+    }
+    for rarity in Rarity::all().into_iter().filter(|r| *r != Rarity::Mythic) {
+        if !file.treasure.iter().any(|d| d.rarity == rarity) {
+            bail!("no treasures defined for rarity {:?}", rarity);
+        }
+    }
+    Ok(file.treasure)
+}
+
+/// Downloads `url`, giving up after `timeout`.
+fn fetch(url: &str, timeout: Duration) -> Result<String> {
+    let agent: ureq::Agent = ureq::Agent::config_builder()
+        .timeout_global(Some(timeout))
+        .build()
+        .into();
+    let mut response = agent.get(url).call()?;
+    Ok(response.body_mut().read_to_string()?)
+}
+
+/// Loads the treasure table from `path`, which may be an `http(s)://` URL,
+/// or the bundled one when `None`. Must be called before the first
+/// `catalog()` lookup to take effect.
+pub fn init_catalog(path: Option<&Path>, timeout: Duration) -> Result<()> {
+    let defs = match path {
+        Some(p) => {
+            let data = match p.to_str() {
+                Some(url) if url.starts_with("http://") || url.starts_with("https://") => {
+                    fetch(url, timeout)
+                        .wrap_err_with(|| format!("Failed to download treasures '{}'", url))?
+                }
+                _ => fs::read_to_string(p)
+                    .wrap_err_with(|| format!("Failed to read treasures '{}'", p.display()))?,
+            };
+            let json = p.extension().is_some_and(|e| e == "json");
+            parse_treasures(&data, json)
+                .wrap_err_with(|| format!("Invalid treasures file '{}'", p.display()))?
+        }
+        None => parse_treasures(BUNDLED_TREASURES, false)?,
+    };
+    let _ = CATALOG.set(defs);
+    Ok(())
+}
+
+pub fn catalog() -> &'static [TreasureDef] {
+    CATALOG.get_or_init(|| {
+        parse_treasures(BUNDLED_TREASURES, false).expect("bundled treasures are valid")
+    })
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Treasure {
+    pub name: String,
+    pub rarity: Rarity,
+}
+
+impl Treasure {
+    pub fn display(&self) {
+        say!(
+            "{} {}",
+            self.name.color(self.rarity.color()).bold(),
+            format!("({:?})", self.rarity).color(self.rarity.color())
+        );
+        if let Some(flavor) = self.def().and_then(|d| d.flavor.as_deref()) {
+            say!("  {}", flavor.italic().dimmed());
+        }
+    }
+
+    fn def(&self) -> Option<&'static TreasureDef> {
+        catalog().iter().find(|d| d.name == self.name)
+    }
+
+    pub fn value(&self) -> u32 {
+        self.def().map_or(0, |d| d.value)
+    }
+
+    /// One line of the collection: the name padded to `name_width`, the
+    /// count and the rarity.
+    pub fn stack_line(&self, count: usize, name_width: usize) -> String {
+        format!(
+            "{} {} {}",
+            pad(&self.name, name_width)
+                .color(self.rarity.color())
+                .bold(),
+            format!("×{}", count).bold(),
+            format!("({:?})", self.rarity).color(self.rarity.color())
+        )
+    }
+}
+
+/// Rolls a treasure from `table` using relative rarity `weights` (in
+/// `Rarity::all()` order); once `pity` non-legendary chests have piled up to
+/// `PITY_THRESHOLD - 1`, the roll is forced to Legendary. A Mythic roll
+/// from a table without Mythic treasures drops a Legendary instead.
+pub fn random_treasure(
+    rng: &mut impl Rng,
+    table: &[TreasureDef],
+    weights: &[u32; 5],
+    pity: u32,
+) -> Treasure {
+    let roll = rng.random_range(0..weights.iter().sum::<u32>());
+
+    let rarity = if pity + 1 >= PITY_THRESHOLD {
+        Rarity::Legendary
+    } else {
+        let mut cumulative = 0;
+        let mut selected = Rarity::Common;
+        for (r, weight) in Rarity::all().into_iter().zip(weights) {
+            cumulative += weight;
+            if roll < cumulative {
+                selected = r;
+                break;
+            }
+        }
+        selected
+    };
+
+    let has_mythic = table.iter().any(|d| d.rarity == Rarity::Mythic);
+    let rarity = if rarity == Rarity::Mythic && !has_mythic {
+        Rarity::Legendary
+    } else {
+        rarity
+    };
+    let candidates: Vec<_> = table.iter().filter(|d| d.rarity == rarity).collect();
+
+    let def = candidates[rng.random_range(0..candidates.len())];
+    Treasure {
+        name: def.name.clone(),
+        rarity,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{SeedableRng, rngs::StdRng};
+
+    use super::*;
+
+    const FIXTURE: &str = r#"
+        [[treasure]]
+        name = "Pebble"
+        rarity = "Common"
+        value = 1
+
+        [[treasure]]
+        name = "Ruby"
+        rarity = "Rare"
+        value = 10
+
+        [[treasure]]
+        name = "Opal"
+        rarity = "Epic"
+        value = 50
+
+        [[treasure]]
+        name = "Crown"
+        rarity = "Legendary"
+        value = 500
+    "#;
+
+    /// Only Commons, unless pity steps in.
+    const COMMON_ONLY: [u32; 5] = [1, 0, 0, 0, 0];
+
+    fn fixture() -> Vec<TreasureDef> {
+        parse_treasures(FIXTURE, false).unwrap()
+    }
+
+    #[test]
+    fn pity_forces_a_legendary_at_the_threshold() {
+        let table = fixture();
+        let mut rng = StdRng::seed_from_u64(7);
+        for pity in 0..PITY_THRESHOLD - 1 {
+            let t = random_treasure(&mut rng, &table, &COMMON_ONLY, pity);
+            assert_eq!(t.rarity, Rarity::Common, "forced early at pity {}", pity);
+        }
+        let t = random_treasure(&mut rng, &table, &COMMON_ONLY, PITY_THRESHOLD - 1);
+        assert_eq!(t.rarity, Rarity::Legendary);
+        assert_eq!(t.name, "Crown");
+    }
+
+    #[test]
+    fn no_run_of_chests_goes_past_the_threshold_without_a_legendary() {
+        let table = fixture();
+        let weights = [60, 25, 10, 5, 0];
+        for seed in 0..50 {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let mut pity = 0;
+            for _ in 0..200 {
+                let t = random_treasure(&mut rng, &table, &weights, pity);
+                if t.rarity >= Rarity::Legendary {
+                    pity = 0;
+                } else {
+                    pity += 1;
+                }
+                assert!(pity < PITY_THRESHOLD, "seed {} went dry", seed);
+            }
+        }
+    }
+
+    #[test]
+    fn draws_only_from_the_rolled_rarity() {
+        let table = fixture();
+        let mut rng = StdRng::seed_from_u64(1);
+        let t = random_treasure(&mut rng, &table, &[0, 1, 0, 0, 0], 0);
+        assert_eq!((t.name.as_str(), t.rarity), ("Ruby", Rarity::Rare));
+        let t = random_treasure(&mut rng, &table, &[0, 0, 1, 0, 0], 0);
+        assert_eq!((t.name.as_str(), t.rarity), ("Opal", Rarity::Epic));
+    }
+
+    #[test]
+    fn mythic_roll_without_mythics_drops_a_legendary() {
+        let table = fixture();
+        let mut rng = StdRng::seed_from_u64(1);
+        let t = random_treasure(&mut rng, &table, &[0, 0, 0, 0, 1], 0);
+        assert_eq!(t.rarity, Rarity::Legendary);
+    }
+
+    #[test]
+    fn parses_json_tables() {
+        let json = r#"{"treasure": [
+            {"name": "Pebble", "rarity": "Common", "value": 1, "flavor": "Grey."},
+            {"name": "Ruby", "rarity": "Rare", "value": 10},
+            {"name": "Opal", "rarity": "Epic", "value": 50},
+            {"name": "Crown", "rarity": "Legendary", "value": 500}
+        ]}"#;
+        let table = parse_treasures(json, true).unwrap();
+        assert_eq!(table.len(), 4);
+        assert_eq!(table[0].flavor.as_deref(), Some("Grey."));
+        assert_eq!(table[1].flavor, None);
+    }
+
+    #[test]
+    fn rejects_duplicate_names() {
+        let data = format!(
+            "{}\n[[treasure]]\nname = \"Ruby\"\nrarity = \"Epic\"\nvalue = 60\n",
+            FIXTURE
+        );
+        let error = parse_treasures(&data, false).unwrap_err();
+        assert_eq!(error.to_string(), "duplicate treasure name 'Ruby'");
+    }
+
+    #[test]
+    fn rejects_a_rarity_with_no_treasures() {
+        let json = r#"{"treasure": [
+            {"name": "Pebble", "rarity": "Common", "value": 1},
+            {"name": "Ruby", "rarity": "Rare", "value": 10},
+            {"name": "Crown", "rarity": "Legendary", "value": 500}
+        ]}"#;
+        let error = parse_treasures(json, true).unwrap_err();
+        assert_eq!(error.to_string(), "no treasures defined for rarity Epic");
+    }
+
+    #[test]
+    fn bundled_table_is_valid() {
+        parse_treasures(BUNDLED_TREASURES, false).unwrap();
+    }
+}
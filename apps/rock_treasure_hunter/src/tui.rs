@@ -0,0 +1,384 @@
+use chrono::Local;
+use color_eyre::eyre::Result;
+use ratatui::{
+    DefaultTerminal, Frame,
+    crossterm::event::{self, Event, KeyCode, KeyEventKind},
+    layout::{Constraint, Layout, Rect},
+    style::{Color, Style, Stylize},
+    text::{Line, Span},
+    widgets::{Block, Gauge, List, ListState, Paragraph},
+};
+use unicode_width::UnicodeWidthStr;
+
+use crate::{
+    Cli, Game, Player,
+    action::{Action, apply_action},
+    after_action,
+    chest::ChestTier,
+    display,
+    location::Location,
+    quit, regen,
+};
+
+/// How many lines of game output the log pane keeps.
+const LOG_LIMIT: usize = 500;
+
+#[derive(Clone)]
+enum Entry {
+    Act(Action),
+    /// An action that waits for a `y` to the question first.
+    Confirm(String, Action),
+    Quit,
+}
+
+#[derive(PartialEq)]
+enum Focus {
+    Menu,
+    Collection,
+}
+
+struct RockTui<'a> {
+    cli: &'a Cli,
+    slot: u32,
+    menu: ListState,
+    log: Vec<String>,
+    collection_scroll: u16,
+    focus: Focus,
+    /// The question asked before running an `Entry::Confirm`, and its action.
+    confirming: Option<(String, Action)>,
+}
+
+/// Runs the game full-screen until the player saves and quits. Game output
+/// captured before the screen switched (regeneration, new quests) opens the
+/// log.
+pub fn run(cli: &Cli, slot: u32, mut game: Game) -> Result<()> {
+    display::capture_output(true);
+    colored::control::set_override(false);
+    let terminal = ratatui::init();
+    let result = RockTui::new(cli, slot).run(terminal, &mut game);
+    ratatui::restore();
+    display::capture_output(false);
+    result?;
+    quit(cli, &mut game, slot)
+}
+
+impl<'a> RockTui<'a> {
+    fn new(cli: &'a Cli, slot: u32) -> Self {
+        Self {
+            cli,
+            slot,
+            menu: ListState::default().with_selected(Some(0)),
+            log: display::take_captured(),
+            collection_scroll: 0,
+            focus: Focus::Menu,
+            confirming: None,
+        }
+    }
+
+    fn run(mut self, mut terminal: DefaultTerminal, game: &mut Game) -> Result<()> {
+        loop {
+            if game.regen {
+                regen::catch_up(&mut game.player);
+                self.push_log();
+            }
+            terminal.draw(|frame| self.draw(frame, &game.player))?;
+            // With --regen, redraw every second so the countdown keeps moving.
+            if game.regen && !event::poll(std::time::Duration::from_secs(1))? {
+                continue;
+            }
+            let Event::Key(key) = event::read()? else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            if let Some((_, action)) = self.confirming.take() {
+                if matches!(key.code, KeyCode::Char('y' | 'Y')) {
+                    self.act(game, action)?;
+                }
+                continue;
+            }
+            match key.code {
+                KeyCode::Esc | KeyCode::Char('q') => return Ok(()),
+                KeyCode::Tab => {
+                    self.focus = match self.focus {
+                        Focus::Menu => Focus::Collection,
+                        Focus::Collection => Focus::Menu,
+                    }
+                }
+                KeyCode::Up | KeyCode::Char('k') => match self.focus {
+                    Focus::Menu => self.menu.select_previous(),
+                    Focus::Collection => {
+                        self.collection_scroll = self.collection_scroll.saturating_sub(1)
+                    }
+                },
+                KeyCode::Down | KeyCode::Char('j') => match self.focus {
+                    Focus::Menu => self.menu.select_next(),
+                    Focus::Collection => self.collection_scroll += 1,
+                },
+                KeyCode::PageUp => {
+                    self.collection_scroll = self.collection_scroll.saturating_sub(10)
+                }
+                KeyCode::PageDown => self.collection_scroll += 10,
+                KeyCode::Enter if self.focus == Focus::Menu => {
+                    let entries = menu_entries(&game.player);
+                    let selected = self.menu.selected().unwrap_or(0).min(entries.len() - 1);
+                    match entries[selected].1.clone() {
+                        Entry::Act(action) => self.act(game, action)?,
+                        Entry::Confirm(question, action) => {
+                            self.confirming = Some((question, action))
+                        }
+                        Entry::Quit => return Ok(()),
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn act(&mut self, game: &mut Game, action: Action) -> Result<()> {
+        self.log.push(String::new());
+        apply_action(game, action);
+        after_action(self.cli, game, self.slot)?;
+        self.push_log();
+        Ok(())
+    }
+
+    /// Moves newly captured output into the log, dropping the oldest lines
+    /// past `LOG_LIMIT`.
+    fn push_log(&mut self) {
+        self.log.extend(display::take_captured());
+        let excess = self.log.len().saturating_sub(LOG_LIMIT);
+        self.log.drain(..excess);
+    }
+
+    fn draw(&mut self, frame: &mut Frame, player: &Player) {
+        let [header, body, footer] = Layout::vertical([
+            Constraint::Length(4),
+            Constraint::Fill(1),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+        let [menu, right] =
+            Layout::horizontal([Constraint::Length(44), Constraint::Fill(1)]).areas(body);
+        let [log, collection] =
+            Layout::vertical([Constraint::Fill(1), Constraint::Percentage(40)]).areas(right);
+
+        self.render_header(frame, header, player);
+        self.render_menu(frame, menu, player);
+        self.render_log(frame, log);
+        self.render_collection(frame, collection, player);
+        let footer_line = match &self.confirming {
+            Some((question, _)) => Line::from(format!(" {} [y/N]", question)).yellow(),
+            None => Line::from(
+                " j/k move · Enter choose · Tab collection · PgUp/PgDn scroll · q save & quit",
+            )
+            .dim(),
+        };
+        frame.render_widget(footer_line, footer);
+    }
+
+    fn render_header(&self, frame: &mut Frame, area: Rect, player: &Player) {
+        let block = Block::bordered().title(format!(" Rock Treasure Hunter — {} ", player.name));
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+        let [status, strength] =
+            Layout::vertical([Constraint::Length(1), Constraint::Length(1)]).areas(inner);
+
+        let mut spans = vec![
+            Span::from(format!("Day {}", player.day)).cyan(),
+            Span::from(" │ "),
+            Span::from(player.location.params().name).green(),
+            Span::from(" │ "),
+            Span::from(format!("Coins {}", player.coins)).yellow(),
+            Span::from(" │ "),
+            Span::from(format!(
+                "Pickaxe {}/{}",
+                player.pickaxe.durability,
+                player.pickaxe.max_durability()
+            )),
+        ];
+        if let Some(boss) = &player.current_boss {
+            spans.push(Span::from(" │ "));
+            spans.push(Span::from(format!("Boss HP {}/{}", boss.hp, boss.max_hp)).red());
+        }
+        frame.render_widget(Line::from(spans), status);
+
+        let max = player.max_strength();
+        let mut label = format!("Strength {}/{}", player.strength, max);
+        if self.cli.regen
+            && let Some(secs) = regen::next_point_in(player, Local::now())
+        {
+            label.push_str(&format!(" (next point in {}s)", secs));
+        }
+        frame.render_widget(
+            Gauge::default()
+                .gauge_style(Color::Blue)
+                .ratio((player.strength as f64 / max.max(1) as f64).min(1.0))
+                .label(label),
+            strength,
+        );
+    }
+
+    fn render_menu(&mut self, frame: &mut Frame, area: Rect, player: &Player) {
+        let items: Vec<String> = menu_entries(player)
+            .into_iter()
+            .map(|(label, _)| label)
+            .collect();
+        let block = Block::bordered().title(" Actions ");
+        let block = if self.focus == Focus::Menu {
+            block.border_style(Color::Green)
+        } else {
+            block
+        };
+        let list = List::new(items)
+            .block(block)
+            .highlight_style(Style::new().reversed())
+            .highlight_symbol("> ");
+        frame.render_stateful_widget(list, area, &mut self.menu);
+    }
+
+    /// The latest output, scrolled so the newest line sits at the bottom.
+    fn render_log(&self, frame: &mut Frame, area: Rect) {
+        let height = area.height.saturating_sub(2) as usize;
+        let start = self.log.len().saturating_sub(height);
+        let lines: Vec<Line> = self.log[start..]
+            .iter()
+            .map(|l| Line::from(l.as_str()))
+            .collect();
+        frame.render_widget(
+            Paragraph::new(lines).block(Block::bordered().title(" Log ")),
+            area,
+        );
+    }
+
+    fn render_collection(&mut self, frame: &mut Frame, area: Rect, player: &Player) {
+        let stacks = player.stacked_collection();
+        let name_width = stacks
+            .iter()
+            .map(|(t, _)| t.name.width())
+            .max()
+            .unwrap_or(0);
+        let lines: Vec<Line> = stacks
+            .iter()
+            .map(|(t, count)| {
+                let color = tui_color(t.rarity.color());
+                Line::from(vec![
+                    Span::from(display::pad(&t.name, name_width))
+                        .fg(color)
+                        .bold(),
+                    Span::from(format!(" ×{} ", count)).bold(),
+                    Span::from(format!("({:?})", t.rarity)).fg(color),
+                ])
+            })
+            .collect();
+        let max_scroll = lines
+            .len()
+            .saturating_sub(area.height.saturating_sub(2) as usize);
+        self.collection_scroll = self.collection_scroll.min(max_scroll as u16);
+
+        let block = Block::bordered().title(format!(
+            " Collection ({} treasures) ",
+            player.collection.len()
+        ));
+        let block = if self.focus == Focus::Collection {
+            block.border_style(Color::Green)
+        } else {
+            block
+        };
+        frame.render_widget(
+            Paragraph::new(lines)
+                .block(block)
+                .scroll((self.collection_scroll, 0)),
+            area,
+        );
+    }
+}
+
+/// The actions on offer right now, with their labels. Gambling, exports and
+/// slot management stay in the prompt interface.
+fn menu_entries(player: &Player) -> Vec<(String, Entry)> {
+    let mut entries = vec![("Hit Rock".to_string(), Entry::Act(Action::HitRock))];
+    for tier in ChestTier::all() {
+        entries.push((
+            format!(
+                "Open {} Chest ({} coins)",
+                tier.params().name,
+                player.chest_cost(tier)
+            ),
+            Entry::Act(Action::OpenChest(tier)),
+        ));
+    }
+    entries.push(match &player.current_boss {
+        Some(boss) => (
+            format!("Hit Boss Rock (HP {}/{})", boss.hp, boss.max_hp),
+            Entry::Act(Action::HitBoss),
+        ),
+        None => (
+            format!(
+                "Challenge Boss Rock (cost {} strength)",
+                crate::boss::CHALLENGE_COST
+            ),
+            Entry::Act(Action::ChallengeBoss),
+        ),
+    });
+    entries.push((
+        format!("Repair Pickaxe (cost {})", player.repair_cost()),
+        Entry::Act(Action::Repair),
+    ));
+    for (t, count) in player.stacked_collection() {
+        if let Some((cost, target)) = t.rarity.craft_recipe()
+            && count >= cost
+        {
+            entries.push((
+                format!("Craft {}× {} → {:?}", cost, t.name, target),
+                Entry::Confirm(
+                    format!("Use {} {}?", cost, t.name),
+                    Action::Craft(t.name.clone()),
+                ),
+            ));
+        }
+    }
+    for loc in Location::all() {
+        if loc != player.location && loc.is_unlocked(player.total_coins_earned) {
+            entries.push((
+                format!("Travel to {}", loc.params().name),
+                Entry::Act(Action::Travel(loc)),
+            ));
+        }
+    }
+    entries.extend([
+        ("Encyclopedia".to_string(), Entry::Act(Action::Encyclopedia)),
+        ("Achievements".to_string(), Entry::Act(Action::Achievements)),
+        ("Stats".to_string(), Entry::Act(Action::Stats)),
+        ("History".to_string(), Entry::Act(Action::History)),
+        ("End Day".to_string(), Entry::Act(Action::EndDay)),
+        ("Save & Quit".to_string(), Entry::Quit),
+    ]);
+    entries
+}
+
+/// The ratatui color for a `colored` one, so the TUI shows what the prompt
+/// interface prints. `colored` names the ANSI light grey "White".
+fn tui_color(color: colored::Color) -> Color {
+    use colored::Color as C;
+    match color {
+        C::Black => Color::Black,
+        C::Red => Color::Red,
+        C::Green => Color::Green,
+        C::Yellow => Color::Yellow,
+        C::Blue => Color::Blue,
+        C::Magenta => Color::Magenta,
+        C::Cyan => Color::Cyan,
+        C::White => Color::Gray,
+        C::BrightBlack => Color::DarkGray,
+        C::BrightRed => Color::LightRed,
+        C::BrightGreen => Color::LightGreen,
+        C::BrightYellow => Color::LightYellow,
+        C::BrightBlue => Color::LightBlue,
+        C::BrightMagenta => Color::LightMagenta,
+        C::BrightCyan => Color::LightCyan,
+        C::BrightWhite => Color::White,
+        C::TrueColor { r, g, b } => Color::Rgb(r, g, b),
+    }
+}
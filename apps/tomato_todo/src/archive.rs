@@ -0,0 +1,90 @@
+use std::fs;
+use std::path::Path;
+
+use chrono::{DateTime, Local};
+use color_eyre::eyre::Result;
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::{Color, Style, Stylize},
+    text::{Line, Span, ToSpan},
+    widgets::{Block, BorderType, List, ListItem, ListState, StatefulWidget},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::TodoItem;
+
+/// A done item moved out of its list by `Z`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Archived {
+    /// The name of the list it was in, for restoring it there.
+    pub list: String,
+    pub item: TodoItem,
+    pub archived_at: DateTime<Local>,
+}
+
+/// The archive kept in `dir`, empty if there isn't one yet.
+pub fn load(dir: &Path) -> Result<Vec<Archived>> {
+    let path = dir.join("archive.json");
+    if !path.exists() {
+        return Ok(vec![]);
+    }
+    let data = fs::read_to_string(path)?;
+    Ok(serde_json::from_str(&data)?)
+}
+
+/// Writes the archive beside the old one and renames it over, so a failed
+/// write leaves the old archive whole.
+pub fn save(dir: &Path, archive: &[Archived]) -> Result<()> {
+    let path = dir.join("archive.json");
+    let tmp = path.with_extension("json.tmp");
+    fs::write(&tmp, serde_json::to_string_pretty(archive)?)?;
+    fs::rename(&tmp, &path)?;
+    Ok(())
+}
+
+/// The archive, newest first, as a list over `area`. `rows` are the
+/// entries that match the search.
+pub fn render(
+    area: Rect,
+    frame: &mut Frame,
+    rows: &[&Archived],
+    state: &mut ListState,
+    search: &str,
+) {
+    let mut title = format!(" Archive · {}", rows.len());
+    if !search.is_empty() {
+        title.push_str(&format!(" · /{}", search));
+    }
+    title.push(' ');
+    let items: Vec<ListItem> = rows
+        .iter()
+        .map(|entry| {
+            let mut details = format!(" · {}", entry.list);
+            if let Some(done) = entry.item.completed_at {
+                details.push_str(&format!(" · done {}", done.format("%b %-d %H:%M")));
+            }
+            details.push_str(&format!(
+                " · archived {}",
+                entry.archived_at.format("%b %-d %H:%M")
+            ));
+            let bullet = if entry.item.is_done { "✓ " } else { "○ " };
+            ListItem::new(Line::from(vec![
+                Span::from(bullet).green(),
+                Span::from(entry.item.short_name()),
+                Span::from(details).dark_gray(),
+            ]))
+        })
+        .collect();
+    List::new(items)
+        .block(
+            Block::bordered()
+                .border_type(BorderType::Rounded)
+                .title(title.to_span().into_centered_line())
+                .title_bottom(" r restore ".to_span().into_right_aligned_line())
+                .fg(Color::Yellow),
+        )
+        .highlight_style(Style::default().bg(Color::DarkGray))
+        .highlight_symbol(">")
+        .render(area, frame.buffer_mut(), state);
+}
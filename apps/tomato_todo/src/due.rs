@@ -0,0 +1,132 @@
+use chrono::{Datelike, Duration, NaiveDate, Weekday};
+use ratatui::style::Color;
+
+/// Splits a trailing `@date` off `text`: `@2024-07-01`, `@today`,
+/// `@tomorrow` or a weekday such as `@fri` (the next one, today included).
+/// Anything else after an `@`, like `@home` or an email address, stays part
+/// of the description.
+pub fn split_due(text: &str, today: NaiveDate) -> (String, Option<NaiveDate>) {
+    let trimmed = text.trim_end();
+    let Some((rest, token)) = trimmed.rsplit_once(char::is_whitespace) else {
+        return (text.to_string(), None);
+    };
+    match token.strip_prefix('@').and_then(|t| parse_date(t, today)) {
+        Some(date) => (rest.trim_end().to_string(), Some(date)),
+        None => (text.to_string(), None),
+    }
+}
+
+fn parse_date(token: &str, today: NaiveDate) -> Option<NaiveDate> {
+    if let Ok(date) = NaiveDate::parse_from_str(token, "%Y-%m-%d") {
+        return Some(date);
+    }
+    match token.to_lowercase().as_str() {
+        "today" => Some(today),
+        "tomorrow" | "tom" => Some(today + Duration::days(1)),
+        day => {
+            let weekday: Weekday = day.parse().ok()?;
+            let ahead =
+                (weekday.num_days_from_monday() + 7 - today.weekday().num_days_from_monday()) % 7;
+            Some(today + Duration::days(ahead as i64))
+        }
+    }
+}
+
+/// The suffix the edit form starts with, so saving it unchanged keeps the date.
+pub fn suffix(due: Option<NaiveDate>) -> String {
+    due.map(|d| format!(" @{}", d.format("%Y-%m-%d")))
+        .unwrap_or_default()
+}
+
+/// Red when overdue, yellow when due today.
+pub fn color(due: NaiveDate, today: NaiveDate) -> Color {
+    if due < today {
+        Color::Red
+    } else if due == today {
+        Color::Yellow
+    } else {
+        Color::DarkGray
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A Wednesday.
+    fn today() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 7, 3).unwrap()
+    }
+
+    fn date(month: u32, day: u32) -> Option<NaiveDate> {
+        NaiveDate::from_ymd_opt(2024, month, day)
+    }
+
+    fn split(text: &str) -> (String, Option<NaiveDate>) {
+        split_due(text, today())
+    }
+
+    #[test]
+    fn iso_dates() {
+        assert_eq!(
+            split("Pay rent @2024-08-01"),
+            ("Pay rent".into(), date(8, 1))
+        );
+        assert_eq!(split("Pay rent @2024-13-01").1, None);
+    }
+
+    #[test]
+    fn relative_dates() {
+        assert_eq!(split("a @today").1, date(7, 3));
+        assert_eq!(split("a @tomorrow").1, date(7, 4));
+        assert_eq!(split("a @tom").1, date(7, 4));
+        assert_eq!(split("a @TODAY").1, date(7, 3));
+    }
+
+    #[test]
+    fn weekdays_are_the_next_one_today_included() {
+        assert_eq!(split("a @wed").1, date(7, 3));
+        assert_eq!(split("a @thu").1, date(7, 4));
+        assert_eq!(split("a @fri").1, date(7, 5));
+        assert_eq!(split("a @Friday").1, date(7, 5));
+        assert_eq!(split("a @mon").1, date(7, 8));
+        assert_eq!(split("a @tue").1, date(7, 9));
+    }
+
+    #[test]
+    fn other_at_signs_stay_in_the_description() {
+        for text in [
+            "Email bob@example.com",
+            "Call @home",
+            "Meet @ noon",
+            "@fri",
+            "Ask @fri about it",
+        ] {
+            assert_eq!(split(text), (text.to_string(), None), "{}", text);
+        }
+    }
+
+    #[test]
+    fn only_the_last_token_is_a_date() {
+        assert_eq!(
+            split("Email bob@example.com @fri"),
+            ("Email bob@example.com".into(), date(7, 5))
+        );
+        assert_eq!(split("Ship it @fri  "), ("Ship it".into(), date(7, 5)));
+    }
+
+    #[test]
+    fn suffix_round_trips() {
+        let due = date(7, 5);
+        let text = format!("Ship it{}", suffix(due));
+        assert_eq!(split(&text), ("Ship it".into(), due));
+        assert_eq!(suffix(None), "");
+    }
+
+    #[test]
+    fn colors() {
+        assert_eq!(color(date(7, 2).unwrap(), today()), Color::Red);
+        assert_eq!(color(today(), today()), Color::Yellow);
+        assert_eq!(color(date(7, 4).unwrap(), today()), Color::DarkGray);
+    }
+}
@@ -0,0 +1,375 @@
+use ratatui::{
+    Frame,
+    crossterm::event::{KeyCode, KeyEvent, KeyModifiers},
+    layout::{Constraint, Flex, Layout, Rect},
+    style::{Color, Stylize},
+    text::{Line, Span, ToSpan},
+    widgets::{Block, BorderType, Clear, Padding, Paragraph},
+};
+
+/// What a key does. `handle_key` acts on these, so the help can't list a
+/// key the app doesn't handle, or miss one it does.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Down,
+    Up,
+    PageDown,
+    PageUp,
+    First,
+    Last,
+    MoveDown,
+    MoveUp,
+    NextList,
+    PreviousList,
+    Add,
+    AddSubtask,
+    Edit,
+    Details,
+    Toggle,
+    Priority,
+    Postpone,
+    ClearDue,
+    Mark,
+    Delete,
+    ClearDone,
+    Archive,
+    Undo,
+    Redo,
+    Sort,
+    Filter,
+    Collapse,
+    Expand,
+    Search,
+    NextMatch,
+    PreviousMatch,
+    Stats,
+    ShowArchive,
+    Restore,
+    NewList,
+    DropList,
+    Export,
+    Focus,
+    Pomodoro,
+    Break,
+    Cancel,
+    Help,
+    Back,
+}
+
+/// A key with the modifiers it needs.
+type Key = (KeyCode, KeyModifiers);
+
+/// Keys, what each does, and what the help says about them.
+type Binding = (&'static [(Key, Action)], &'static str);
+
+/// Keys, by category.
+pub type Bindings = &'static [(&'static str, &'static [Binding])];
+
+const fn key(c: char) -> Key {
+    (KeyCode::Char(c), KeyModifiers::NONE)
+}
+
+const fn code(code: KeyCode) -> Key {
+    (code, KeyModifiers::NONE)
+}
+
+/// The keys of the list view.
+pub const LIST: Bindings = &[
+    (
+        "Move",
+        &[
+            (
+                &[
+                    (key('j'), Action::Down),
+                    (key('k'), Action::Up),
+                    (code(KeyCode::Down), Action::Down),
+                    (code(KeyCode::Up), Action::Up),
+                ],
+                "move",
+            ),
+            (
+                &[
+                    (code(KeyCode::PageUp), Action::PageUp),
+                    (code(KeyCode::PageDown), Action::PageDown),
+                ],
+                "page",
+            ),
+            (
+                &[
+                    (code(KeyCode::Home), Action::First),
+                    (code(KeyCode::End), Action::Last),
+                ],
+                "first/last",
+            ),
+            (
+                &[(key('J'), Action::MoveDown), (key('K'), Action::MoveUp)],
+                "reorder",
+            ),
+            (
+                &[
+                    (code(KeyCode::Tab), Action::NextList),
+                    (code(KeyCode::BackTab), Action::PreviousList),
+                ],
+                "next/previous list",
+            ),
+        ],
+    ),
+    (
+        "Items",
+        &[
+            (&[(key('A'), Action::Add)], "add"),
+            (&[(key('a'), Action::AddSubtask)], "add subtask"),
+            (&[(key('e'), Action::Edit)], "edit"),
+            (&[(key('i'), Action::Details)], "details"),
+            (
+                &[(code(KeyCode::Enter), Action::Toggle)],
+                "toggle done (the marked items, if any)",
+            ),
+            (&[(key('!'), Action::Priority)], "priority"),
+            (
+                &[(key('+'), Action::Postpone), (key('-'), Action::ClearDue)],
+                "postpone/clear due",
+            ),
+            (&[(key(' '), Action::Mark)], "mark for Enter and D"),
+            (
+                &[(key('D'), Action::Delete)],
+                "delete (the marked items, if any)",
+            ),
+            (&[(key('C'), Action::ClearDone)], "clear done"),
+            (&[(key('Z'), Action::Archive)], "archive done"),
+            (
+                &[
+                    (key('u'), Action::Undo),
+                    ((KeyCode::Char('r'), KeyModifiers::CONTROL), Action::Redo),
+                ],
+                "undo/redo",
+            ),
+        ],
+    ),
+    (
+        "View",
+        &[
+            (&[(key('o'), Action::Sort)], "sort"),
+            (&[(key('f'), Action::Filter)], "filter"),
+            (
+                &[(key('h'), Action::Collapse), (key('l'), Action::Expand)],
+                "collapse/expand",
+            ),
+            (&[(key('/'), Action::Search)], "search"),
+            (
+                &[
+                    (key('n'), Action::NextMatch),
+                    (key('N'), Action::PreviousMatch),
+                ],
+                "next/previous match",
+            ),
+            (&[(key('S'), Action::Stats)], "stats"),
+            (&[(key('v'), Action::ShowArchive)], "archive"),
+        ],
+    ),
+    (
+        "Lists",
+        &[
+            (&[(key('L'), Action::NewList)], "new list"),
+            (&[(key('X'), Action::DropList)], "drop empty list"),
+            (&[(key('x'), Action::Export)], "export as Markdown"),
+        ],
+    ),
+    (
+        "Timers",
+        &[
+            (&[(key('F'), Action::Focus)], "focus"),
+            (&[(key('p'), Action::Pomodoro)], "pomodoro"),
+            (&[(key('b'), Action::Break)], "break"),
+            (&[(key('c'), Action::Cancel)], "cancel"),
+        ],
+    ),
+    (
+        "App",
+        &[
+            (&[(key('?'), Action::Help)], "help"),
+            (
+                &[(code(KeyCode::Esc), Action::Back)],
+                "clear marks, then the search, then quit",
+            ),
+        ],
+    ),
+];
+
+/// The keys of the archive, shown with `v`.
+pub const ARCHIVE: Bindings = &[(
+    "Archive",
+    &[
+        (
+            &[
+                (key('j'), Action::Down),
+                (key('k'), Action::Up),
+                (code(KeyCode::Down), Action::Down),
+                (code(KeyCode::Up), Action::Up),
+            ],
+            "move",
+        ),
+        (&[(key('/'), Action::Search)], "search"),
+        (&[(key('r'), Action::Restore)], "restore"),
+        (
+            &[(key('v'), Action::Back), (code(KeyCode::Esc), Action::Back)],
+            "back; Esc clears the search first",
+        ),
+    ],
+)];
+
+/// What `key` does in the view `bindings` are for. Control has to match;
+/// Shift is part of the character.
+pub fn action(bindings: Bindings, key: &KeyEvent) -> Option<Action> {
+    let control = key.modifiers.contains(KeyModifiers::CONTROL);
+    bindings
+        .iter()
+        .flat_map(|(_, rows)| rows.iter())
+        .flat_map(|(keys, _)| keys.iter())
+        .find(|((code, modifiers), _)| {
+            *code == key.code && modifiers.contains(KeyModifiers::CONTROL) == control
+        })
+        .map(|(_, action)| *action)
+}
+
+fn key_name((code, modifiers): &Key) -> String {
+    let name = match code {
+        KeyCode::Char(' ') => "Space".to_string(),
+        KeyCode::Char(c) => c.to_string(),
+        KeyCode::Up => "↑".to_string(),
+        KeyCode::Down => "↓".to_string(),
+        KeyCode::PageUp => "PgUp".to_string(),
+        KeyCode::PageDown => "PgDn".to_string(),
+        KeyCode::BackTab => "Shift+Tab".to_string(),
+        code => code.to_string(),
+    };
+    if modifiers.contains(KeyModifiers::CONTROL) {
+        format!("Ctrl+{}", name)
+    } else {
+        name
+    }
+}
+
+/// A row's keys as the help shows them, such as `+/-`.
+fn keys_label(keys: &[(Key, Action)]) -> String {
+    keys.iter()
+        .map(|(key, _)| key_name(key))
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Every binding on one line, for the status bar.
+pub fn hints(bindings: Bindings) -> String {
+    bindings
+        .iter()
+        .flat_map(|(_, rows)| rows.iter())
+        .map(|(keys, label)| format!("{} {}", keys_label(keys), label))
+        .collect::<Vec<_>>()
+        .join(" · ")
+}
+
+/// A popup over `area` listing the bindings of the list and the archive
+/// under their categories.
+pub fn render(area: Rect, frame: &mut Frame) {
+    let categories = || LIST.iter().chain(ARCHIVE);
+    let key_width = categories()
+        .flat_map(|(_, rows)| rows.iter())
+        .map(|(keys, _)| keys_label(keys).chars().count())
+        .max()
+        .unwrap_or(0);
+    let mut lines = Vec::new();
+    for (category, rows) in categories() {
+        if !lines.is_empty() {
+            lines.push(Line::default());
+        }
+        lines.push(category.to_span().bold().yellow().into());
+        for (keys, label) in rows.iter() {
+            lines.push(Line::from(vec![
+                Span::from(format!("  {:<key_width$}  ", keys_label(keys))).green(),
+                label.to_span(),
+            ]));
+        }
+    }
+
+    let width = lines.iter().map(Line::width).max().unwrap_or(0) as u16 + 4;
+    let [popup] = Layout::horizontal([Constraint::Length(width)])
+        .flex(Flex::Center)
+        .areas(area);
+    let [popup] = Layout::vertical([Constraint::Length(lines.len() as u16 + 2)])
+        .flex(Flex::Center)
+        .areas(popup);
+
+    frame.render_widget(Clear, popup);
+    frame.render_widget(
+        Paragraph::new(lines).block(
+            Block::bordered()
+                .border_type(BorderType::Rounded)
+                .title(" Keys ".to_span().into_centered_line())
+                .padding(Padding::horizontal(1))
+                .fg(Color::White),
+        ),
+        popup,
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn press(bindings: Bindings, code: KeyCode, modifiers: KeyModifiers) -> Option<Action> {
+        action(bindings, &KeyEvent::new(code, modifiers))
+    }
+
+    #[test]
+    fn keys_map_to_their_actions() {
+        let none = KeyModifiers::NONE;
+        assert_eq!(press(LIST, KeyCode::Char('j'), none), Some(Action::Down));
+        assert_eq!(press(LIST, KeyCode::Down, none), Some(Action::Down));
+        assert_eq!(
+            press(LIST, KeyCode::BackTab, KeyModifiers::SHIFT),
+            Some(Action::PreviousList)
+        );
+        assert_eq!(
+            press(LIST, KeyCode::Char('N'), KeyModifiers::SHIFT),
+            Some(Action::PreviousMatch)
+        );
+        assert_eq!(
+            press(LIST, KeyCode::Char('r'), KeyModifiers::CONTROL),
+            Some(Action::Redo)
+        );
+        assert_eq!(press(LIST, KeyCode::Char('r'), none), None);
+        assert_eq!(press(LIST, KeyCode::Char('j'), KeyModifiers::CONTROL), None);
+        assert_eq!(
+            press(ARCHIVE, KeyCode::Char('r'), none),
+            Some(Action::Restore)
+        );
+        assert_eq!(press(ARCHIVE, KeyCode::Esc, none), Some(Action::Back));
+    }
+
+    #[test]
+    fn no_key_is_bound_twice_in_a_view() {
+        for bindings in [LIST, ARCHIVE] {
+            let keys: Vec<&Key> = bindings
+                .iter()
+                .flat_map(|(_, rows)| rows.iter())
+                .flat_map(|(keys, _)| keys.iter().map(|(key, _)| key))
+                .collect();
+            for (i, key) in keys.iter().enumerate() {
+                assert!(!keys[..i].contains(key), "{} is bound twice", key_name(key));
+            }
+        }
+    }
+
+    #[test]
+    fn hints_name_the_keys_as_bound() {
+        let hints = hints(LIST);
+        assert!(
+            hints.contains("Tab/Shift+Tab next/previous list"),
+            "{}",
+            hints
+        );
+        assert!(hints.contains("u/Ctrl+r undo/redo"), "{}", hints);
+        assert!(hints.contains("Space mark"), "{}", hints);
+        assert!(hints.contains("Esc clear marks"), "{}", hints);
+    }
+}
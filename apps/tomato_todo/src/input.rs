@@ -0,0 +1,175 @@
+use unicode_segmentation::UnicodeSegmentation;
+
+/// The text in the form and where the cursor is in it, as a byte offset
+/// that always falls between graphemes, so an emoji moves and deletes as one.
+#[derive(Debug, Default)]
+pub struct Input {
+    value: String,
+    cursor: usize,
+}
+
+impl Input {
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+
+    pub fn cursor(&self) -> usize {
+        self.cursor
+    }
+
+    /// Replaces the text, with the cursor at its end.
+    pub fn set(&mut self, value: String) {
+        self.cursor = value.len();
+        self.value = value;
+    }
+
+    /// Empties the form and returns what was in it.
+    pub fn take(&mut self) -> String {
+        self.cursor = 0;
+        std::mem::take(&mut self.value)
+    }
+
+    pub fn insert(&mut self, text: &str) {
+        self.value.insert_str(self.cursor, text);
+        self.cursor += text.len();
+    }
+
+    /// Removes the grapheme before the cursor.
+    pub fn backspace(&mut self) {
+        let start = self.previous();
+        self.value.replace_range(start..self.cursor, "");
+        self.cursor = start;
+    }
+
+    /// Removes the grapheme after the cursor.
+    pub fn delete(&mut self) {
+        let end = self.next();
+        self.value.replace_range(self.cursor..end, "");
+    }
+
+    pub fn left(&mut self) {
+        self.cursor = self.previous();
+    }
+
+    pub fn right(&mut self) {
+        self.cursor = self.next();
+    }
+
+    /// To the start of the line the cursor is on.
+    pub fn home(&mut self) {
+        self.cursor = self.value[..self.cursor].rfind('\n').map_or(0, |i| i + 1);
+    }
+
+    /// To the end of the line the cursor is on.
+    pub fn end(&mut self) {
+        self.cursor += self.value[self.cursor..]
+            .find('\n')
+            .unwrap_or(self.value.len() - self.cursor);
+    }
+
+    fn previous(&self) -> usize {
+        self.value[..self.cursor]
+            .grapheme_indices(true)
+            .next_back()
+            .map_or(0, |(i, _)| i)
+    }
+
+    fn next(&self) -> usize {
+        self.value[self.cursor..]
+            .graphemes(true)
+            .next()
+            .map_or(self.cursor, |g| self.cursor + g.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A family emoji: several code points joined into one grapheme.
+    const FAMILY: &str = "👨‍👩‍👧";
+
+    fn at_end(value: &str) -> Input {
+        let mut input = Input::default();
+        input.set(value.to_string());
+        input
+    }
+
+    #[test]
+    fn moves_over_an_emoji_in_one_step() {
+        let mut input = at_end(&format!("a{}b", FAMILY));
+        input.left();
+        assert_eq!(input.cursor(), 1 + FAMILY.len());
+        input.left();
+        assert_eq!(input.cursor(), 1);
+        input.right();
+        assert_eq!(input.cursor(), 1 + FAMILY.len());
+    }
+
+    #[test]
+    fn backspace_and_delete_remove_a_whole_emoji() {
+        let mut input = at_end(&format!("a{}b", FAMILY));
+        input.left();
+        input.backspace();
+        assert_eq!(input.value(), "ab");
+        assert_eq!(input.cursor(), 1);
+
+        let mut input = at_end(&format!("a{}🍅b", FAMILY));
+        input.home();
+        input.right();
+        input.delete();
+        assert_eq!(input.value(), "a🍅b");
+        input.delete();
+        assert_eq!(input.value(), "ab");
+    }
+
+    #[test]
+    fn combining_marks_stay_with_their_letter() {
+        let mut input = at_end("cafe\u{301}");
+        input.left();
+        assert_eq!(input.cursor(), 3);
+        input.backspace();
+        assert_eq!(input.value(), "cae\u{301}");
+    }
+
+    #[test]
+    fn inserts_at_the_cursor() {
+        let mut input = at_end("ac");
+        input.left();
+        input.insert(FAMILY);
+        input.insert("b");
+        assert_eq!(input.value(), format!("a{}bc", FAMILY));
+        assert_eq!(input.cursor(), 1 + FAMILY.len() + 1);
+    }
+
+    #[test]
+    fn edges_do_nothing() {
+        let mut input = at_end("ab");
+        input.right();
+        input.delete();
+        assert_eq!((input.value(), input.cursor()), ("ab", 2));
+        input.home();
+        input.left();
+        input.backspace();
+        assert_eq!((input.value(), input.cursor()), ("ab", 0));
+    }
+
+    #[test]
+    fn home_and_end_stay_on_the_line() {
+        let mut input = at_end("one\ntwo\nthree");
+        input.home();
+        assert_eq!(input.cursor(), 8);
+        input.left();
+        input.home();
+        assert_eq!(input.cursor(), 4);
+        input.end();
+        assert_eq!(input.cursor(), 7);
+    }
+
+    #[test]
+    fn take_empties_the_form() {
+        let mut input = at_end("ab");
+        assert_eq!(input.take(), "ab");
+        assert_eq!((input.value(), input.cursor()), ("", 0));
+    }
+}
@@ -1,12 +1,80 @@
-use color_eyre::eyre::Result;
+use std::{
+    cmp::Reverse,
+    collections::{HashSet, VecDeque},
+    fs,
+    io::{self, Write},
+    path::PathBuf,
+    sync::OnceLock,
+    time::{Duration, Instant},
+};
+
+use chrono::{DateTime, Datelike, Local, NaiveDate};
+use clap::Parser;
+use color_eyre::eyre::{Result, eyre};
+use directories::ProjectDirs;
+use help::Action;
+use pomodoro::{Phase, Pomodoro};
 use ratatui::{
     DefaultTerminal, Frame,
-    crossterm::event::{self, Event, KeyEvent},
-    layout::{Constraint, Layout, Rect},
+    crossterm::{
+        event::{self, DisableBracketedPaste, EnableBracketedPaste, Event, KeyEvent, KeyModifiers},
+        execute,
+    },
+    layout::{Constraint, Flex, Layout, Position, Rect},
     style::{Color, Style, Stylize},
-    text::ToSpan,
-    widgets::{Block, BorderType, List, ListItem, ListState, Padding, Paragraph, Widget},
+    text::{Line, Span, ToSpan},
+    widgets::{
+        Block, BorderType, Clear, List, ListItem, ListState, Padding, Paragraph, Scrollbar,
+        ScrollbarOrientation, ScrollbarState, StatefulWidget, Tabs, Widget, Wrap,
+    },
 };
+use serde::{Deserialize, Serialize};
+use sessions::{Focus, Session};
+use unicode_width::UnicodeWidthChar;
+
+mod archive;
+mod due;
+mod help;
+mod input;
+mod markdown;
+mod pomodoro;
+mod sessions;
+mod stats;
+mod store;
+mod undo;
+
+#[derive(Parser)]
+#[command(
+    name = "tomato_todo",
+    version,
+    about = "A todo list with pomodoro timers for the terminal."
+)]
+struct Cli {
+    /// Keep the lists and sessions in DIR instead of the platform's data directory
+    #[arg(long, value_name = "DIR", env = "TOMATO_TODO_DATA_DIR")]
+    data_dir: Option<PathBuf>,
+
+    /// Load the `- [ ]` / `- [x]` tasks of a Markdown file into the list,
+    /// updating tasks already there and adding the rest
+    #[arg(long, value_name = "FILE")]
+    import: Option<PathBuf>,
+
+    /// Replace the list's items with the imported ones instead
+    #[arg(long, requires = "import")]
+    replace: bool,
+
+    /// Write the list to FILE as a Markdown task list and exit
+    #[arg(long, value_name = "FILE")]
+    export: Option<PathBuf>,
+
+    /// The list --import and --export use, by name; the first by default.
+    /// --import creates it if there's none by that name
+    #[arg(long, value_name = "NAME")]
+    list: Option<String>,
+}
+
+/// The data directory given on the command line, if one was.
+static DATA_DIR: OnceLock<PathBuf> = OnceLock::new();
 
 enum FormAction {
     None,
@@ -14,161 +82,2566 @@ enum FormAction {
     Escape,
 }
 
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+enum FormTarget {
+    #[default]
+    Task,
+    List,
+    /// Adding a subtask to the task at this index.
+    Subtask(usize),
+    /// Replacing the description of this item.
+    Edit(ItemPath),
+    /// Narrowing the list to items matching what's typed.
+    Search,
+}
+
+/// What the keyboard goes to instead of the list.
+#[derive(Debug, Default, PartialEq)]
+enum Modal {
+    #[default]
+    None,
+    /// Waiting for `y` before going ahead.
+    Confirm(Confirm),
+    /// Typing into the form.
+    Input(FormTarget),
+    /// The work timer ran out: `b` takes the break, `p` starts another
+    /// pomodoro, `c` stops, and any other key leaves the choice in the timer.
+    Break,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Confirm {
+    /// Deleting this item.
+    Delete(ItemPath),
+    /// Clearing this many done items.
+    ClearDone(usize),
+    /// Deleting every marked item.
+    DeleteMarked(usize),
+}
+
+impl Confirm {
+    /// The dialog's title and question.
+    fn prompt(self, app_state: &AppState) -> (&'static str, String) {
+        match self {
+            Confirm::Delete(path) => {
+                let name = app_state
+                    .item(path)
+                    .map_or(String::new(), TodoItem::short_name);
+                (" Delete ", format!("Delete '{}'? (y/n)", name))
+            }
+            Confirm::DeleteMarked(count) => (
+                " Delete marked ",
+                format!(
+                    "Delete {} marked item{}? (y/n)",
+                    count,
+                    if count == 1 { "" } else { "s" }
+                ),
+            ),
+            Confirm::ClearDone(count) => (
+                " Clear done ",
+                format!(
+                    "Remove {} done item{}? (y/n)",
+                    count,
+                    if count == 1 { "" } else { "s" }
+                ),
+            ),
+        }
+    }
+
+    fn apply(self, app_state: &mut AppState) {
+        match self {
+            Confirm::Delete(path) => {
+                app_state.checkpoint("delete");
+                if let Some(item) = app_state.item(path) {
+                    let message = format!("Deleted '{}' — u to undo", item.short_name());
+                    app_state.post(message);
+                }
+                match path.sub {
+                    Some(sub) => {
+                        app_state.items_mut()[path.task].subtasks.remove(sub);
+                        app_state.complete_parent(path.task);
+                    }
+                    None => {
+                        app_state.items_mut().remove(path.task);
+                    }
+                }
+                app_state.clamp_selection();
+            }
+            Confirm::DeleteMarked(count) => {
+                app_state.delete_marked();
+                app_state.post(format!("Deleted {} items — u to undo", count));
+            }
+            Confirm::ClearDone(count) => {
+                app_state.checkpoint("clear done");
+                app_state.clear_done();
+                app_state.post(format!("Cleared {} done — u to undo", count));
+            }
+        }
+    }
+}
+
 #[derive(Debug, Default)]
 struct AppState {
-    items: Vec<TodoItem>,
+    lists: Vec<TodoList>,
+    current_list: usize,
     list_state: ListState,
-    is_add_new: bool,
-    input_value: String,
+    /// How many rows the list had when last drawn, for paging.
+    page: u16,
+    modal: Modal,
+    input: input::Input,
+    /// What was entered in the add form this session, oldest first, for
+    /// Up and Down to bring back.
+    entered: Vec<String>,
+    /// The entry the form is showing while going through `entered`; `None`
+    /// while it shows what was typed.
+    entered_pos: Option<usize>,
+    /// What was typed before Up first went back through `entered`.
+    draft: String,
+    sessions: Vec<Session>,
+    focus: Option<Focus>,
+    pomodoro: Option<Pomodoro>,
+    show_stats: bool,
+    show_help: bool,
+    /// Showing the archive instead of the list.
+    show_archive: bool,
+    /// Done items archived with `Z`, oldest first, as kept on disk.
+    archive: Vec<archive::Archived>,
+    archive_state: ListState,
+    /// Showing everything about the selected item.
+    show_detail: bool,
+    /// Showing open items by priority, highest first, above done ones.
+    sort_by_priority: bool,
+    filter: Filter,
+    /// Whether a task is marked done once all its subtasks are.
+    auto_complete: bool,
+    /// Items marked with Space, for Enter and `D` to act on together. Any
+    /// change to the lists clears them, as it may renumber the items.
+    marked: HashSet<ItemPath>,
+    /// Only items whose description contains this, ignoring case, are shown.
+    search: String,
+    store: store::Store,
+    history: undo::History<Snapshot>,
+    /// Notes for the status bar, oldest first, and when each was posted.
+    messages: VecDeque<(String, Instant)>,
+    /// When a timer last ran out, so the timer can flash for a while.
+    alerted: Option<Instant>,
+    /// The Markdown file given to `--import`, and the list it went into;
+    /// `x` on that list writes back to it.
+    markdown: Option<(String, PathBuf)>,
 }
 
-#[derive(Debug, Default)]
-struct TodoItem {
-    is_done: bool,
-    description: String,
+/// What undo puts back: every list, the archive, and which item was
+/// selected where.
+#[derive(Debug)]
+struct Snapshot {
+    lists: Vec<TodoList>,
+    archive: Vec<archive::Archived>,
+    current_list: usize,
+    selected: Option<ItemPath>,
 }
 
-fn main() -> Result<()> {
-    let mut state = AppState::default();
+/// Where an item is in a list: a task, or one of that task's subtasks.
+/// Ordered so a task's subtasks come after it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+struct ItemPath {
+    task: usize,
+    sub: Option<usize>,
+}
 
-    color_eyre::install()?;
+impl ItemPath {
+    fn task(task: usize) -> Self {
+        Self { task, sub: None }
+    }
+}
 
-    let terminal = ratatui::init();
-    let result = run(terminal, &mut state);
+/// Which items the list shows.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+enum Filter {
+    #[default]
+    All,
+    Active,
+    Done,
+}
 
-    ratatui::restore();
+impl Filter {
+    fn next(self) -> Self {
+        match self {
+            Filter::All => Filter::Active,
+            Filter::Active => Filter::Done,
+            Filter::Done => Filter::All,
+        }
+    }
 
-    result
+    fn shows(self, item: &TodoItem) -> bool {
+        match self {
+            Filter::All => true,
+            Filter::Active => !item.is_done,
+            Filter::Done => item.is_done,
+        }
+    }
 }
 
-fn run(mut terminal: DefaultTerminal, app_state: &mut AppState) -> Result<()> {
-    loop {
-        terminal.draw(|f| render(f, app_state))?;
-        if let Event::Key(key) = event::read()? {
-            if app_state.is_add_new {
-                match handle_add_new(key, app_state) {
-                    FormAction::None => {}
-                    FormAction::Submit => {
-                        app_state.is_add_new = false;
-                        app_state.items.push(TodoItem {
-                            is_done: false,
-                            description: app_state.input_value.clone(),
-                        });
-                        app_state.input_value.clear();
-                    }
-                    FormAction::Escape => {
-                        app_state.is_add_new = false;
-                        app_state.input_value.clear();
-                    }
+impl AppState {
+    fn items(&self) -> &Vec<TodoItem> {
+        &self.lists[self.current_list].items
+    }
+
+    fn items_mut(&mut self) -> &mut Vec<TodoItem> {
+        &mut self.lists[self.current_list].items
+    }
+
+    fn item(&self, path: ItemPath) -> Option<&TodoItem> {
+        let task = self.items().get(path.task)?;
+        match path.sub {
+            Some(sub) => task.subtasks.get(sub),
+            None => Some(task),
+        }
+    }
+
+    fn item_mut(&mut self, path: ItemPath) -> Option<&mut TodoItem> {
+        let task = self.items_mut().get_mut(path.task)?;
+        match path.sub {
+            Some(sub) => task.subtasks.get_mut(sub),
+            None => Some(task),
+        }
+    }
+
+    /// Marks the task at `task` done when all its subtasks are, and open
+    /// when one isn't, if auto-completion is on.
+    fn complete_parent(&mut self, task: usize) {
+        let auto_complete = self.auto_complete;
+        if let Some(task) = self.items_mut().get_mut(task)
+            && auto_complete
+            && !task.subtasks.is_empty()
+        {
+            let done = task.subtasks.iter().all(|s| s.is_done);
+            task.set_done(done);
+        }
+    }
+
+    /// Remembers the lists as they are, ahead of a change called `label`.
+    fn checkpoint(&mut self, label: &str) {
+        let snapshot = self.snapshot();
+        self.history.record(label, snapshot);
+        self.marked.clear();
+    }
+
+    fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            lists: self.lists.clone(),
+            archive: self.archive.clone(),
+            current_list: self.current_list,
+            selected: self.selected(),
+        }
+    }
+
+    /// Undoes (or with `redo`, redoes) the last change and says which it was.
+    /// An archive the change touched is written back first; if that fails,
+    /// nothing is undone.
+    fn step_history(&mut self, redo: bool) {
+        let mut snapshot = self.snapshot();
+        let label = if redo {
+            self.history.redo(&mut snapshot)
+        } else {
+            self.history.undo(&mut snapshot)
+        };
+        if label.is_some()
+            && snapshot.archive != self.archive
+            && let Err(e) = archive::save(self.store.dir(), &snapshot.archive)
+        {
+            if redo {
+                self.history.undo(&mut snapshot);
+            } else {
+                self.history.redo(&mut snapshot);
+            }
+            self.post(format!("Couldn't save the archive: {}", e));
+            return;
+        }
+        let message = match label {
+            Some(label) => {
+                self.archive = snapshot.archive;
+                self.lists = snapshot.lists;
+                self.switch_list(snapshot.current_list.min(self.lists.len() - 1));
+                if let Some(index) = snapshot.selected {
+                    self.select_item(index);
                 }
+                format!("{} {}", if redo { "Redid" } else { "Undid" }, label)
             }
+            None => format!("Nothing to {}", if redo { "redo" } else { "undo" }),
+        };
+        self.post(message);
+    }
+
+    /// Puts `message` up in the status bar for `MESSAGE_FOR`.
+    fn post(&mut self, message: String) {
+        self.messages.push_back((message, Instant::now()));
+    }
+
+    /// Lets go of messages that have been up for `MESSAGE_FOR`.
+    fn expire_messages(&mut self) {
+        while self
+            .messages
+            .front()
+            .is_some_and(|(_, posted)| posted.elapsed() >= MESSAGE_FOR)
+        {
+            self.messages.pop_front();
+        }
+    }
+
+    /// How many items, subtasks included, the current list has, and how
+    /// many of them are done.
+    fn counts(&self) -> (usize, usize) {
+        self.items()
+            .iter()
+            .flat_map(|item| std::iter::once(item).chain(&item.subtasks))
+            .fold((0, 0), |(total, done), item| {
+                (total + 1, done + usize::from(item.is_done))
+            })
+    }
+
+    fn switch_list(&mut self, index: usize) {
+        self.current_list = index;
+        self.marked.clear();
+        self.list_state = ListState::default();
+        if !self.order().is_empty() {
+            self.list_state.select_first();
+        }
+    }
 
-            if handle_key(key, app_state) {
-                break;
+    /// Credits a pomodoro whose work timer ran out to its task and offers
+    /// a break, or ends one whose break did. Returns whether anything changed.
+    fn tick_pomodoro(&mut self) -> bool {
+        let Some(pomodoro) = self.pomodoro.as_mut().filter(|p| p.is_over()) else {
+            return false;
+        };
+        match pomodoro.phase {
+            Phase::Work => {
+                pomodoro.phase = Phase::BreakOffered;
+                self.sessions.push(pomodoro.session());
+                if let Some(item) = self
+                    .lists
+                    .get_mut(pomodoro.list)
+                    .and_then(|l| l.find_mut(&pomodoro.task))
+                {
+                    item.pomodoros += 1;
+                }
+                let task = pomodoro.task.lines().next().unwrap_or_default().to_string();
+                if self.modal == Modal::None {
+                    self.modal = Modal::Break;
+                }
+                self.alert("Pomodoro done", &format!("{} — time for a break", task));
+            }
+            _ => {
+                self.pomodoro = None;
+                self.alert("Break over", "Back to work");
             }
         }
+        true
     }
-    Ok(())
-}
 
-fn handle_add_new(key: KeyEvent, app_state: &mut AppState) -> FormAction {
-    match key.code {
-        event::KeyCode::Char(c) => {
-            app_state.input_value.push(c);
+    /// Moves the current list's done items to the archive, leaving a done
+    /// task with open subtasks as `clear_done` does. The archive is written
+    /// before the list changes, so a failed write loses nothing, and items
+    /// it already holds aren't added again.
+    fn archive_done(&mut self) {
+        let list = &self.lists[self.current_list];
+        let done: Vec<TodoItem> = list
+            .items
+            .iter()
+            .flat_map(|item| match item.clears() {
+                true => vec![item],
+                false => item.subtasks.iter().filter(|s| s.is_done).collect(),
+            })
+            .cloned()
+            .collect();
+        if done.is_empty() {
+            self.post("Nothing done to archive".to_string());
+            return;
         }
-        event::KeyCode::Backspace => {
-            app_state.input_value.pop();
+        let count = done.len();
+        let mut archive = self.archive.clone();
+        let now = Local::now();
+        for item in done {
+            if !archive
+                .iter()
+                .any(|a| a.list == list.name && a.item == item)
+            {
+                archive.push(archive::Archived {
+                    list: list.name.clone(),
+                    item,
+                    archived_at: now,
+                });
+            }
         }
-        event::KeyCode::Esc => {
-            return FormAction::Escape;
+        if let Err(e) = archive::save(self.store.dir(), &archive) {
+            self.post(format!("Couldn't archive: {}", e));
+            return;
         }
-        event::KeyCode::Enter => {
-            return FormAction::Submit;
+        self.checkpoint("archive");
+        self.archive = archive;
+        self.clear_done();
+        self.post(format!(
+            "Archived {} done item{} — v to view",
+            count,
+            if count == 1 { "" } else { "s" }
+        ));
+    }
+
+    /// The archive entries matching the search, newest first, as indices
+    /// into `archive`.
+    fn archive_rows(&self) -> Vec<usize> {
+        let search = self.search.to_lowercase();
+        (0..self.archive.len())
+            .rev()
+            .filter(|&i| {
+                self.archive[i]
+                    .item
+                    .description
+                    .to_lowercase()
+                    .contains(&search)
+            })
+            .collect()
+    }
+
+    /// Takes the selected entry out of the archive and puts its item back
+    /// at the end of the list it came from, or the current one if that's
+    /// gone. As with archiving, the archive is written first.
+    fn restore_archived(&mut self) {
+        let Some(index) = self
+            .archive_state
+            .selected()
+            .and_then(|row| self.archive_rows().get(row).copied())
+        else {
+            return;
+        };
+        let mut archive = self.archive.clone();
+        let entry = archive.remove(index);
+        if let Err(e) = archive::save(self.store.dir(), &archive) {
+            self.post(format!("Couldn't restore: {}", e));
+            return;
+        }
+        self.checkpoint("restore");
+        self.archive = archive;
+        let list = self
+            .lists
+            .iter()
+            .position(|l| l.name == entry.list)
+            .unwrap_or(self.current_list);
+        self.post(format!(
+            "Restored '{}' to {}",
+            entry.item.short_name(),
+            self.lists[list].name
+        ));
+        self.lists[list].items.push(entry.item);
+        let rows = self.archive_rows().len();
+        if rows == 0 {
+            self.archive_state.select(None);
+        } else if self.archive_state.selected().is_some_and(|row| row >= rows) {
+            self.archive_state.select(Some(rows - 1));
         }
-        _ => {}
     }
-    FormAction::None
-}
 
-fn handle_key(key: KeyEvent, app_state: &mut AppState) -> bool {
-    match key.code {
-        event::KeyCode::Esc => {
-            return true;
+    /// Shows the entry before (or after) the one in the add form, like a
+    /// shell's history; going past the newest brings back what was typed.
+    fn step_entered(&mut self, back: bool) {
+        let pos = match (self.entered_pos, back) {
+            (None, true) => match self.entered.len().checked_sub(1) {
+                Some(last) => {
+                    self.draft = self.input.value().to_string();
+                    Some(last)
+                }
+                None => return,
+            },
+            (None, false) => return,
+            (Some(pos), true) => Some(pos.saturating_sub(1)),
+            (Some(pos), false) => Some(pos + 1).filter(|&next| next < self.entered.len()),
+        };
+        self.entered_pos = pos;
+        let value = match pos {
+            Some(pos) => self.entered[pos].clone(),
+            None => std::mem::take(&mut self.draft),
+        };
+        self.input.set(value);
+    }
+
+    /// Adds what the add form saved to `entered`, unless it's the same as
+    /// the last entry.
+    fn remember(&mut self, value: &str) {
+        self.entered_pos = None;
+        if self.entered.last().is_none_or(|last| last != value) {
+            self.entered.push(value.to_string());
         }
-        event::KeyCode::Enter => {
-            if let Some(index) = app_state.list_state.selected() {
-                if let Some(item) = app_state.items.get_mut(index) {
-                    item.is_done = !item.is_done;
+    }
+
+    /// Writes the current list as Markdown: back to the file it was
+    /// imported from, or to `<list name>.md` in the data directory.
+    fn export_markdown(&mut self) {
+        let list = &self.lists[self.current_list];
+        let path = match &self.markdown {
+            Some((name, path)) if *name == list.name => Ok(path.clone()),
+            _ => get_data_path(&format!("{}.md", list.name.replace(['/', '\\'], "-"))),
+        };
+        let written = path.and_then(|path| {
+            fs::write(&path, markdown::render(&list.items))?;
+            Ok(path)
+        });
+        let message = match written {
+            Ok(path) => format!("Exported '{}' to {}", list.name, path.display()),
+            Err(e) => format!("Couldn't export: {}", e),
+        };
+        self.post(message);
+    }
+
+    /// Rings the terminal bell, flashes the timer and, where built with
+    /// the `notify` feature, shows a desktop notification. None of these
+    /// are worth stopping for if they fail.
+    fn alert(&mut self, summary: &str, body: &str) {
+        let mut stdout = io::stdout();
+        let _ = stdout.write_all(b"\x07").and_then(|_| stdout.flush());
+        self.alerted = Some(Instant::now());
+        if let Err(e) = pomodoro::notify(summary, body) {
+            self.post(format!("Couldn't show a notification: {}", e));
+        }
+    }
+
+    /// Whether the timer is in its flashed state: every other second for
+    /// a few seconds after it runs out.
+    fn flash(&self) -> bool {
+        self.alerted
+            .map(|at| at.elapsed())
+            .is_some_and(|since| since < FLASH_FOR && since.as_secs() % 2 == 0)
+    }
+
+    /// The rows of the list, in the order they're shown: the tasks the
+    /// filter and search let through, each followed by its subtasks that
+    /// pass unless it's collapsed. A task with a passing subtask is shown
+    /// too, for context.
+    fn order(&self) -> Vec<ItemPath> {
+        let items = self.items();
+        let search = self.search.to_lowercase();
+        let passes = |item: &TodoItem| {
+            self.filter.shows(item) && item.description.to_lowercase().contains(&search)
+        };
+        let mut tasks: Vec<usize> = (0..items.len())
+            .filter(|&i| passes(&items[i]) || items[i].subtasks.iter().any(passes))
+            .collect();
+        if self.sort_by_priority {
+            tasks.sort_by_key(|&i| (items[i].is_done, Reverse(items[i].priority)));
+        }
+
+        let mut order = Vec::new();
+        for task in tasks {
+            order.push(ItemPath::task(task));
+            if items[task].collapsed {
+                continue;
+            }
+            for (sub, item) in items[task].subtasks.iter().enumerate() {
+                if passes(item) {
+                    order.push(ItemPath {
+                        task,
+                        sub: Some(sub),
+                    });
                 }
             }
         }
-        event::KeyCode::Char(char) => match char {
-            'j' => {
-                app_state.list_state.select_next();
+        order
+    }
+
+    /// Which item the selected row shows.
+    fn selected(&self) -> Option<ItemPath> {
+        self.list_state
+            .selected()
+            .and_then(|row| self.order().get(row).copied())
+    }
+
+    /// Selects the row showing item `path`, wherever sorting put it; if
+    /// the filter now hides it, the selection stays on the same row.
+    fn select_item(&mut self, path: ItemPath) {
+        match self.order().iter().position(|&p| p == path) {
+            Some(row) => self.list_state.select(Some(row)),
+            None => self.clamp_selection(),
+        }
+    }
+
+    /// Keeps item `selected` selected after the filter or search changed, or
+    /// the same row if it's now hidden, or the first row if nothing was.
+    fn refilter(&mut self, selected: Option<ItemPath>) {
+        match selected {
+            Some(path) => self.select_item(path),
+            None => self.clamp_selection(),
+        }
+        if self.list_state.selected().is_none() && !self.order().is_empty() {
+            self.list_state.select_first();
+        }
+    }
+
+    /// Sets the search to `search`, keeping the selection where it can.
+    fn set_search(&mut self, search: String) {
+        let selected = self.selected();
+        self.search = search;
+        self.refilter(selected);
+    }
+
+    /// Moves the selection to the next match (`1`) or the previous one
+    /// (`-1`), wrapping around at either end.
+    fn cycle_match(&mut self, by: isize) {
+        let count = self.order().len();
+        if count == 0 {
+            return;
+        }
+        let row = self.list_state.selected().unwrap_or(0).min(count - 1);
+        let row = (row as isize + by).rem_euclid(count as isize) as usize;
+        self.list_state.select(Some(row));
+    }
+
+    /// Swaps the selected item with the one above (`-1`) or below (`1`),
+    /// keeping it selected; does nothing at either end of the list. Tasks
+    /// move with their subtasks, and subtasks only within their task.
+    /// Manual order only applies to the unsorted, unfiltered view.
+    fn move_selected(&mut self, by: isize) {
+        if self.sort_by_priority || self.filter != Filter::All || !self.search.is_empty() {
+            return;
+        }
+        let Some(path) = self.selected() else {
+            return;
+        };
+        let (count, index) = match path.sub {
+            Some(sub) => (self.items()[path.task].subtasks.len(), sub),
+            None => (self.items().len(), path.task),
+        };
+        let Some(target) = index.checked_add_signed(by).filter(|t| *t < count) else {
+            return;
+        };
+        // Marks are by position, so they'd point at the wrong items after.
+        self.checkpoint("move");
+        match path.sub {
+            Some(_) => self.items_mut()[path.task].subtasks.swap(index, target),
+            None => self.items_mut().swap(index, target),
+        }
+        self.select_item(match path.sub {
+            Some(_) => ItemPath {
+                sub: Some(target),
+                ..path
+            },
+            None => ItemPath::task(target),
+        });
+    }
+
+    /// Marks the selected item, or unmarks it if it was.
+    fn toggle_mark(&mut self) {
+        if let Some(path) = self.selected()
+            && !self.marked.remove(&path)
+        {
+            self.marked.insert(path);
+        }
+    }
+
+    /// Marks every marked item done, or open again if they all were done.
+    fn complete_marked(&mut self) {
+        let marked: Vec<ItemPath> = self.marked.iter().copied().collect();
+        let done = !marked
+            .iter()
+            .all(|&path| self.item(path).is_some_and(|i| i.is_done));
+        self.checkpoint(if done {
+            "complete marked"
+        } else {
+            "reopen marked"
+        });
+        for &path in &marked {
+            if let Some(item) = self.item_mut(path) {
+                item.set_done(done);
             }
-            'k' => {
-                app_state.list_state.select_previous();
+        }
+        for path in marked {
+            if path.sub.is_some() {
+                self.complete_parent(path.task);
             }
-            'D' => {
-                if let Some(index) = app_state.list_state.selected() {
-                    app_state.items.remove(index);
+        }
+    }
+
+    /// Removes every marked item, last first so the rest keep their places.
+    fn delete_marked(&mut self) {
+        let mut marked: Vec<ItemPath> = self.marked.iter().copied().collect();
+        marked.sort_by(|a, b| b.cmp(a));
+        self.checkpoint("delete marked");
+        for path in marked {
+            match path.sub {
+                Some(sub) => {
+                    if let Some(task) = self.items_mut().get_mut(path.task)
+                        && sub < task.subtasks.len()
+                    {
+                        task.subtasks.remove(sub);
+                        self.complete_parent(path.task);
+                    }
+                }
+                None if path.task < self.items().len() => {
+                    self.items_mut().remove(path.task);
                 }
+                None => {}
             }
-            'A' => {
-                app_state.is_add_new = true;
+        }
+        self.clamp_selection();
+    }
+
+    /// Hides (or with `expand`, shows) the subtasks of the selected task,
+    /// or of the task a selected subtask belongs to, selecting that task.
+    fn set_collapsed(&mut self, collapsed: bool) {
+        let Some(path) = self.selected() else {
+            return;
+        };
+        self.items_mut()[path.task].collapsed = collapsed;
+        self.select_item(ItemPath::task(path.task));
+    }
+
+    /// How many items, subtasks included, `clear_done` would remove.
+    fn clearable(&self) -> usize {
+        self.items()
+            .iter()
+            .map(|item| match item.clears() {
+                true => 1 + item.subtasks.len(),
+                false => item.subtasks.iter().filter(|s| s.is_done).count(),
+            })
+            .sum()
+    }
+
+    /// Removes every done item in the current list, a done task taking its
+    /// subtasks with it, and leaves the selection on the nearest item that
+    /// stays: the selected one if it does, else the next one down the view,
+    /// else the closest one above. A done task with open subtasks stays, so
+    /// they aren't lost; only its done subtasks go.
+    fn clear_done(&mut self) {
+        let order = self.order();
+        let row = self.list_state.selected().unwrap_or(0).min(order.len());
+        let items = self.items();
+        let tasks_before = |i: usize| items[..i].iter().filter(|item| item.clears()).count();
+        let subtasks_before =
+            |subtasks: &[TodoItem], i: usize| subtasks[..i].iter().filter(|s| s.is_done).count();
+        let keep = order[row..]
+            .iter()
+            .chain(order[..row].iter().rev())
+            .find(|p| {
+                let task = &items[p.task];
+                !task.clears() && p.sub.is_none_or(|sub| !task.subtasks[sub].is_done)
+            })
+            .map(|p| ItemPath {
+                task: p.task - tasks_before(p.task),
+                sub: p
+                    .sub
+                    .map(|sub| sub - subtasks_before(&items[p.task].subtasks, sub)),
+            });
+
+        self.items_mut().retain(|item| !item.clears());
+        for item in self.items_mut() {
+            item.subtasks.retain(|sub| !sub.is_done);
+        }
+        match keep {
+            Some(path) => self.select_item(path),
+            None => self.list_state.select(None),
+        }
+    }
+
+    /// Keeps the selection on an existing item; the list scrolls it into
+    /// view when drawn at the new size.
+    fn clamp_selection(&mut self) {
+        let last = self.order().len().checked_sub(1);
+        if let Some(selected) = self.list_state.selected() {
+            self.list_state.select(last.map(|last| selected.min(last)));
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+struct TodoList {
+    name: String,
+    items: Vec<TodoItem>,
+}
+
+impl TodoList {
+    /// The first task or subtask described as `description`.
+    fn find_mut(&mut self, description: &str) -> Option<&mut TodoItem> {
+        for item in &mut self.items {
+            if item.description == description {
+                return Some(item);
             }
-            _ => {}
-        },
-        _ => {}
+            if let Some(sub) = item
+                .subtasks
+                .iter_mut()
+                .find(|s| s.description == description)
+            {
+                return Some(sub);
+            }
+        }
+        None
     }
-    false
 }
 
-fn render(frame: &mut Frame, app_state: &mut AppState) {
-    let [border_area] = Layout::vertical([Constraint::Fill(1)])
-        .margin(1)
-        .areas(frame.area());
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+struct TodoItem {
+    is_done: bool,
+    description: String,
+    #[serde(default)]
+    priority: Priority,
+    #[serde(default)]
+    due: Option<NaiveDate>,
+    /// Pomodoros completed on this task.
+    #[serde(default)]
+    pomodoros: u32,
+    /// Child tasks, listed indented beneath this one.
+    #[serde(default)]
+    subtasks: Vec<TodoItem>,
+    /// The subtasks are hidden in the list.
+    #[serde(default)]
+    collapsed: bool,
+    /// When the item was added; unknown for items from before this was kept.
+    #[serde(default)]
+    created_at: Option<DateTime<Local>>,
+    /// When the item was last marked done, while it stays done.
+    #[serde(default)]
+    completed_at: Option<DateTime<Local>>,
+}
 
-    if app_state.is_add_new {
-        render_input_form(app_state, frame);
-    } else {
-        render_list(border_area, frame, app_state);
+impl TodoItem {
+    /// Marks the item done or open, noting when it was done.
+    fn set_done(&mut self, done: bool) {
+        if done != self.is_done {
+            self.is_done = done;
+            self.completed_at = done.then(Local::now);
+        }
+    }
+
+    /// Whether clearing done items takes this task away: it's done, and so
+    /// is every subtask, so nothing unfinished goes with it.
+    fn clears(&self) -> bool {
+        self.is_done && self.subtasks.iter().all(|s| s.is_done)
+    }
+    /// The first line of the description, cut to 40 characters.
+    fn short_name(&self) -> String {
+        let first = self.description.lines().next().unwrap_or_default();
+        let mut name: String = first.chars().take(40).collect();
+        if name.len() < self.description.len() {
+            name.push('…');
+        }
+        name
     }
 }
 
-fn render_input_form(app_state: &mut AppState, frame: &mut Frame) {
-    Paragraph::new(app_state.input_value.as_str())
-        .block(
-            Block::bordered()
-                .title(" Input Description ".to_span().into_centered_line())
-                .fg(Color::Green)
-                .padding(Padding::uniform(1))
-                .border_type(BorderType::Rounded),
-        )
-        .render(frame.area(), frame.buffer_mut());
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+enum Priority {
+    Low,
+    #[default]
+    Medium,
+    High,
 }
 
-fn render_list(border_area: Rect, frame: &mut Frame, app_state: &mut AppState) {
-    let [inner_area] = Layout::vertical([Constraint::Fill(1)])
-        .margin(1)
-        .areas(border_area);
+impl Priority {
+    fn next(self) -> Self {
+        match self {
+            Priority::Low => Priority::Medium,
+            Priority::Medium => Priority::High,
+            Priority::High => Priority::Low,
+        }
+    }
 
-    Block::bordered()
-        .border_type(BorderType::Rounded)
-        .title(" Tomato ".to_span().into_centered_line())
-        .fg(Color::Yellow)
-        .render(border_area, frame.buffer_mut());
+    fn color(self) -> Color {
+        match self {
+            Priority::Low => Color::Blue,
+            Priority::Medium => Color::Yellow,
+            Priority::High => Color::Red,
+        }
+    }
+}
+
+fn main() -> Result<()> {
+    color_eyre::install()?;
+    let cli = Cli::parse();
+    if let Some(dir) = cli.data_dir {
+        DATA_DIR.get_or_init(|| dir);
+    }
+
+    let (store, lists) = store::Store::open()?;
+    let mut state = AppState {
+        lists,
+        sessions: sessions::load_sessions()?,
+        archive: archive::load(store.dir())?,
+        store,
+        auto_complete: std::env::var(AUTO_COMPLETE_VAR).map_or(true, |v| {
+            !matches!(
+                v.to_lowercase().as_str(),
+                "0" | "false" | "no" | "n" | "off"
+            )
+        }),
+        ..Default::default()
+    };
+    if state.lists.is_empty() {
+        state.lists.push(TodoList {
+            name: "Tasks".into(),
+            items: vec![],
+        });
+    }
 
-    let list = List::new(app_state.items.iter().map(|i| {
-        let value = if i.is_done {
-            i.description.to_span().crossed_out()
+    let index = match &cli.list {
+        Some(name) => match state.lists.iter().position(|l| l.name == *name) {
+            Some(index) => index,
+            None if cli.import.is_some() => {
+                state.lists.push(TodoList {
+                    name: name.clone(),
+                    items: vec![],
+                });
+                state.lists.len() - 1
+            }
+            None => return Err(eyre!("no list named '{}'", name)),
+        },
+        None => 0,
+    };
+    if let Some(path) = cli.import {
+        let imported = markdown::parse(&fs::read_to_string(&path)?, Local::now());
+        let list = &mut state.lists[index];
+        if cli.replace {
+            list.items = imported;
         } else {
-            i.description.to_span()
-        };
-        ListItem::from(value)
-    }))
-    .highlight_symbol(">")
-    .highlight_style(Style::default().fg(Color::Green));
+            markdown::merge(&mut list.items, imported);
+        }
+        state.markdown = Some((list.name.clone(), path));
+        state.store.save(&state.lists)?;
+    }
+    if let Some(path) = cli.export {
+        let list = &state.lists[index];
+        fs::write(&path, markdown::render(&list.items))?;
+        println!("Exported '{}' to {}", list.name, path.display());
+        return state.store.close(&state.lists);
+    }
+    state.switch_list(index);
 
-    frame.render_stateful_widget(list, inner_area, &mut app_state.list_state);
+    let terminal = ratatui::init();
+    // Pastes arrive whole rather than as keys, so a newline in one can't
+    // submit the form.
+    execute!(io::stdout(), EnableBracketedPaste)?;
+    let result = run(terminal, &mut state);
+
+    // Restore the terminal even if this fails.
+    let _ = execute!(io::stdout(), DisableBracketedPaste);
+    ratatui::restore();
+
+    result?;
+    state.store.close(&state.lists)
+}
+
+/// Set to `0` (or `false`, `no`, `off`) to stop tasks being marked done
+/// when their last open subtask is.
+const AUTO_COMPLETE_VAR: &str = "TOMATO_TODO_AUTO_COMPLETE";
+/// How often the screen redraws while waiting for a key, so timers tick.
+const TICK: Duration = Duration::from_secs(1);
+/// How long a status bar message stays up.
+const MESSAGE_FOR: Duration = Duration::from_secs(3);
+/// How long the timer flashes after running out.
+const FLASH_FOR: Duration = Duration::from_secs(6);
+
+fn run(mut terminal: DefaultTerminal, app_state: &mut AppState) -> Result<()> {
+    loop {
+        if app_state.tick_pomodoro() {
+            save(app_state)?;
+        }
+        app_state.expire_messages();
+        terminal.draw(|f| render(f, app_state))?;
+        if !event::poll(TICK)? {
+            continue;
+        }
+        let key = match event::read()? {
+            Event::Key(key) => key,
+            Event::Paste(text) => {
+                if let Modal::Input(target) = app_state.modal {
+                    paste(&text, target, app_state);
+                }
+                continue;
+            }
+            Event::Resize(..) => {
+                app_state.clamp_selection();
+                continue;
+            }
+            _ => continue,
+        };
+        if let Modal::Input(target) = app_state.modal {
+            handle_form(key, target, app_state);
+        } else if handle_key(key, app_state) {
+            break;
+        }
+        save(app_state)?;
+    }
+    Ok(())
+}
+
+/// A key for the form showing `target`: edits the text, or saves or
+/// cancels what it's for.
+fn handle_form(key: KeyEvent, target: FormTarget, app_state: &mut AppState) {
+    match handle_add_new(key, app_state) {
+        FormAction::None if target == FormTarget::Search => {
+            app_state.set_search(app_state.input.value().to_string());
+        }
+        FormAction::None => {}
+        FormAction::Submit => {
+            app_state.modal = Modal::None;
+            let value = app_state.input.take();
+            if matches!(target, FormTarget::Task | FormTarget::Subtask(_)) {
+                app_state.remember(&value);
+            }
+            match target {
+                FormTarget::Task => {
+                    app_state.checkpoint("add");
+                    let (description, due) = due::split_due(&value, today());
+                    app_state.items_mut().push(TodoItem {
+                        description,
+                        due,
+                        created_at: Some(Local::now()),
+                        ..Default::default()
+                    })
+                }
+                FormTarget::Subtask(task) => {
+                    app_state.checkpoint("add subtask");
+                    let (description, due) = due::split_due(&value, today());
+                    let parent = &mut app_state.items_mut()[task];
+                    parent.collapsed = false;
+                    parent.subtasks.push(TodoItem {
+                        description,
+                        due,
+                        created_at: Some(Local::now()),
+                        ..Default::default()
+                    });
+                    let sub = parent.subtasks.len() - 1;
+                    app_state.complete_parent(task);
+                    app_state.select_item(ItemPath {
+                        task,
+                        sub: Some(sub),
+                    });
+                }
+                FormTarget::List => {
+                    app_state.lists.push(TodoList {
+                        name: value,
+                        items: vec![],
+                    });
+                    app_state.switch_list(app_state.lists.len() - 1);
+                }
+                FormTarget::Edit(path) => {
+                    app_state.checkpoint("edit");
+                    let list = app_state.current_list;
+                    if let Some(item) = app_state.item_mut(path) {
+                        let (description, due) = due::split_due(&value, today());
+                        item.due = due;
+                        let old = std::mem::replace(&mut item.description, description);
+                        let new = item.description.clone();
+                        // Keep a running pomodoro pointed at the renamed task.
+                        if let Some(p) = &mut app_state.pomodoro
+                            && p.list == list
+                            && p.task == old
+                        {
+                            p.task = new;
+                        }
+                    }
+                }
+                FormTarget::Search => {
+                    app_state.set_search(value);
+                    if !app_state.order().is_empty() {
+                        app_state.list_state.select_first();
+                    }
+                }
+            }
+        }
+        FormAction::Escape => {
+            app_state.modal = Modal::None;
+            app_state.entered_pos = None;
+            app_state.input.take();
+            if target == FormTarget::Search {
+                app_state.set_search(String::new());
+            }
+        }
+    }
+}
+
+fn save(app_state: &mut AppState) -> Result<()> {
+    // Whatever else the change posted says more than this does.
+    if app_state.store.save(&app_state.lists)? && app_state.messages.is_empty() {
+        app_state.post("Saved".to_string());
+    }
+    sessions::save_sessions(&app_state.sessions)
+}
+
+/// Puts pasted text in the form at the cursor. Only descriptions can have
+/// more than one line; elsewhere line breaks become spaces.
+fn paste(text: &str, target: FormTarget, app_state: &mut AppState) {
+    let text = text.replace("\r\n", "\n").replace('\r', "\n");
+    match target {
+        FormTarget::Task | FormTarget::Subtask(_) | FormTarget::Edit(_) => {
+            app_state.input.insert(&text)
+        }
+        FormTarget::List => app_state.input.insert(&text.replace('\n', " ")),
+        FormTarget::Search => {
+            app_state.input.insert(&text.replace('\n', " "));
+            app_state.set_search(app_state.input.value().to_string());
+        }
+    }
+}
+
+fn handle_add_new(key: KeyEvent, app_state: &mut AppState) -> FormAction {
+    let adding = matches!(
+        app_state.modal,
+        Modal::Input(FormTarget::Task | FormTarget::Subtask(_))
+    );
+    match key.code {
+        event::KeyCode::Char(c) => {
+            app_state.input.insert(c.encode_utf8(&mut [0; 4]));
+        }
+        event::KeyCode::Backspace => app_state.input.backspace(),
+        event::KeyCode::Delete => app_state.input.delete(),
+        event::KeyCode::Left => app_state.input.left(),
+        event::KeyCode::Right => app_state.input.right(),
+        event::KeyCode::Home => app_state.input.home(),
+        event::KeyCode::End => app_state.input.end(),
+        event::KeyCode::Up if adding => app_state.step_entered(true),
+        event::KeyCode::Down if adding => app_state.step_entered(false),
+        event::KeyCode::Esc => {
+            return FormAction::Escape;
+        }
+        event::KeyCode::Enter
+            if key
+                .modifiers
+                .intersects(KeyModifiers::SHIFT | KeyModifiers::ALT) =>
+        {
+            app_state.input.insert("\n");
+        }
+        event::KeyCode::Enter => {
+            return FormAction::Submit;
+        }
+        _ => {}
+    }
+    FormAction::None
+}
+
+fn handle_key(key: KeyEvent, app_state: &mut AppState) -> bool {
+    if app_state.show_help || app_state.show_detail {
+        app_state.show_help = false;
+        app_state.show_detail = false;
+        return false;
+    }
+    // Whatever the answer, the key ends here rather than reaching the list.
+    if let Modal::Confirm(confirm) = app_state.modal {
+        app_state.modal = Modal::None;
+        if matches!(key.code, event::KeyCode::Char('y' | 'Y')) {
+            confirm.apply(app_state);
+        }
+        return false;
+    }
+    // `b`, `p` and `c` do what they do from the list; anything else just
+    // closes the prompt.
+    if app_state.modal == Modal::Break {
+        app_state.modal = Modal::None;
+        if !matches!(
+            help::action(help::LIST, &key),
+            Some(Action::Break | Action::Pomodoro | Action::Cancel)
+        ) {
+            return false;
+        }
+    }
+    if app_state.show_archive {
+        match help::action(help::ARCHIVE, &key) {
+            Some(Action::Back)
+                if key.code == event::KeyCode::Esc && !app_state.search.is_empty() =>
+            {
+                app_state.set_search(String::new());
+            }
+            Some(Action::Back) => app_state.show_archive = false,
+            Some(Action::Down) => app_state.archive_state.select_next(),
+            Some(Action::Up) => app_state.archive_state.select_previous(),
+            Some(Action::Search) => {
+                app_state.input.set(app_state.search.clone());
+                app_state.modal = Modal::Input(FormTarget::Search);
+            }
+            Some(Action::Restore) => app_state.restore_archived(),
+            _ => {}
+        }
+        return false;
+    }
+    if app_state.show_stats {
+        if matches!(key.code, event::KeyCode::Esc | event::KeyCode::Char('S')) {
+            app_state.show_stats = false;
+        }
+        return false;
+    }
+    let Some(action) = help::action(help::LIST, &key) else {
+        return false;
+    };
+    match action {
+        // Esc drops marks, then an active search, before it quits.
+        Action::Back if !app_state.marked.is_empty() => {
+            app_state.marked.clear();
+        }
+        Action::Back if !app_state.search.is_empty() => {
+            app_state.set_search(String::new());
+        }
+        Action::Back => {
+            return true;
+        }
+        Action::Toggle if !app_state.marked.is_empty() => {
+            let selected = app_state.selected();
+            app_state.complete_marked();
+            if let Some(path) = selected {
+                app_state.select_item(path);
+            }
+        }
+        Action::Toggle => {
+            if let Some(path) = app_state.selected() {
+                app_state.checkpoint("toggle");
+                if let Some(item) = app_state.item_mut(path) {
+                    item.set_done(!item.is_done);
+                }
+                if path.sub.is_some() {
+                    app_state.complete_parent(path.task);
+                }
+                app_state.select_item(path);
+            }
+        }
+        Action::Down => {
+            app_state.list_state.select_next();
+        }
+        Action::Up => {
+            app_state.list_state.select_previous();
+        }
+        Action::PageDown => {
+            app_state.list_state.scroll_down_by(app_state.page.max(1));
+        }
+        Action::PageUp => {
+            app_state.list_state.scroll_up_by(app_state.page.max(1));
+        }
+        Action::First => {
+            app_state.list_state.select_first();
+        }
+        Action::Last => {
+            app_state.list_state.select_last();
+        }
+        Action::NextList => {
+            app_state.switch_list((app_state.current_list + 1) % app_state.lists.len());
+        }
+        Action::PreviousList => {
+            let count = app_state.lists.len();
+            app_state.switch_list((app_state.current_list + count - 1) % count);
+        }
+        Action::Redo => {
+            app_state.step_history(true);
+        }
+        Action::MoveDown => app_state.move_selected(1),
+        Action::MoveUp => app_state.move_selected(-1),
+        Action::Mark => app_state.toggle_mark(),
+        Action::Delete if !app_state.marked.is_empty() => {
+            app_state.modal = Modal::Confirm(Confirm::DeleteMarked(app_state.marked.len()));
+        }
+        Action::Delete => {
+            if let Some(path) = app_state.selected() {
+                app_state.modal = Modal::Confirm(Confirm::Delete(path));
+            }
+        }
+        Action::Priority => {
+            if let Some(path) = app_state.selected() {
+                app_state.checkpoint("priority change");
+                if let Some(item) = app_state.item_mut(path) {
+                    item.priority = item.priority.next();
+                }
+                app_state.select_item(path);
+            }
+        }
+        Action::Postpone => {
+            if let Some(path) = app_state.selected() {
+                app_state.checkpoint("postpone");
+                if let Some(item) = app_state.item_mut(path) {
+                    item.due = Some(item.due.unwrap_or(today()) + chrono::Duration::days(1));
+                }
+            }
+        }
+        Action::ClearDue => {
+            if let Some(path) = app_state.selected() {
+                app_state.checkpoint("due date clear");
+                if let Some(item) = app_state.item_mut(path) {
+                    item.due = None;
+                }
+            }
+        }
+        Action::Filter => {
+            let selected = app_state.selected();
+            app_state.filter = app_state.filter.next();
+            app_state.refilter(selected);
+        }
+        Action::Search => {
+            app_state.input.set(app_state.search.clone());
+            app_state.modal = Modal::Input(FormTarget::Search);
+        }
+        Action::NextMatch if !app_state.search.is_empty() => app_state.cycle_match(1),
+        Action::PreviousMatch if !app_state.search.is_empty() => app_state.cycle_match(-1),
+        Action::Sort => {
+            let selected = app_state.selected();
+            app_state.sort_by_priority = !app_state.sort_by_priority;
+            if let Some(path) = selected {
+                app_state.select_item(path);
+            }
+        }
+        Action::Add => {
+            app_state.modal = Modal::Input(FormTarget::Task);
+        }
+        Action::AddSubtask => {
+            if let Some(path) = app_state.selected() {
+                app_state.modal = Modal::Input(FormTarget::Subtask(path.task));
+            }
+        }
+        Action::Collapse => app_state.set_collapsed(true),
+        Action::Expand => app_state.set_collapsed(false),
+        Action::NewList => {
+            app_state.modal = Modal::Input(FormTarget::List);
+        }
+        Action::Edit => {
+            if let Some(path) = app_state.selected()
+                && let Some(item) = app_state.item(path)
+            {
+                let value = format!("{}{}", item.description, due::suffix(item.due));
+                app_state.input.set(value);
+                app_state.modal = Modal::Input(FormTarget::Edit(path));
+            }
+        }
+        Action::Focus => match app_state.focus.take() {
+            Some(focus) => app_state.sessions.push(focus.finish()),
+            None => {
+                app_state.focus = app_state
+                    .selected()
+                    .and_then(|path| app_state.item(path))
+                    .map(|item| Focus {
+                        task: item.description.clone(),
+                        started: Local::now(),
+                    });
+            }
+        },
+        Action::Stats => {
+            app_state.show_stats = true;
+        }
+        Action::Pomodoro => match &mut app_state.pomodoro {
+            Some(p) if p.phase == Phase::BreakOffered => {
+                *p = Pomodoro::start(p.task.clone(), p.list);
+            }
+            Some(p) => p.toggle_pause(),
+            None => {
+                app_state.pomodoro = app_state
+                    .selected()
+                    .and_then(|path| app_state.item(path))
+                    .map(|item| Pomodoro::start(item.description.clone(), app_state.current_list));
+            }
+        },
+        Action::Break => {
+            if let Some(p) = &mut app_state.pomodoro
+                && p.phase == Phase::BreakOffered
+            {
+                p.start_break();
+            }
+        }
+        Action::Cancel => {
+            app_state.pomodoro = None;
+        }
+        Action::Undo => app_state.step_history(false),
+        Action::Help => app_state.show_help = true,
+        Action::Details => app_state.show_detail = app_state.selected().is_some(),
+        Action::ClearDone => {
+            let done = app_state.clearable();
+            if done > 0 {
+                app_state.modal = Modal::Confirm(Confirm::ClearDone(done));
+            }
+        }
+        Action::Export => app_state.export_markdown(),
+        Action::Archive => app_state.archive_done(),
+        Action::ShowArchive => {
+            app_state.show_archive = true;
+            app_state.archive_state.select_first();
+        }
+        // Only empty lists can be removed, and there is always one left.
+        Action::DropList if app_state.items().is_empty() && app_state.lists.len() > 1 => {
+            app_state.checkpoint("list removal");
+            app_state.lists.remove(app_state.current_list);
+            app_state.switch_list(app_state.current_list.saturating_sub(1));
+        }
+        _ => {}
+    }
+    false
+}
+
+fn render(frame: &mut Frame, app_state: &mut AppState) {
+    let [main_area, status_area] =
+        Layout::vertical([Constraint::Fill(1), Constraint::Length(1)]).areas(frame.area());
+    let [border_area] = Layout::vertical([Constraint::Fill(1)])
+        .margin(1)
+        .areas(main_area);
+
+    if let Modal::Input(FormTarget::Search) = app_state.modal {
+        // The list stays in view below the box as it narrows.
+        let [search_area, list_area] =
+            Layout::vertical([Constraint::Length(3), Constraint::Fill(1)]).areas(border_area);
+        if app_state.show_archive {
+            render_archive(list_area, frame, app_state);
+        } else {
+            render_list(list_area, frame, app_state);
+        }
+        render_input_form(search_area, frame, app_state, FormTarget::Search);
+    } else if let Modal::Input(target) = app_state.modal {
+        render_input_form(main_area, frame, app_state, target);
+    } else if app_state.show_archive {
+        render_archive(border_area, frame, app_state);
+    } else if app_state.show_stats {
+        stats::render(
+            border_area,
+            frame,
+            &app_state.lists,
+            &app_state.archive,
+            &app_state.sessions,
+        );
+    } else {
+        render_list(border_area, frame, app_state);
+        if let Modal::Confirm(confirm) = app_state.modal {
+            let (title, question) = confirm.prompt(app_state);
+            render_confirm(border_area, frame, title, &question);
+        }
+        if app_state.modal == Modal::Break
+            && let Some(pomodoro) = &app_state.pomodoro
+        {
+            render_break(border_area, frame, &pomodoro.task);
+        }
+        if app_state.show_help {
+            help::render(border_area, frame);
+        }
+        if app_state.show_detail
+            && let Some(item) = app_state.selected().and_then(|path| app_state.item(path))
+        {
+            render_detail(border_area, frame, item);
+        }
+    }
+    render_status_bar(status_area, frame, app_state);
+}
+
+fn render_status_bar(area: Rect, frame: &mut Frame, app_state: &AppState) {
+    let (mode, hints) = if let Modal::Input(FormTarget::Search) = app_state.modal {
+        (
+            " SEARCH ",
+            "Enter keep and jump to first match · Esc clear".to_string(),
+        )
+    } else if let Modal::Input(target) = app_state.modal {
+        let (mode, recall) = match target {
+            FormTarget::Edit(_) => (" EDIT ", ""),
+            FormTarget::Task | FormTarget::Subtask(_) => (" ADD ", " · ↑/↓ earlier entries"),
+            _ => (" ADD ", ""),
+        };
+        (
+            mode,
+            format!(
+                "Enter save · end with @date, @fri or @tomorrow for a due date · Alt/Shift+Enter new line{} · Esc cancel",
+                recall
+            ),
+        )
+    } else if app_state.show_archive {
+        (" ARCHIVE ", help::hints(help::ARCHIVE))
+    } else if app_state.show_stats {
+        (" STATS ", "S/Esc back".to_string())
+    } else if app_state.show_help {
+        (" HELP ", "any key closes".to_string())
+    } else if app_state.show_detail {
+        (" DETAIL ", "any key closes".to_string())
+    } else if app_state.modal == Modal::Break {
+        (
+            " BREAK ",
+            "b take break · p another pomodoro · c stop · any other key hides this".to_string(),
+        )
+    } else if app_state.modal != Modal::None {
+        (" CONFIRM ", "y yes · any other key cancels".to_string())
+    } else if let Some(focus) = &app_state.focus {
+        (
+            " FOCUS ",
+            format!(
+                "{} since {} · F finish session",
+                focus.task.lines().next().unwrap_or_default(),
+                focus.started.format("%H:%M")
+            ),
+        )
+    } else if let Some(pomodoro) = &app_state.pomodoro {
+        let hints = match pomodoro.phase {
+            Phase::BreakOffered => "b take break · p another pomodoro · c stop",
+            _ => "p pause/resume · c cancel",
+        };
+        (" POMODORO ", hints.to_string())
+    } else {
+        (" NORMAL ", help::hints(help::LIST))
+    };
+    let (total, done) = app_state.counts();
+    let mut summary = format!(
+        " {} item{} · {} active · {} done",
+        total,
+        if total == 1 { "" } else { "s" },
+        total - done,
+        done
+    );
+    if app_state.filter != Filter::All {
+        summary.push_str(&format!(" · {:?}", app_state.filter));
+    }
+    if !app_state.search.is_empty() {
+        summary.push_str(&format!(" · /{}", app_state.search));
+    }
+    if !app_state.marked.is_empty() {
+        summary.push_str(&format!(" · {} marked", app_state.marked.len()));
+    }
+    if let Some(pomodoro) = &app_state.pomodoro {
+        summary.push_str(&format!(" · {}", pomodoro.clock()));
+    }
+    summary.push_str(&format!(
+        " · 🍅 {} this week ",
+        sessions::this_week(&app_state.sessions)
+    ));
+    let [hints_area, summary_area] = Layout::horizontal([
+        Constraint::Fill(1),
+        Constraint::Length(line_width(&summary) as u16),
+    ])
+    .areas(area);
+
+    Line::from(vec![
+        mode.to_span().black().on_yellow().bold(),
+        " ".to_span(),
+        if app_state.messages.is_empty() {
+            hints.to_span().dark_gray()
+        } else {
+            // Newest first, in case they don't all fit.
+            let messages: Vec<&str> = app_state
+                .messages
+                .iter()
+                .rev()
+                .map(|(message, _)| message.as_str())
+                .collect();
+            Span::from(messages.join(" · ")).yellow()
+        },
+    ])
+    .render(hints_area, frame.buffer_mut());
+    summary
+        .to_span()
+        .yellow()
+        .into_right_aligned_line()
+        .render(summary_area, frame.buffer_mut());
+}
+
+fn render_input_form(area: Rect, frame: &mut Frame, app_state: &mut AppState, target: FormTarget) {
+    let title = match target {
+        FormTarget::Task => " Input Description ",
+        FormTarget::List => " New List Name ",
+        FormTarget::Subtask(_) => " Input Subtask ",
+        FormTarget::Edit(_) => " Edit Description ",
+        FormTarget::Search => " Search ",
+    };
+    let padding = match target {
+        FormTarget::Search => Padding::horizontal(1),
+        _ => Padding::uniform(1),
+    };
+    let block = Block::bordered()
+        .title(title.to_span().into_centered_line())
+        .fg(Color::Green)
+        .padding(padding)
+        .border_type(BorderType::Rounded);
+    let inner = block.inner(area);
+
+    let width = inner.width as usize;
+    let lines = wrap_text(app_state.input.value(), width);
+    let (row, column) = cursor_position(app_state.input.value(), app_state.input.cursor(), width);
+    // Keep the cursor line in view once the text outgrows the box.
+    let scroll = (row as u16 + 1).saturating_sub(inner.height);
+
+    Paragraph::new(lines.into_iter().map(Line::from).collect::<Vec<_>>())
+        .block(block)
+        .scroll((scroll, 0))
+        .render(area, frame.buffer_mut());
+    frame.set_cursor_position(Position::new(
+        (inner.x + column as u16).min(inner.right().saturating_sub(1)),
+        inner.y + row as u16 - scroll,
+    ));
+}
+
+/// The row and column of byte `cursor` of `text` once wrapped by
+/// `wrap_text` at `width`. A cursor on a wrap goes at the start of the
+/// next row.
+fn cursor_position(text: &str, cursor: usize, width: usize) -> (usize, usize) {
+    let mut row = 0;
+    let mut start = 0;
+    for raw in text.split('\n') {
+        let segments = wrap_text(raw, width);
+        let last = segments.len() - 1;
+        for (n, segment) in segments.iter().enumerate() {
+            let end = start + segment.len();
+            if cursor < end || (cursor == end && n == last) {
+                return (row, line_width(&text[start..cursor]));
+            }
+            start = end;
+            row += 1;
+        }
+        // Past the newline.
+        start += 1;
+    }
+    (row.saturating_sub(1), 0)
+}
+
+/// A small dialog in the middle of `area` asking `question`.
+fn render_confirm(area: Rect, frame: &mut Frame, title: &str, question: &str) {
+    let [dialog] = Layout::horizontal([Constraint::Length(line_width(question) as u16 + 4)])
+        .flex(Flex::Center)
+        .areas(area);
+    let [dialog] = Layout::vertical([Constraint::Length(3)])
+        .flex(Flex::Center)
+        .areas(dialog);
+
+    Clear.render(dialog, frame.buffer_mut());
+    Paragraph::new(question)
+        .centered()
+        .block(
+            Block::bordered()
+                .border_type(BorderType::Rounded)
+                .title(title.to_span().into_centered_line())
+                .fg(Color::Red),
+        )
+        .render(dialog, frame.buffer_mut());
+}
+
+fn render_archive(area: Rect, frame: &mut Frame, app_state: &mut AppState) {
+    let rows: Vec<&archive::Archived> = app_state
+        .archive_rows()
+        .into_iter()
+        .map(|i| &app_state.archive[i])
+        .collect();
+    archive::render(
+        area,
+        frame,
+        &rows,
+        &mut app_state.archive_state,
+        &app_state.search,
+    );
+}
+
+/// The choice offered when a pomodoro's work timer runs out, over `area`.
+fn render_break(area: Rect, frame: &mut Frame, task: &str) {
+    let task = task.lines().next().unwrap_or_default();
+    let lines = [
+        format!("🍅 {} done!", task),
+        String::new(),
+        format!("b  take a {}-minute break", pomodoro::BREAK_MINUTES),
+        "p  start another pomodoro".to_string(),
+        "c  stop".to_string(),
+    ];
+    let width = lines.iter().map(|l| line_width(l)).max().unwrap_or(0) as u16 + 6;
+    let [dialog] = Layout::horizontal([Constraint::Length(width)])
+        .flex(Flex::Center)
+        .areas(area);
+    let [dialog] = Layout::vertical([Constraint::Length(lines.len() as u16 + 2)])
+        .flex(Flex::Center)
+        .areas(dialog);
+
+    Clear.render(dialog, frame.buffer_mut());
+    Paragraph::new(lines.map(Line::from).to_vec())
+        .block(
+            Block::bordered()
+                .border_type(BorderType::Rounded)
+                .title(" Time's up ".to_span().into_centered_line())
+                .padding(Padding::horizontal(2))
+                .fg(Color::Green),
+        )
+        .render(dialog, frame.buffer_mut());
+}
+
+/// Everything about `item` in a popup over `area`.
+fn render_detail(area: Rect, frame: &mut Frame, item: &TodoItem) {
+    let when = |time: Option<DateTime<Local>>| match time {
+        Some(time) => {
+            let age = match format_age(time, Local::now()).as_str() {
+                "now" => "just now".to_string(),
+                age => format!("{} ago", age),
+            };
+            format!("{} ({})", time.format("%a %b %-d %Y, %H:%M"), age)
+        }
+        None => "-".to_string(),
+    };
+    let done = item.subtasks.iter().filter(|s| s.is_done).count();
+    let rows = [
+        ("Priority", format!("{:?}", item.priority)),
+        (
+            "Due",
+            item.due.map_or("-".to_string(), |due| {
+                due.format("%a %b %-d %Y").to_string()
+            }),
+        ),
+        ("Created", when(item.created_at)),
+        ("Completed", when(item.completed_at)),
+        ("Pomodoros", item.pomodoros.to_string()),
+        (
+            "Subtasks",
+            match item.subtasks.len() {
+                0 => "-".to_string(),
+                total => format!("{}/{} done", done, total),
+            },
+        ),
+    ];
+
+    let mut lines: Vec<Line> = item
+        .description
+        .lines()
+        .map(|l| Line::from(l.to_string()).bold())
+        .collect();
+    lines.push(Line::default());
+    for (label, value) in rows {
+        lines.push(Line::from(vec![
+            Span::from(format!("{:<11}", label)).yellow(),
+            Span::from(value),
+        ]));
+    }
+
+    let width = (lines.iter().map(Line::width).max().unwrap_or(0) as u16 + 4).min(area.width);
+    let [popup] = Layout::horizontal([Constraint::Length(width)])
+        .flex(Flex::Center)
+        .areas(area);
+    let [popup] = Layout::vertical([Constraint::Length(lines.len() as u16 + 2)])
+        .flex(Flex::Center)
+        .areas(popup);
+
+    Clear.render(popup, frame.buffer_mut());
+    Paragraph::new(lines)
+        .wrap(Wrap { trim: false })
+        .block(
+            Block::bordered()
+                .border_type(BorderType::Rounded)
+                .title(" Details ".to_span().into_centered_line())
+                .padding(Padding::horizontal(1))
+                .fg(Color::White),
+        )
+        .render(popup, frame.buffer_mut());
+}
+
+/// How long ago `since` was, in the largest whole unit: "now" under a
+/// minute, then minutes up to an hour, hours up to a day, then days.
+fn format_age(since: DateTime<Local>, now: DateTime<Local>) -> String {
+    let age = now - since;
+    if age.num_minutes() < 1 {
+        "now".to_string()
+    } else if age.num_hours() < 1 {
+        format!("{}m", age.num_minutes())
+    } else if age.num_days() < 1 {
+        format!("{}h", age.num_hours())
+    } else {
+        format!("{}d", age.num_days())
+    }
+}
+
+fn today() -> NaiveDate {
+    Local::now().date_naive()
+}
+
+fn line_width(line: &str) -> usize {
+    line.chars().filter_map(UnicodeWidthChar::width).sum()
+}
+
+/// Splits `text` on newlines and wraps each line at `width` columns,
+/// breaking after the last space that fits, or mid-word when there is none.
+/// Spaces stay at the ends of lines, so the lines join back into `text`.
+fn wrap_text(text: &str, width: usize) -> Vec<String> {
+    let width = width.max(1);
+    let mut lines = Vec::new();
+    for raw in text.split('\n') {
+        let mut line = String::new();
+        let mut used = 0;
+        for c in raw.chars() {
+            let w = c.width().unwrap_or(0);
+            if used + w > width {
+                let rest = match line.trim_end().rfind(' ') {
+                    Some(space) => line.split_off(space + 1),
+                    None => String::new(),
+                };
+                lines.push(std::mem::replace(&mut line, rest));
+                used = line_width(&line);
+            }
+            line.push(c);
+            used += w;
+        }
+        lines.push(line);
+    }
+    lines
+}
+
+fn render_list(border_area: Rect, frame: &mut Frame, app_state: &mut AppState) {
+    let [inner_area] = Layout::vertical([Constraint::Fill(1)])
+        .margin(1)
+        .areas(border_area);
+    let [tabs_area, timer_area, inner_area] = Layout::vertical([
+        Constraint::Length(1),
+        Constraint::Length(app_state.pomodoro.is_some().into()),
+        Constraint::Fill(1),
+    ])
+    .areas(inner_area);
+
+    Tabs::new(app_state.lists.iter().map(|l| l.name.as_str()))
+        .select(app_state.current_list)
+        .highlight_style(Style::default().fg(Color::Green).bold())
+        .render(tabs_area, frame.buffer_mut());
+
+    Block::bordered()
+        .border_type(BorderType::Rounded)
+        .title(list_title(app_state).to_span().into_centered_line())
+        .title_bottom(" ? help ".to_span().into_right_aligned_line())
+        .fg(Color::Yellow)
+        .render(border_area, frame.buffer_mut());
+
+    if let Some(pomodoro) = &app_state.pomodoro {
+        pomodoro.render(timer_area, frame, app_state.flash());
+    }
+
+    let list = List::new(app_state.order().into_iter().filter_map(|path| {
+        let i = app_state.item(path)?;
+        let mut description = i.description.clone();
+        if i.pomodoros > 0 {
+            description.push_str(&format!(" 🍅×{}", i.pomodoros));
+        }
+        if !i.subtasks.is_empty() {
+            let done = i.subtasks.iter().filter(|s| s.is_done).count();
+            description.push_str(&format!(" ({}/{})", done, i.subtasks.len()));
+            if i.collapsed {
+                description.push_str(" ▸");
+            }
+        }
+        // Subtasks sit one bullet in from their task.
+        let indent = if path.sub.is_some() { "  " } else { "" };
+        let bullet = if app_state.marked.contains(&path) {
+            "◆ ".to_span().magenta().bold()
+        } else {
+            "● ".to_span().fg(i.priority.color())
+        };
+        // The due date and the item's age, right-aligned on the first line.
+        let mut right = Vec::new();
+        if let Some(due) = i.due {
+            let label = if due.year() == today().year() {
+                due.format("%a %b %-d").to_string()
+            } else {
+                due.format("%b %-d %Y").to_string()
+            };
+            right.push(Span::from(label).fg(due::color(due, today())));
+        }
+        if let Some(created) = i.created_at {
+            if !right.is_empty() {
+                right.push(" ".to_span());
+            }
+            right.push(Span::from(format_age(created, Local::now())).dark_gray());
+        }
+        let right_width: usize = right.iter().map(|s| line_width(&s.content)).sum();
+        // Wrap beside the bullet and the right-hand labels, past the
+        // highlight symbol.
+        let width = (inner_area.width as usize)
+            .saturating_sub(3 + indent.len() + if right.is_empty() { 0 } else { right_width + 1 });
+        let mut lines: Vec<Line> = wrap_text(&description, width)
+            .into_iter()
+            .enumerate()
+            .map(|(n, l)| {
+                let lead = if n == 0 {
+                    bullet.clone()
+                } else {
+                    "  ".to_span()
+                };
+                Line::from(vec![Span::from(indent), lead, Span::from(l)])
+            })
+            .collect();
+        if let Some(first) = lines.first_mut()
+            && !right.is_empty()
+        {
+            // Leave room for the highlight symbol.
+            let used: usize = first.spans.iter().map(|s| line_width(&s.content)).sum();
+            let gap = (inner_area.width as usize)
+                .saturating_sub(used + right_width + 1)
+                .max(1);
+            first.spans.push(" ".repeat(gap).into());
+            first.spans.extend(right);
+        }
+        let item = ListItem::new(lines);
+        Some(if i.is_done { item.crossed_out() } else { item })
+    }))
+    .highlight_symbol(">")
+    .highlight_style(Style::default().fg(Color::Green));
+
+    app_state.page = inner_area.height;
+    frame.render_stateful_widget(list, inner_area, &mut app_state.list_state);
+
+    // Rows and items differ once descriptions wrap, but items are what
+    // the list scrolls by, so they're what the scrollbar tracks.
+    let count = app_state.order().len();
+    if count > inner_area.height as usize {
+        let mut state = ScrollbarState::new(count.saturating_sub(inner_area.height as usize) + 1)
+            .position(app_state.list_state.offset());
+        Scrollbar::new(ScrollbarOrientation::VerticalRight)
+            .begin_symbol(None)
+            .end_symbol(None)
+            .render(
+                Rect {
+                    y: inner_area.y,
+                    height: inner_area.height,
+                    ..border_area
+                },
+                frame.buffer_mut(),
+                &mut state,
+            );
+    }
+}
+
+/// " Tomato ", plus the filter and sort order when they aren't the default,
+/// and where the selection is in the list.
+fn list_title(app_state: &AppState) -> String {
+    let mut title = " Tomato".to_string();
+    if app_state.filter != Filter::All {
+        title.push_str(&format!(" · {:?}", app_state.filter));
+    }
+    if !app_state.search.is_empty() {
+        title.push_str(&format!(" · /{}", app_state.search));
+    }
+    if app_state.sort_by_priority {
+        title.push_str(" · by priority");
+    }
+    // Home and End leave the row out of range until the list is drawn.
+    let count = app_state.order().len();
+    if let Some(row) = app_state.list_state.selected().filter(|_| count > 0) {
+        title.push_str(&format!(" · {}/{}", row.min(count - 1) + 1, count));
+    }
+    title.push(' ');
+    title
+}
+
+/// The directory everything is kept in: `--data-dir` (or
+/// `TOMATO_TODO_DATA_DIR`) if given, else the platform's data directory.
+fn get_data_dir() -> Result<PathBuf> {
+    if let Some(dir) = DATA_DIR.get() {
+        return Ok(dir.clone());
+    }
+    let proj = ProjectDirs::from("", "", "tomato_todo")
+        .ok_or_else(|| eyre!("cannot determine data directory"))?;
+    Ok(proj.data_dir().to_path_buf())
+}
+
+/// `file` inside the app's data directory, which is created if missing.
+fn get_data_path(file: &str) -> Result<PathBuf> {
+    let dir = get_data_dir()?;
+    fs::create_dir_all(&dir)?;
+    Ok(dir.join(file))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use ratatui::backend::TestBackend;
+
+    use super::*;
+
+    /// One list named "Tasks" holding `tasks`, with the first selected.
+    fn app(tasks: &[&str]) -> AppState {
+        let mut app = AppState {
+            lists: vec![TodoList {
+                name: "Tasks".into(),
+                items: tasks
+                    .iter()
+                    .map(|t| TodoItem {
+                        description: t.to_string(),
+                        ..Default::default()
+                    })
+                    .collect(),
+            }],
+            ..Default::default()
+        };
+        app.switch_list(0);
+        app
+    }
+
+    /// Sends `code` where `run` would: to the form while one is open, else
+    /// to the list.
+    fn press(app: &mut AppState, code: event::KeyCode) {
+        let key = KeyEvent::from(code);
+        match app.modal {
+            Modal::Input(target) => handle_form(key, target, app),
+            _ => {
+                handle_key(key, app);
+            }
+        }
+    }
+
+    fn type_text(app: &mut AppState, text: &str) {
+        for c in text.chars() {
+            press(app, event::KeyCode::Char(c));
+        }
+    }
+
+    fn descriptions(app: &AppState) -> Vec<&str> {
+        app.items().iter().map(|i| i.description.as_str()).collect()
+    }
+
+    #[test]
+    fn data_dir_comes_from_the_command_line() {
+        // The only test to set it, as it can be set once per process.
+        let dir = tempfile::tempdir().unwrap();
+        let data_dir = dir.path().join("tomato");
+        DATA_DIR.set(data_dir.clone()).unwrap();
+
+        assert_eq!(get_data_dir().unwrap(), data_dir);
+        assert_eq!(
+            get_data_path("todos.json").unwrap(),
+            data_dir.join("todos.json")
+        );
+        assert!(data_dir.is_dir());
+    }
+
+    #[test]
+    fn age_thresholds() {
+        let now = Local::now();
+        let age = |seconds: i64| format_age(now - chrono::Duration::seconds(seconds), now);
+        assert_eq!(age(0), "now");
+        assert_eq!(age(59), "now");
+        assert_eq!(age(60), "1m");
+        assert_eq!(age(59 * 60 + 59), "59m");
+        assert_eq!(age(60 * 60), "1h");
+        assert_eq!(age(24 * 60 * 60 - 1), "23h");
+        assert_eq!(age(24 * 60 * 60), "1d");
+        assert_eq!(age(400 * 24 * 60 * 60), "400d");
+        // A clock that went backwards reads as new.
+        assert_eq!(age(-3600), "now");
+    }
+
+    #[test]
+    fn items_from_before_timestamps_load() {
+        let item: TodoItem =
+            serde_json::from_str(r#"{"is_done": true, "description": "old"}"#).unwrap();
+        assert_eq!((item.created_at, item.completed_at), (None, None));
+    }
+
+    #[test]
+    fn completed_at_is_set_and_cleared_with_done() {
+        let mut item = TodoItem::default();
+        item.set_done(true);
+        let at = item.completed_at.expect("completed_at set");
+        item.set_done(true);
+        assert_eq!(item.completed_at, Some(at));
+        item.set_done(false);
+        assert_eq!(item.completed_at, None);
+    }
+
+    #[test]
+    fn add_appends_a_new_item() {
+        let mut app = app(&["one"]);
+        press(&mut app, event::KeyCode::Char('A'));
+        assert_eq!(app.modal, Modal::Input(FormTarget::Task));
+        assert_eq!(app.input.value(), "");
+        type_text(&mut app, "two");
+        press(&mut app, event::KeyCode::Enter);
+
+        assert_eq!(app.modal, Modal::None);
+        assert_eq!(descriptions(&app), ["one", "two"]);
+        assert!(app.items()[1].created_at.is_some());
+        assert_eq!(app.entered, ["two"]);
+    }
+
+    #[test]
+    fn edit_replaces_the_selected_description_in_place() {
+        let mut app = app(&["one", "tow", "three"]);
+        app.items_mut()[1].is_done = true;
+        app.items_mut()[1].priority = Priority::High;
+        app.items_mut()[1].pomodoros = 2;
+        app.list_state.select(Some(1));
+
+        press(&mut app, event::KeyCode::Char('e'));
+        assert_eq!(app.modal, Modal::Input(FormTarget::Edit(ItemPath::task(1))));
+        assert_eq!(app.input.value(), "tow");
+        press(&mut app, event::KeyCode::Backspace);
+        press(&mut app, event::KeyCode::Backspace);
+        type_text(&mut app, "wo");
+        press(&mut app, event::KeyCode::Enter);
+
+        assert_eq!(descriptions(&app), ["one", "two", "three"]);
+        let item = &app.items()[1];
+        assert!(item.is_done);
+        assert_eq!((item.priority, item.pomodoros), (Priority::High, 2));
+        // Edits aren't offered again by Up in the add form.
+        assert!(app.entered.is_empty());
+    }
+
+    #[test]
+    fn escape_leaves_the_item_alone() {
+        let mut app = app(&["one"]);
+        press(&mut app, event::KeyCode::Char('e'));
+        type_text(&mut app, " more");
+        press(&mut app, event::KeyCode::Esc);
+
+        assert_eq!(app.modal, Modal::None);
+        assert_eq!(descriptions(&app), ["one"]);
+        assert_eq!(app.input.value(), "");
+    }
+
+    #[test]
+    fn edit_and_add_undo_separately() {
+        let mut app = app(&["one"]);
+        press(&mut app, event::KeyCode::Char('e'));
+        type_text(&mut app, "!");
+        press(&mut app, event::KeyCode::Enter);
+        press(&mut app, event::KeyCode::Char('A'));
+        type_text(&mut app, "two");
+        press(&mut app, event::KeyCode::Enter);
+        assert_eq!(descriptions(&app), ["one!", "two"]);
+
+        press(&mut app, event::KeyCode::Char('u'));
+        assert_eq!(descriptions(&app), ["one!"]);
+        press(&mut app, event::KeyCode::Char('u'));
+        assert_eq!(descriptions(&app), ["one"]);
+    }
+
+    #[test]
+    fn move_keeps_the_moved_item_selected() {
+        let mut app = app(&["one", "two", "three"]);
+        press(&mut app, event::KeyCode::Char('J'));
+        assert_eq!(descriptions(&app), ["two", "one", "three"]);
+        assert_eq!(app.selected(), Some(ItemPath::task(1)));
+        press(&mut app, event::KeyCode::Char('J'));
+        assert_eq!(descriptions(&app), ["two", "three", "one"]);
+        assert_eq!(app.selected(), Some(ItemPath::task(2)));
+        press(&mut app, event::KeyCode::Char('K'));
+        assert_eq!(descriptions(&app), ["two", "one", "three"]);
+        assert_eq!(app.selected(), Some(ItemPath::task(1)));
+    }
+
+    #[test]
+    fn move_stops_at_either_end() {
+        let mut app = app(&["one", "two"]);
+        press(&mut app, event::KeyCode::Char('K'));
+        assert_eq!(descriptions(&app), ["one", "two"]);
+        app.list_state.select(Some(1));
+        press(&mut app, event::KeyCode::Char('J'));
+        assert_eq!(descriptions(&app), ["one", "two"]);
+        assert_eq!(app.selected(), Some(ItemPath::task(1)));
+        // Nothing moved, so there's nothing to undo.
+        press(&mut app, event::KeyCode::Char('u'));
+        assert_eq!(app.messages.back().unwrap().0, "Nothing to undo");
+    }
+
+    #[test]
+    fn move_on_an_empty_list_does_nothing() {
+        let mut app = app(&[]);
+        press(&mut app, event::KeyCode::Char('J'));
+        press(&mut app, event::KeyCode::Char('K'));
+        assert!(app.items().is_empty());
+    }
+
+    #[test]
+    fn subtasks_move_within_their_task() {
+        let mut app = app(&["one", "two"]);
+        app.items_mut()[0].subtasks = vec![
+            TodoItem {
+                description: "a".into(),
+                ..Default::default()
+            },
+            TodoItem {
+                description: "b".into(),
+                ..Default::default()
+            },
+        ];
+        let a = ItemPath {
+            task: 0,
+            sub: Some(0),
+        };
+        app.select_item(a);
+        press(&mut app, event::KeyCode::Char('K'));
+        assert_eq!(app.selected(), Some(a));
+        press(&mut app, event::KeyCode::Char('J'));
+        let subtasks: Vec<&str> = app.items()[0]
+            .subtasks
+            .iter()
+            .map(|s| s.description.as_str())
+            .collect();
+        assert_eq!(subtasks, ["b", "a"]);
+        // The last subtask doesn't leave its task.
+        press(&mut app, event::KeyCode::Char('J'));
+        assert_eq!(descriptions(&app), ["one", "two"]);
+        assert_eq!(app.items()[0].subtasks[1].description, "a");
+    }
+
+    #[test]
+    fn move_clears_marks_that_would_go_stale() {
+        let mut app = app(&["one", "two", "three"]);
+        press(&mut app, event::KeyCode::Char(' '));
+        press(&mut app, event::KeyCode::Char('J'));
+        assert!(app.marked.is_empty());
+        // Enter now toggles only the selected item, not whatever took the
+        // marked one's old place.
+        press(&mut app, event::KeyCode::Enter);
+        let done: Vec<bool> = app.items().iter().map(|i| i.is_done).collect();
+        assert_eq!(descriptions(&app), ["two", "one", "three"]);
+        assert_eq!(done, [false, true, false]);
+    }
+
+    #[test]
+    fn move_is_ignored_in_a_filtered_view() {
+        let mut app = app(&["one", "two"]);
+        app.set_search("o".into());
+        press(&mut app, event::KeyCode::Char('J'));
+        assert_eq!(descriptions(&app), ["one", "two"]);
+    }
+
+    fn tasks(order: &[ItemPath]) -> Vec<usize> {
+        order.iter().map(|p| p.task).collect()
+    }
+
+    #[test]
+    fn new_list_key_works_during_a_search() {
+        let mut app = app(&["one", "two"]);
+        app.set_search("o".into());
+        press(&mut app, event::KeyCode::Char('L'));
+        assert_eq!(app.modal, Modal::Input(FormTarget::List));
+        type_text(&mut app, "Work");
+        press(&mut app, event::KeyCode::Enter);
+        assert_eq!(app.lists.len(), 2);
+        assert_eq!((app.current_list, app.lists[1].name.as_str()), (1, "Work"));
+    }
+
+    #[test]
+    fn shift_n_during_a_search_goes_to_the_previous_match() {
+        let mut app = app(&["one", "two", "three"]);
+        app.set_search("o".into());
+        press(&mut app, event::KeyCode::Char('N'));
+        assert_eq!(app.modal, Modal::None);
+        assert_eq!(app.selected(), Some(ItemPath::task(1)));
+        press(&mut app, event::KeyCode::Esc);
+        press(&mut app, event::KeyCode::Char('N'));
+        assert_eq!(app.modal, Modal::None);
+        assert_eq!(app.lists.len(), 1);
+    }
+
+    #[test]
+    fn filters_map_rows_to_the_items_they_show() {
+        let mut app = app(&["one", "two", "three", "four"]);
+        app.items_mut()[1].is_done = true;
+        app.items_mut()[3].is_done = true;
+        assert_eq!(tasks(&app.order()), [0, 1, 2, 3]);
+
+        press(&mut app, event::KeyCode::Char('f'));
+        assert_eq!(app.filter, Filter::Active);
+        assert_eq!(tasks(&app.order()), [0, 2]);
+
+        press(&mut app, event::KeyCode::Char('f'));
+        assert_eq!(app.filter, Filter::Done);
+        assert_eq!(tasks(&app.order()), [1, 3]);
+        app.list_state.select(Some(1));
+        assert_eq!(app.selected(), Some(ItemPath::task(3)));
+    }
+
+    #[test]
+    fn toggling_in_a_filtered_view_changes_the_shown_item() {
+        let mut app = app(&["one", "two", "three"]);
+        app.items_mut()[0].is_done = true;
+        press(&mut app, event::KeyCode::Char('f'));
+        // Row 1 of the active view is "three", not the model's item 1.
+        app.list_state.select(Some(1));
+        press(&mut app, event::KeyCode::Enter);
+        let done: Vec<bool> = app.items().iter().map(|i| i.is_done).collect();
+        assert_eq!(done, [true, false, true]);
+    }
+
+    #[test]
+    fn filter_keeps_the_selected_item_when_it_stays_visible() {
+        let mut app = app(&["one", "two", "three"]);
+        app.items_mut()[0].is_done = true;
+        app.list_state.select(Some(2));
+        press(&mut app, event::KeyCode::Char('f'));
+        assert_eq!(app.selected(), Some(ItemPath::task(2)));
+        assert_eq!(app.list_state.selected(), Some(1));
+    }
+
+    #[test]
+    fn priority_order_puts_open_high_priority_items_first() {
+        let mut app = app(&["low", "high done", "medium", "high"]);
+        app.items_mut()[0].priority = Priority::Low;
+        app.items_mut()[1].priority = Priority::High;
+        app.items_mut()[1].is_done = true;
+        app.items_mut()[3].priority = Priority::High;
+        app.list_state.select(Some(2));
+
+        press(&mut app, event::KeyCode::Char('o'));
+        assert_eq!(tasks(&app.order()), [3, 2, 0, 1]);
+        assert_eq!(app.selected(), Some(ItemPath::task(2)));
+    }
+
+    #[test]
+    fn search_ignores_case() {
+        let mut app = app(&["Buy milk", "Call Bob", "buy bread"]);
+        app.set_search("BUY".into());
+        assert_eq!(tasks(&app.order()), [0, 2]);
+        press(&mut app, event::KeyCode::Esc);
+        assert_eq!(tasks(&app.order()), [0, 1, 2]);
+    }
+
+    /// Draws `app` on a `width` by `height` screen and returns the text of
+    /// each row.
+    fn draw(app: &mut AppState, width: u16, height: u16) -> Vec<String> {
+        let mut terminal = ratatui::Terminal::new(TestBackend::new(width, height)).unwrap();
+        terminal.draw(|frame| render(frame, app)).unwrap();
+        let buffer = terminal.backend().buffer();
+        (0..height)
+            .map(|y| (0..width).map(|x| buffer[(x, y)].symbol()).collect())
+            .collect()
+    }
+
+    fn numbered(count: usize) -> AppState {
+        let names: Vec<String> = (1..=count).map(|n| format!("item {}", n)).collect();
+        app(&names.iter().map(String::as_str).collect::<Vec<_>>())
+    }
+
+    /// Whether the selected row is within the drawn part of the list.
+    fn selection_in_view(app: &AppState) -> bool {
+        let (offset, selected) = (app.list_state.offset(), app.list_state.selected().unwrap());
+        offset <= selected && selected < offset + app.page as usize
+    }
+
+    #[test]
+    fn offset_follows_the_selection() {
+        let mut app = numbered(50);
+        draw(&mut app, 40, 12);
+        let page = app.page as usize;
+        assert!(page > 0 && page < 50, "page {}", page);
+
+        for row in [page - 1, page, 30, 49, 20, 0] {
+            app.list_state.select(Some(row));
+            draw(&mut app, 40, 12);
+            assert!(
+                selection_in_view(&app),
+                "row {} offset {}",
+                row,
+                app.list_state.offset()
+            );
+        }
+    }
+
+    #[test]
+    fn end_shows_a_full_last_page() {
+        let mut app = numbered(50);
+        draw(&mut app, 40, 12);
+        press(&mut app, event::KeyCode::End);
+        let screen = draw(&mut app, 40, 12);
+
+        assert_eq!(app.list_state.selected(), Some(49));
+        assert_eq!(app.list_state.offset(), 50 - app.page as usize);
+        assert!(screen.iter().any(|row| row.contains(">● item 50")));
+        assert!(screen[1].contains("· 50/50 "), "{:?}", screen[1]);
+    }
+
+    #[test]
+    fn page_keys_move_by_the_drawn_height() {
+        let mut app = numbered(50);
+        draw(&mut app, 40, 12);
+        let page = app.page as usize;
+
+        press(&mut app, event::KeyCode::PageDown);
+        assert_eq!(app.list_state.selected(), Some(page));
+        let screen = draw(&mut app, 40, 12);
+        assert!(selection_in_view(&app));
+        assert!(screen[1].contains(&format!("· {}/50 ", page + 1)));
+
+        press(&mut app, event::KeyCode::PageUp);
+        draw(&mut app, 40, 12);
+        assert_eq!(app.list_state.selected(), Some(0));
+        assert_eq!(app.list_state.offset(), 0);
+    }
+
+    #[test]
+    fn shrinking_the_screen_keeps_the_selection_in_view() {
+        let mut app = numbered(50);
+        app.list_state.select(Some(40));
+        draw(&mut app, 40, 30);
+        draw(&mut app, 40, 8);
+        assert!(selection_in_view(&app));
+    }
+
+    fn sub(task: usize, sub: usize) -> ItemPath {
+        ItemPath {
+            task,
+            sub: Some(sub),
+        }
+    }
+
+    /// "one" with subtasks "a" and "b", then "two" with "c", then "three".
+    fn with_subtasks() -> AppState {
+        let mut app = app(&["one", "two", "three"]);
+        let item = |d: &str| TodoItem {
+            description: d.into(),
+            ..Default::default()
+        };
+        app.items_mut()[0].subtasks = vec![item("a"), item("b")];
+        app.items_mut()[1].subtasks = vec![item("c")];
+        app
+    }
+
+    #[test]
+    fn expanded_subtasks_follow_their_task() {
+        let app = with_subtasks();
+        assert_eq!(
+            app.order(),
+            [
+                ItemPath::task(0),
+                sub(0, 0),
+                sub(0, 1),
+                ItemPath::task(1),
+                sub(1, 0),
+                ItemPath::task(2),
+            ]
+        );
+    }
+
+    #[test]
+    fn collapsed_subtasks_are_left_out() {
+        let mut app = with_subtasks();
+        app.select_item(sub(0, 1));
+        press(&mut app, event::KeyCode::Char('h'));
+        assert_eq!(
+            app.order(),
+            [
+                ItemPath::task(0),
+                ItemPath::task(1),
+                sub(1, 0),
+                ItemPath::task(2)
+            ]
+        );
+        assert_eq!(app.selected(), Some(ItemPath::task(0)));
+
+        press(&mut app, event::KeyCode::Char('l'));
+        assert_eq!(app.order().len(), 6);
+        assert_eq!(app.selected(), Some(ItemPath::task(0)));
+    }
+
+    #[test]
+    fn a_matching_subtask_brings_its_task() {
+        let mut app = with_subtasks();
+        app.set_search("c".into());
+        assert_eq!(app.order(), [ItemPath::task(1), sub(1, 0)]);
+        // Even collapsed, the task shows; the subtask stays hidden.
+        app.items_mut()[1].collapsed = true;
+        assert_eq!(app.order(), [ItemPath::task(1)]);
+    }
+
+    #[test]
+    fn filter_applies_to_subtasks() {
+        let mut app = with_subtasks();
+        app.items_mut()[0].subtasks[0].is_done = true;
+        app.filter = Filter::Done;
+        assert_eq!(app.order(), [ItemPath::task(0), sub(0, 0)]);
+        app.filter = Filter::Active;
+        assert_eq!(app.order().len(), 5);
+        assert!(!app.order().contains(&sub(0, 0)));
+    }
+
+    #[test]
+    fn sorting_keeps_subtasks_under_their_task() {
+        let mut app = with_subtasks();
+        app.items_mut()[1].priority = Priority::High;
+        app.sort_by_priority = true;
+        assert_eq!(
+            app.order(),
+            [
+                ItemPath::task(1),
+                sub(1, 0),
+                ItemPath::task(0),
+                sub(0, 0),
+                sub(0, 1),
+                ItemPath::task(2),
+            ]
+        );
+    }
+
+    #[test]
+    fn finishing_the_last_subtask_completes_the_task() {
+        let mut app = with_subtasks();
+        app.auto_complete = true;
+        app.select_item(sub(1, 0));
+        press(&mut app, event::KeyCode::Enter);
+        assert!(app.items()[1].is_done);
+        press(&mut app, event::KeyCode::Enter);
+        assert!(!app.items()[1].is_done);
+    }
+
+    /// `app(tasks)` with the first task done, keeping its archive in `dir`.
+    fn with_archive(dir: &Path, tasks: &[&str]) -> AppState {
+        let mut app = app(tasks);
+        app.store = store::Store::open_in(dir.to_path_buf(), false).unwrap().0;
+        app.items_mut()[0].is_done = true;
+        app
+    }
+
+    fn archived(dir: &Path) -> Vec<String> {
+        archive::load(dir)
+            .unwrap()
+            .into_iter()
+            .map(|a| a.item.description)
+            .collect()
+    }
+
+    #[test]
+    fn undoing_an_archive_takes_the_items_back_out_of_it() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut app = with_archive(dir.path(), &["Milk", "Eggs"]);
+        press(&mut app, event::KeyCode::Char('Z'));
+        assert_eq!(descriptions(&app), ["Eggs"]);
+        assert_eq!(archived(dir.path()), ["Milk"]);
+
+        press(&mut app, event::KeyCode::Char('u'));
+        assert_eq!(descriptions(&app), ["Milk", "Eggs"]);
+        assert!(app.archive.is_empty());
+        assert!(archived(dir.path()).is_empty());
+
+        app.step_history(true);
+        assert_eq!(descriptions(&app), ["Eggs"]);
+        assert_eq!(archived(dir.path()), ["Milk"]);
+    }
+
+    #[test]
+    fn undoing_a_restore_puts_the_item_back_in_the_archive() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut app = with_archive(dir.path(), &["Milk", "Eggs"]);
+        press(&mut app, event::KeyCode::Char('Z'));
+        press(&mut app, event::KeyCode::Char('v'));
+        press(&mut app, event::KeyCode::Char('r'));
+        assert_eq!(descriptions(&app), ["Eggs", "Milk"]);
+        assert!(archived(dir.path()).is_empty());
+
+        press(&mut app, event::KeyCode::Esc);
+        press(&mut app, event::KeyCode::Char('u'));
+        assert_eq!(descriptions(&app), ["Eggs"]);
+        assert_eq!(archived(dir.path()), ["Milk"]);
+        assert_eq!(app.archive.len(), 1);
+    }
+
+    #[test]
+    fn clearing_done_keeps_open_subtasks() {
+        let mut app = with_subtasks();
+        app.auto_complete = false;
+        app.items_mut()[0].is_done = true;
+        app.items_mut()[0].subtasks[0].is_done = true;
+        app.items_mut()[2].is_done = true;
+        assert_eq!(app.clearable(), 2);
+
+        press(&mut app, event::KeyCode::Char('C'));
+        assert_eq!(app.modal, Modal::Confirm(Confirm::ClearDone(2)));
+        press(&mut app, event::KeyCode::Char('y'));
+        assert_eq!(descriptions(&app), ["one", "two"]);
+        assert!(app.items()[0].is_done);
+        let subtasks: Vec<&str> = app.items()[0]
+            .subtasks
+            .iter()
+            .map(|s| s.description.as_str())
+            .collect();
+        assert_eq!(subtasks, ["b"]);
+        assert_eq!(app.counts(), (4, 1));
+    }
+
+    #[test]
+    fn a_done_task_with_only_done_subtasks_goes_with_them() {
+        let mut app = with_subtasks();
+        app.items_mut()[1].is_done = true;
+        app.items_mut()[1].subtasks[0].is_done = true;
+        assert_eq!(app.clearable(), 2);
+        app.clear_done();
+        assert_eq!(descriptions(&app), ["one", "three"]);
+    }
 }
@@ -0,0 +1,260 @@
+use chrono::{DateTime, Local};
+
+use crate::{Priority, TodoItem, due};
+
+/// Reads the task lines of a Markdown document: `- [ ] text` and
+/// `- [x] text`, with `*` or `+` for the bullet. An indented task is a
+/// subtask of the task above it. Text may start with a priority marker,
+/// `(A)` high, `(B)` medium or `(C)` low, and end with an `@date` as typed
+/// into the form. Tags such as `#work` are kept as part of the text.
+/// Anything else, headings and prose included, is skipped.
+pub fn parse(text: &str, now: DateTime<Local>) -> Vec<TodoItem> {
+    let mut items: Vec<TodoItem> = Vec::new();
+    for line in text.lines() {
+        let content = line.trim_start();
+        let indented = content.len() < line.len();
+        let Some(rest) = content
+            .strip_prefix(['-', '*', '+'])
+            .and_then(|rest| rest.strip_prefix(' '))
+        else {
+            continue;
+        };
+        let (is_done, rest) = match rest.get(..3) {
+            Some("[x]" | "[X]") => (true, &rest[3..]),
+            Some("[ ]") => (false, &rest[3..]),
+            _ => continue,
+        };
+        let rest = rest.trim();
+        let (priority, rest) = match rest.get(..4) {
+            Some("(A) ") => (Priority::High, &rest[4..]),
+            Some("(B) ") => (Priority::Medium, &rest[4..]),
+            Some("(C) ") => (Priority::Low, &rest[4..]),
+            _ => (Priority::Medium, rest),
+        };
+        let (description, due) = due::split_due(rest.trim(), now.date_naive());
+        let item = TodoItem {
+            is_done,
+            description,
+            priority,
+            due,
+            created_at: Some(now),
+            completed_at: is_done.then_some(now),
+            ..Default::default()
+        };
+        match items.last_mut() {
+            Some(parent) if indented => parent.subtasks.push(item),
+            _ => items.push(item),
+        }
+    }
+    items
+}
+
+/// Writes `items` as a Markdown task list that `parse` reads back the
+/// same. Medium is the default priority, so it gets no marker. A
+/// description's line breaks become spaces.
+pub fn render(items: &[TodoItem]) -> String {
+    let mut text = String::new();
+    for item in items {
+        render_item(&mut text, item, "");
+        for sub in &item.subtasks {
+            render_item(&mut text, sub, "  ");
+        }
+    }
+    text
+}
+
+fn render_item(text: &mut String, item: &TodoItem, indent: &str) {
+    let check = if item.is_done { "x" } else { " " };
+    let priority = match item.priority {
+        Priority::High => "(A) ",
+        Priority::Medium => "",
+        Priority::Low => "(C) ",
+    };
+    let description = item.description.lines().collect::<Vec<_>>().join(" ");
+    text.push_str(&format!(
+        "{}- [{}] {}{}{}\n",
+        indent,
+        check,
+        priority,
+        description,
+        due::suffix(item.due)
+    ));
+}
+
+/// Folds `imported` into `items`: a task or subtask with the same
+/// description as one already there takes its done state, priority and due
+/// date; the rest are added at the end.
+pub fn merge(items: &mut Vec<TodoItem>, imported: Vec<TodoItem>) {
+    for mut new in imported {
+        let subtasks = std::mem::take(&mut new.subtasks);
+        let index = match items.iter().position(|i| i.description == new.description) {
+            Some(index) => {
+                update(&mut items[index], new);
+                index
+            }
+            None => {
+                items.push(new);
+                items.len() - 1
+            }
+        };
+        let item = &mut items[index];
+        for sub in subtasks {
+            match item
+                .subtasks
+                .iter_mut()
+                .find(|s| s.description == sub.description)
+            {
+                Some(existing) => update(existing, sub),
+                None => item.subtasks.push(sub),
+            }
+        }
+    }
+}
+
+fn update(item: &mut TodoItem, new: TodoItem) {
+    item.set_done(new.is_done);
+    item.priority = new.priority;
+    item.due = new.due;
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use super::*;
+
+    fn now() -> DateTime<Local> {
+        Local::now()
+    }
+
+    fn item(description: &str) -> TodoItem {
+        TodoItem {
+            description: description.into(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn parses_tasks_and_skips_everything_else() {
+        let text = "# Groceries\n\nSome prose.\n- [ ] Milk\n* [x] Eggs\n+ [X] Bread\n- Not a task\n-[ ] No space\n";
+        let items = parse(text, now());
+        let parsed: Vec<(&str, bool)> = items
+            .iter()
+            .map(|i| (i.description.as_str(), i.is_done))
+            .collect();
+        assert_eq!(parsed, [("Milk", false), ("Eggs", true), ("Bread", true)]);
+    }
+
+    #[test]
+    fn done_items_are_completed_when_imported() {
+        let at = now();
+        let items = parse("- [x] Done\n- [ ] Open\n", at);
+        assert_eq!(items[0].completed_at, Some(at));
+        assert_eq!(items[1].completed_at, None);
+        assert!(items.iter().all(|i| i.created_at == Some(at)));
+    }
+
+    #[test]
+    fn indented_tasks_are_subtasks() {
+        let items = parse(
+            "- [ ] Trip\n  - [x] Book\n\t- [ ] Pack\n- [ ] Home\n",
+            now(),
+        );
+        assert_eq!(items.len(), 2);
+        let subtasks: Vec<&str> = items[0]
+            .subtasks
+            .iter()
+            .map(|s| s.description.as_str())
+            .collect();
+        assert_eq!(subtasks, ["Book", "Pack"]);
+    }
+
+    #[test]
+    fn priorities_due_dates_and_tags() {
+        let items = parse(
+            "- [ ] (A) Urgent #work @2024-07-01\n- [ ] (C) Someday\n- [ ] (D) Plain\n",
+            now(),
+        );
+        assert_eq!(items[0].priority, Priority::High);
+        assert_eq!(items[0].description, "Urgent #work");
+        assert_eq!(items[0].due, NaiveDate::from_ymd_opt(2024, 7, 1));
+        assert_eq!(items[1].priority, Priority::Low);
+        assert_eq!(
+            (items[2].priority, items[2].description.as_str()),
+            (Priority::Medium, "(D) Plain")
+        );
+    }
+
+    #[test]
+    fn render_then_parse_gives_the_same_items() {
+        let mut trip = item("Trip #travel");
+        trip.priority = Priority::High;
+        trip.due = NaiveDate::from_ymd_opt(2024, 7, 1);
+        let mut book = item("Book a room @ the inn");
+        book.is_done = true;
+        trip.subtasks = vec![book, item("Pack")];
+        let mut later = item("Later");
+        later.priority = Priority::Low;
+        let items = vec![trip, later];
+
+        let text = render(&items);
+        assert_eq!(
+            text,
+            "- [ ] (A) Trip #travel @2024-07-01\n  - [x] Book a room @ the inn\n  - [ ] Pack\n- [ ] (C) Later\n"
+        );
+        let parsed = parse(&text, now());
+        assert_eq!(render(&parsed), text);
+        let shape =
+            |items: &[TodoItem]| -> Vec<(String, bool, Priority, Option<NaiveDate>, usize)> {
+                items
+                    .iter()
+                    .flat_map(|i| std::iter::once(i).chain(&i.subtasks))
+                    .map(|i| {
+                        (
+                            i.description.clone(),
+                            i.is_done,
+                            i.priority,
+                            i.due,
+                            i.subtasks.len(),
+                        )
+                    })
+                    .collect()
+            };
+        assert_eq!(shape(&parsed), shape(&items));
+    }
+
+    #[test]
+    fn line_breaks_become_spaces() {
+        let text = render(&[item("two\nlines")]);
+        assert_eq!(text, "- [ ] two lines\n");
+    }
+
+    #[test]
+    fn merge_updates_matches_and_adds_the_rest() {
+        let mut items = vec![item("Milk"), item("Eggs")];
+        items[1].subtasks = vec![item("Free range")];
+        let imported = parse(
+            "- [x] Milk\n- [ ] Eggs\n  - [x] Free range\n  - [ ] A dozen\n- [ ] Bread\n",
+            now(),
+        );
+        merge(&mut items, imported);
+
+        let parsed = parse(&render(&items), now());
+        let done: Vec<(&str, bool)> = parsed
+            .iter()
+            .flat_map(|i| std::iter::once(i).chain(&i.subtasks))
+            .map(|i| (i.description.as_str(), i.is_done))
+            .collect();
+        assert_eq!(
+            done,
+            [
+                ("Milk", true),
+                ("Eggs", false),
+                ("Free range", true),
+                ("A dozen", false),
+                ("Bread", false),
+            ]
+        );
+        assert!(items[0].completed_at.is_some());
+    }
+}
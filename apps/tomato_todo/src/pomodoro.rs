@@ -0,0 +1,170 @@
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Local};
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::{Color, Style},
+    widgets::Gauge,
+};
+
+use crate::sessions::Session;
+
+pub const WORK_MINUTES: u64 = 25;
+pub const BREAK_MINUTES: u64 = 5;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Phase {
+    Work,
+    /// The work timer ran out; waiting for the break to be taken or skipped.
+    BreakOffered,
+    Break,
+}
+
+/// A pomodoro bound to one task, counting down while not paused.
+#[derive(Debug)]
+pub struct Pomodoro {
+    pub task: String,
+    /// The list the task is in, so its counter can be found on completion.
+    pub list: usize,
+    pub phase: Phase,
+    started: DateTime<Local>,
+    /// When the timer last resumed, or `None` while paused.
+    running_since: Option<Instant>,
+    /// Time run before the last pause.
+    banked: Duration,
+}
+
+impl Pomodoro {
+    pub fn start(task: String, list: usize) -> Self {
+        Self {
+            task,
+            list,
+            phase: Phase::Work,
+            started: Local::now(),
+            running_since: Some(Instant::now()),
+            banked: Duration::ZERO,
+        }
+    }
+
+    fn length(&self) -> Duration {
+        let minutes = match self.phase {
+            Phase::Break => BREAK_MINUTES,
+            _ => WORK_MINUTES,
+        };
+        Duration::from_secs(minutes * 60)
+    }
+
+    fn elapsed(&self) -> Duration {
+        let running = self.running_since.map_or(Duration::ZERO, |t| t.elapsed());
+        (self.banked + running).min(self.length())
+    }
+
+    fn remaining(&self) -> Duration {
+        self.length() - self.elapsed()
+    }
+
+    pub fn toggle_pause(&mut self) {
+        match self.running_since.take() {
+            Some(since) => self.banked += since.elapsed(),
+            None => self.running_since = Some(Instant::now()),
+        }
+    }
+
+    pub fn start_break(&mut self) {
+        self.phase = Phase::Break;
+        self.running_since = Some(Instant::now());
+        self.banked = Duration::ZERO;
+    }
+
+    /// Whether a running work or break timer has reached zero.
+    pub fn is_over(&self) -> bool {
+        self.phase != Phase::BreakOffered && self.remaining().is_zero()
+    }
+
+    /// The finished work period, for the focus statistics.
+    pub fn session(&self) -> Session {
+        Session {
+            task: self.task.clone(),
+            started: self.started,
+            minutes: WORK_MINUTES as i64,
+        }
+    }
+
+    /// Time left as "mm:ss".
+    fn time_left(&self) -> String {
+        let left = self.remaining().as_secs();
+        format!("{:02}:{:02}", left / 60, left % 60)
+    }
+
+    /// The phase and time left in a few characters, for the status bar.
+    pub fn clock(&self) -> String {
+        let paused = if self.running_since.is_none() {
+            " ⏸"
+        } else {
+            ""
+        };
+        match self.phase {
+            Phase::Work => format!("🍅 {}{}", self.time_left(), paused),
+            Phase::BreakOffered => "🍅 done".to_string(),
+            Phase::Break => format!("☕ {}{}", self.time_left(), paused),
+        }
+    }
+
+    /// Draws the timer, swapping its colors while `flash` is on so a
+    /// finished timer stands out.
+    pub fn render(&self, area: Rect, frame: &mut Frame, flash: bool) {
+        let clock = self.time_left();
+        let task = self.task.lines().next().unwrap_or_default();
+        let paused = if self.running_since.is_none() {
+            " (paused)"
+        } else {
+            ""
+        };
+        let (label, color) = match self.phase {
+            Phase::Work => (
+                format!("🍅 {} · {} left{}", task, clock, paused),
+                Color::Red,
+            ),
+            Phase::BreakOffered => (
+                format!(
+                    "🍅 {} done! b {}-min break · p another · c stop",
+                    task, BREAK_MINUTES
+                ),
+                Color::Green,
+            ),
+            Phase::Break => (format!("☕ Break · {} left{}", clock, paused), Color::Cyan),
+        };
+        let ratio = self.elapsed().as_secs_f64() / self.length().as_secs_f64();
+        let style = if flash {
+            Style::default().fg(Color::Black).bg(color)
+        } else {
+            Style::default().fg(color)
+        };
+        frame.render_widget(
+            Gauge::default()
+                .gauge_style(style)
+                .ratio(ratio.clamp(0.0, 1.0))
+                .label(label),
+            area,
+        );
+    }
+}
+
+/// Shows a desktop notification. Without the `notify` feature there is
+/// nothing to show it with, so this does nothing.
+#[cfg(feature = "notify")]
+pub fn notify(summary: &str, body: &str) -> Result<(), String> {
+    notify_rust::Notification::new()
+        .appname("tomato_todo")
+        .summary(summary)
+        .body(body)
+        .show()
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(not(feature = "notify"))]
+pub fn notify(_summary: &str, _body: &str) -> Result<(), String> {
+    Ok(())
+}
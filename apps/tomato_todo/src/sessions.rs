@@ -0,0 +1,114 @@
+use std::{collections::BTreeMap, fs};
+
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate};
+use color_eyre::eyre::Result;
+use ratatui::{
+    Frame,
+    layout::{Constraint, Rect},
+    style::{Color, Style, Stylize},
+    text::ToSpan,
+    widgets::{Block, BorderType, Row, Table},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::get_data_path;
+
+/// A finished focus session on one task.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Session {
+    pub task: String,
+    pub started: DateTime<Local>,
+    pub minutes: i64,
+}
+
+/// The focus session currently running.
+#[derive(Debug)]
+pub struct Focus {
+    pub task: String,
+    pub started: DateTime<Local>,
+}
+
+impl Focus {
+    pub fn finish(self) -> Session {
+        Session {
+            minutes: (Local::now() - self.started).num_minutes(),
+            task: self.task,
+            started: self.started,
+        }
+    }
+}
+
+/// Session count and total minutes for each day, newest first.
+fn per_day(sessions: &[Session]) -> Vec<(NaiveDate, usize, i64)> {
+    let mut days: BTreeMap<NaiveDate, (usize, i64)> = BTreeMap::new();
+    for s in sessions {
+        let day = days.entry(s.started.date_naive()).or_default();
+        day.0 += 1;
+        day.1 += s.minutes;
+    }
+    days.into_iter()
+        .rev()
+        .map(|(date, (count, minutes))| (date, count, minutes))
+        .collect()
+}
+
+/// The Monday of the week `day` is in.
+pub fn week_start(day: NaiveDate) -> NaiveDate {
+    day - Duration::days(day.weekday().num_days_from_monday() as i64)
+}
+
+/// Sessions started since Monday of the current week.
+pub fn this_week(sessions: &[Session]) -> usize {
+    let monday = week_start(Local::now().date_naive());
+    sessions
+        .iter()
+        .filter(|s| s.started.date_naive() >= monday)
+        .count()
+}
+
+pub fn render_stats(area: Rect, frame: &mut Frame, sessions: &[Session]) {
+    let days = per_day(sessions);
+    let most = days.iter().map(|(_, count, _)| *count).max().unwrap_or(0);
+    let rows = days.into_iter().map(|(date, count, minutes)| {
+        Row::new(vec![
+            date.format("%a %Y-%m-%d").to_string(),
+            count.to_string(),
+            format!("{} min", minutes),
+            "█".repeat(count * 20 / most.max(1)),
+        ])
+    });
+
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(14),
+            Constraint::Length(8),
+            Constraint::Length(10),
+            Constraint::Fill(1),
+        ],
+    )
+    .header(Row::new(vec!["Day", "Sessions", "Focused", ""]).bold())
+    .style(Style::default().fg(Color::White))
+    .block(
+        Block::bordered()
+            .border_type(BorderType::Rounded)
+            .title(" Focus Sessions ".to_span().into_centered_line())
+            .fg(Color::Yellow),
+    );
+    frame.render_widget(table, area);
+}
+
+pub fn load_sessions() -> Result<Vec<Session>> {
+    let path = get_data_path("sessions.json")?;
+    if !path.exists() {
+        return Ok(vec![]);
+    }
+    let data = fs::read_to_string(path)?;
+    Ok(serde_json::from_str(&data)?)
+}
+
+pub fn save_sessions(sessions: &[Session]) -> Result<()> {
+    let path = get_data_path("sessions.json")?;
+    fs::write(path, serde_json::to_string_pretty(sessions)?)?;
+    Ok(())
+}
@@ -0,0 +1,246 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use chrono::{DateTime, Duration, Local, NaiveDate};
+use ratatui::{
+    Frame,
+    layout::{Constraint, Layout, Rect},
+    style::{Color, Style, Stylize},
+    text::{Line, Span, ToSpan},
+    widgets::{Bar, BarChart, BarGroup, Block, BorderType, Paragraph},
+};
+
+use crate::{
+    TodoItem, TodoList,
+    archive::Archived,
+    sessions::{self, Session},
+};
+
+/// Days of sessions the bar chart covers, ending today.
+const CHART_DAYS: i64 = 14;
+
+/// How many of `dates` fall on each of the `days` days up to and including
+/// `last`, oldest first, with the empty days in as zeros.
+fn per_day(
+    dates: impl Iterator<Item = NaiveDate>,
+    last: NaiveDate,
+    days: i64,
+) -> Vec<(NaiveDate, u64)> {
+    let first = last - Duration::days(days - 1);
+    let mut counts: BTreeMap<NaiveDate, u64> =
+        (0..days).map(|n| (first + Duration::days(n), 0)).collect();
+    for date in dates {
+        if let Some(count) = counts.get_mut(&date) {
+            *count += 1;
+        }
+    }
+    counts.into_iter().collect()
+}
+
+/// Days in a row, up to `today`, with at least one of `days`. A streak
+/// that reached yesterday still counts until today is over.
+fn streak(days: &BTreeSet<NaiveDate>, today: NaiveDate) -> usize {
+    let mut day = if days.contains(&today) {
+        today
+    } else {
+        today - Duration::days(1)
+    };
+    let mut count = 0;
+    while days.contains(&day) {
+        count += 1;
+        day -= Duration::days(1);
+    }
+    count
+}
+
+/// Every done task and subtask, archived or not, that knows when it was
+/// completed.
+fn completed<'a>(lists: &'a [TodoList], archive: &'a [Archived]) -> Vec<&'a TodoItem> {
+    lists
+        .iter()
+        .flat_map(|list| &list.items)
+        .chain(archive.iter().map(|entry| &entry.item))
+        .flat_map(|item| std::iter::once(item).chain(&item.subtasks))
+        .filter(|item| item.is_done && item.completed_at.is_some())
+        .collect()
+}
+
+/// "2d 3h", or "-" when there's nothing to average.
+fn format_duration(duration: Option<Duration>) -> String {
+    match duration {
+        Some(d) if d.num_days() > 0 => format!("{}d {}h", d.num_days(), d.num_hours() % 24),
+        Some(d) if d.num_hours() > 0 => format!("{}h {}m", d.num_hours(), d.num_minutes() % 60),
+        Some(d) => format!("{}m", d.num_minutes()),
+        None => "-".to_string(),
+    }
+}
+
+/// Completions, a chart of sessions per day and the sessions table.
+pub fn render(
+    area: Rect,
+    frame: &mut Frame,
+    lists: &[TodoList],
+    archive: &[Archived],
+    sessions: &[Session],
+) {
+    let [summary_area, chart_area, table_area] = Layout::vertical([
+        Constraint::Length(6),
+        Constraint::Length(10),
+        Constraint::Fill(1),
+    ])
+    .areas(area);
+
+    let now = Local::now();
+    let today = now.date_naive();
+    let done = completed(lists, archive);
+    let done_on = |item: &&TodoItem| item.completed_at.map(|t: DateTime<Local>| t.date_naive());
+    let days: BTreeSet<NaiveDate> = done.iter().filter_map(done_on).collect();
+    let monday = sessions::week_start(today);
+    let ages: Vec<Duration> = done
+        .iter()
+        .filter_map(|item| Some(item.completed_at? - item.created_at?))
+        .collect();
+    let average_age =
+        (!ages.is_empty()).then(|| ages.iter().copied().sum::<Duration>() / ages.len() as i32);
+
+    let rows = [
+        (
+            "Completed today",
+            done.iter()
+                .filter(|i| done_on(i) == Some(today))
+                .count()
+                .to_string(),
+        ),
+        (
+            "This week",
+            done.iter()
+                .filter(|i| done_on(i) >= Some(monday))
+                .count()
+                .to_string(),
+        ),
+        ("Average age when done", format_duration(average_age)),
+        (
+            "Streak",
+            match streak(&days, today) {
+                1 => "1 day".to_string(),
+                n => format!("{} days", n),
+            },
+        ),
+    ];
+    let lines: Vec<Line> = rows
+        .into_iter()
+        .map(|(label, value)| {
+            Line::from(vec![
+                Span::from(format!("{:<23}", label)).yellow(),
+                Span::from(value).bold(),
+            ])
+        })
+        .collect();
+    frame.render_widget(
+        Paragraph::new(lines).block(
+            Block::bordered()
+                .border_type(BorderType::Rounded)
+                .title(" Tasks ".to_span().into_centered_line())
+                .fg(Color::Yellow),
+        ),
+        summary_area,
+    );
+
+    let bars: Vec<Bar> = per_day(
+        sessions.iter().map(|s| s.started.date_naive()),
+        today,
+        CHART_DAYS,
+    )
+    .into_iter()
+    .map(|(date, count)| {
+        Bar::default()
+            .value(count)
+            .label(date.format("%d").to_string().into())
+            .text_value(if count > 0 {
+                count.to_string()
+            } else {
+                String::new()
+            })
+    })
+    .collect();
+    frame.render_widget(
+        BarChart::default()
+            .data(BarGroup::default().bars(&bars))
+            .bar_width(3)
+            .bar_gap(1)
+            .bar_style(Color::Red)
+            .value_style(Style::new().black().on_red())
+            .block(
+                Block::bordered()
+                    .border_type(BorderType::Rounded)
+                    .title(
+                        format!(" 🍅 Sessions, last {} days ", CHART_DAYS)
+                            .to_span()
+                            .into_centered_line(),
+                    )
+                    .fg(Color::Yellow),
+            ),
+        chart_area,
+    );
+
+    sessions::render_stats(table_area, frame, sessions);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, month, day).unwrap()
+    }
+
+    #[test]
+    fn per_day_counts_each_day_with_gaps_as_zero() {
+        // Across a leap day; dates outside the window are left out.
+        let dates = [date(3, 1), date(3, 3), date(3, 3), date(2, 20), date(3, 4)];
+        assert_eq!(
+            per_day(dates.into_iter(), date(3, 3), 4),
+            [
+                (date(2, 29), 0),
+                (date(3, 1), 1),
+                (date(3, 2), 0),
+                (date(3, 3), 2),
+            ]
+        );
+    }
+
+    #[test]
+    fn per_day_with_nothing_is_all_zeros() {
+        let counts = per_day(std::iter::empty(), date(7, 3), CHART_DAYS);
+        assert_eq!(counts.len(), CHART_DAYS as usize);
+        assert_eq!(counts.first().unwrap().0, date(6, 20));
+        assert_eq!(counts.last().unwrap(), &(date(7, 3), 0));
+    }
+
+    #[test]
+    fn streak_counts_back_from_today() {
+        let days = BTreeSet::from([date(7, 1), date(7, 2), date(7, 3), date(6, 28)]);
+        assert_eq!(streak(&days, date(7, 3)), 3);
+    }
+
+    #[test]
+    fn streak_ending_yesterday_still_counts() {
+        let days = BTreeSet::from([date(7, 1), date(7, 2)]);
+        assert_eq!(streak(&days, date(7, 3)), 2);
+        assert_eq!(streak(&days, date(7, 4)), 0);
+    }
+
+    #[test]
+    fn streak_across_a_month_end() {
+        let days = BTreeSet::from([date(6, 29), date(6, 30), date(7, 1)]);
+        assert_eq!(streak(&days, date(7, 1)), 3);
+        assert_eq!(streak(&BTreeSet::new(), date(7, 1)), 0);
+    }
+
+    #[test]
+    fn durations() {
+        assert_eq!(format_duration(None), "-");
+        assert_eq!(format_duration(Some(Duration::minutes(5))), "5m");
+        assert_eq!(format_duration(Some(Duration::minutes(125))), "2h 5m");
+        assert_eq!(format_duration(Some(Duration::hours(51))), "2d 3h");
+    }
+}
@@ -0,0 +1,400 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use color_eyre::eyre::{Result, eyre};
+use serde::{Deserialize, Serialize};
+
+use crate::{TodoItem, TodoList, get_data_dir};
+
+/// Set to `1` (or `true`, `yes`, `on`) to record changes in an append-only
+/// log instead of rewriting `todos.json` after every key.
+const APPEND_LOG_VAR: &str = "TOMATO_TODO_APPEND_LOG";
+/// Log entries kept before a save folds them into the snapshot.
+const COMPACT_AFTER: usize = 500;
+
+/// A list from before the JSON store, one item per line, imported into an
+/// empty store.
+const PLAINTEXT: &str = "todos.txt";
+
+/// One line of `todos.log`: a list as it now stands, or the new number of
+/// lists after some were removed. Replayed in order over `todos.json`.
+#[derive(Serialize, Deserialize)]
+enum Entry<L> {
+    Put(usize, L),
+    Truncate(usize),
+}
+
+/// Where the lists are kept. Saves that change nothing write nothing. With
+/// the append log on, each save writes only the lists that changed since
+/// the last one; quitting, or a log past
+/// `COMPACT_AFTER` entries, rewrites the snapshot and empties the log.
+#[derive(Debug, Default)]
+pub struct Store {
+    /// The directory holding `todos.json` and `todos.log`.
+    dir: PathBuf,
+    append_log: bool,
+    /// The lists as of the last save, to tell what changed.
+    saved: Vec<TodoList>,
+    logged: usize,
+    /// The log ends in a line cut short by a crash.
+    torn: bool,
+}
+
+impl Store {
+    pub fn open() -> Result<(Self, Vec<TodoList>)> {
+        let append_log = std::env::var(APPEND_LOG_VAR)
+            .is_ok_and(|v| matches!(v.to_lowercase().as_str(), "1" | "true" | "yes" | "y" | "on"));
+        Self::open_in(get_data_dir()?, append_log)
+    }
+
+    /// Opens the store kept in `dir`, creating the directory if missing.
+    pub fn open_in(dir: PathBuf, append_log: bool) -> Result<(Self, Vec<TodoList>)> {
+        fs::create_dir_all(&dir)?;
+        let mut store = Store {
+            dir,
+            append_log,
+            ..Default::default()
+        };
+        let path = store.db_path();
+        let plaintext = store.dir.join(PLAINTEXT);
+        let mut lists: Vec<TodoList> = if path.exists() {
+            serde_json::from_str(&fs::read_to_string(path)?)?
+        } else if plaintext.exists() {
+            import_plaintext(&plaintext, &path)?
+        } else {
+            vec![]
+        };
+
+        let log_path = store.log_path();
+        if log_path.exists() {
+            let log = fs::read_to_string(&log_path)?;
+            let lines: Vec<&str> = log.lines().filter(|l| !l.trim().is_empty()).collect();
+            for (i, line) in lines.iter().enumerate() {
+                let entry: Entry<TodoList> = match serde_json::from_str(line) {
+                    Ok(entry) => entry,
+                    // A write cut short leaves at most the last line unfinished.
+                    Err(_) if i + 1 == lines.len() => {
+                        store.torn = true;
+                        break;
+                    }
+                    Err(e) => return Err(eyre!("{}, line {}: {}", log_path.display(), i + 1, e)),
+                };
+                match entry {
+                    Entry::Put(index, list) if index < lists.len() => lists[index] = list,
+                    Entry::Put(_, list) => lists.push(list),
+                    Entry::Truncate(len) => lists.truncate(len),
+                }
+                store.logged += 1;
+            }
+        }
+        store.saved = lists.clone();
+        Ok((store, lists))
+    }
+
+    /// Saves `lists` if they changed since the last save, and says whether
+    /// they had.
+    pub fn save(&mut self, lists: &[TodoList]) -> Result<bool> {
+        if lists == self.saved.as_slice() {
+            return Ok(false);
+        }
+        if !self.append_log {
+            return self.compact(lists).map(|_| true);
+        }
+        let mut lines = String::new();
+        for (index, list) in lists.iter().enumerate() {
+            if self.saved.get(index) != Some(list) {
+                lines.push_str(&serde_json::to_string(&Entry::Put(index, list))?);
+                lines.push('\n');
+            }
+        }
+        if lists.len() < self.saved.len() {
+            lines.push_str(&serde_json::to_string(&Entry::<&TodoList>::Truncate(
+                lists.len(),
+            ))?);
+            lines.push('\n');
+        }
+        let count = lines.lines().count();
+        // Appending after a torn line would bury it mid-log, so start afresh.
+        if self.torn || self.logged + count > COMPACT_AFTER {
+            return self.compact(lists).map(|_| true);
+        }
+
+        let mut log = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.log_path())?;
+        log.write_all(lines.as_bytes())?;
+        log.sync_data()?;
+        self.logged += count;
+        self.saved = lists.to_vec();
+        Ok(true)
+    }
+
+    /// Writes every list to a fresh snapshot and removes the log. The
+    /// snapshot is written beside the old one, synced and renamed over it,
+    /// so a crash leaves one or the other intact; the log goes only once
+    /// the new snapshot is on disk.
+    pub fn compact(&mut self, lists: &[TodoList]) -> Result<()> {
+        let path = self.db_path();
+        let tmp = path.with_extension("json.tmp");
+        let mut file = File::create(&tmp)?;
+        file.write_all(serde_json::to_string_pretty(lists)?.as_bytes())?;
+        file.sync_all()?;
+        fs::rename(&tmp, &path)?;
+        sync_dir(&self.dir)?;
+
+        let log_path = self.log_path();
+        if log_path.exists() {
+            fs::remove_file(log_path)?;
+            sync_dir(&self.dir)?;
+        }
+        self.logged = 0;
+        self.torn = false;
+        self.saved = lists.to_vec();
+        Ok(())
+    }
+
+    /// Folds the log into the snapshot on the way out, if there is one.
+    pub fn close(&mut self, lists: &[TodoList]) -> Result<()> {
+        if self.logged > 0 || self.torn {
+            self.compact(lists)?;
+        }
+        Ok(())
+    }
+
+    /// The directory the store, and the files kept beside it, live in.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    fn db_path(&self) -> PathBuf {
+        self.dir.join("todos.json")
+    }
+
+    fn log_path(&self) -> PathBuf {
+        self.dir.join("todos.log")
+    }
+}
+
+/// Makes renames and removals in `dir` durable. Only Unix can open a
+/// directory to sync it.
+fn sync_dir(dir: &Path) -> Result<()> {
+    #[cfg(unix)]
+    File::open(dir)?.sync_all()?;
+    #[cfg(not(unix))]
+    let _ = dir;
+    Ok(())
+}
+
+/// Reads the old plaintext list at `path` into a list called "Tasks" and
+/// writes it to the snapshot at `db`, then renames the file so it's
+/// imported once.
+/// Lines may be ticked with `[x]`, and may start with `- ` or `* `.
+fn import_plaintext(path: &Path, db: &Path) -> Result<Vec<TodoList>> {
+    let items = fs::read_to_string(path)?
+        .lines()
+        .map(|line| line.trim().trim_start_matches(['-', '*']).trim_start())
+        .filter(|line| !line.is_empty())
+        .map(|line| {
+            let (is_done, description) = match line.get(..3) {
+                Some("[x]" | "[X]") => (true, &line[3..]),
+                Some("[ ]") => (false, &line[3..]),
+                _ => (false, line),
+            };
+            TodoItem {
+                is_done,
+                description: description.trim().to_string(),
+                ..Default::default()
+            }
+        })
+        .collect();
+    let lists = vec![TodoList {
+        name: "Tasks".into(),
+        items,
+    }];
+
+    fs::write(db, serde_json::to_string_pretty(&lists)?)?;
+    fs::rename(path, path.with_extension("txt.imported"))?;
+    Ok(lists)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(lists: &[TodoList]) -> Vec<(&str, bool)> {
+        lists[0]
+            .items
+            .iter()
+            .map(|i| (i.description.as_str(), i.is_done))
+            .collect()
+    }
+
+    #[test]
+    fn empty_directory_has_no_lists() {
+        let dir = tempfile::tempdir().unwrap();
+        let (_, lists) = Store::open_in(dir.path().join("new"), false).unwrap();
+        assert!(lists.is_empty());
+        assert!(dir.path().join("new").is_dir());
+    }
+
+    #[test]
+    fn imports_a_plaintext_list_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let plaintext = dir.path().join(PLAINTEXT);
+        fs::write(
+            &plaintext,
+            "- Buy milk\n\n* [x] Call Bob\n[ ] Write report\n",
+        )
+        .unwrap();
+
+        let (_, lists) = Store::open_in(dir.path().to_path_buf(), false).unwrap();
+        assert_eq!(lists[0].name, "Tasks");
+        assert_eq!(
+            names(&lists),
+            [
+                ("Buy milk", false),
+                ("Call Bob", true),
+                ("Write report", false)
+            ]
+        );
+        assert!(!plaintext.exists());
+        assert!(dir.path().join("todos.txt.imported").exists());
+        assert!(dir.path().join("todos.json").exists());
+
+        // A plaintext list turning up again isn't imported over the store.
+        fs::write(&plaintext, "Something else\n").unwrap();
+        let (_, reopened) = Store::open_in(dir.path().to_path_buf(), false).unwrap();
+        assert_eq!(reopened, lists);
+        assert!(plaintext.exists());
+    }
+
+    #[test]
+    fn saves_reopen_from_the_same_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        let (mut store, mut lists) = Store::open_in(dir.path().to_path_buf(), false).unwrap();
+        lists.push(TodoList {
+            name: "Work".into(),
+            items: vec![TodoItem {
+                description: "Ship it".into(),
+                ..Default::default()
+            }],
+        });
+        assert!(store.save(&lists).unwrap());
+        assert!(!store.save(&lists).unwrap());
+
+        let (_, reopened) = Store::open_in(dir.path().to_path_buf(), false).unwrap();
+        assert_eq!(reopened, lists);
+    }
+
+    fn list(name: &str, tasks: &[&str]) -> TodoList {
+        TodoList {
+            name: name.into(),
+            items: tasks
+                .iter()
+                .map(|t| TodoItem {
+                    description: t.to_string(),
+                    ..Default::default()
+                })
+                .collect(),
+        }
+    }
+
+    fn log_lines(store: &Store) -> usize {
+        fs::read_to_string(store.log_path()).map_or(0, |log| log.lines().count())
+    }
+
+    fn append(store: &Store, text: &str) {
+        let mut log = OpenOptions::new()
+            .append(true)
+            .open(store.log_path())
+            .unwrap();
+        log.write_all(text.as_bytes()).unwrap();
+    }
+
+    #[test]
+    fn replays_puts_and_truncates_over_the_snapshot() {
+        let dir = tempfile::tempdir().unwrap();
+        let (mut store, _) = Store::open_in(dir.path().to_path_buf(), true).unwrap();
+        let mut lists = vec![list("Home", &["Milk"]), list("Work", &["Ship"])];
+        store.compact(&lists).unwrap();
+
+        lists[0].items[0].is_done = true;
+        lists.push(list("Trip", &["Pack"]));
+        assert!(store.save(&lists).unwrap());
+        // Only the lists that changed are written.
+        assert_eq!(log_lines(&store), 2);
+        lists.truncate(1);
+        assert!(store.save(&lists).unwrap());
+        assert_eq!(log_lines(&store), 3);
+
+        let (_, reopened) = Store::open_in(dir.path().to_path_buf(), true).unwrap();
+        assert_eq!(reopened, lists);
+    }
+
+    #[test]
+    fn a_torn_last_line_is_skipped_and_compacted_away() {
+        let dir = tempfile::tempdir().unwrap();
+        let (mut store, _) = Store::open_in(dir.path().to_path_buf(), true).unwrap();
+        let lists = vec![list("Home", &["Milk"])];
+        store.save(&lists).unwrap();
+        append(&store, r#"{"Put":[0,{"name":"Ho"#);
+
+        let (mut store, reopened) = Store::open_in(dir.path().to_path_buf(), true).unwrap();
+        assert_eq!(reopened, lists);
+        assert!(store.torn);
+
+        let lists = vec![list("Home", &["Milk", "Eggs"])];
+        store.save(&lists).unwrap();
+        assert!(!store.log_path().exists());
+        assert!(!store.db_path().with_extension("json.tmp").exists());
+        let (_, reopened) = Store::open_in(dir.path().to_path_buf(), true).unwrap();
+        assert_eq!(reopened, lists);
+    }
+
+    #[test]
+    fn a_torn_line_before_the_end_is_an_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let (mut store, _) = Store::open_in(dir.path().to_path_buf(), true).unwrap();
+        store.save(&[list("Home", &["Milk"])]).unwrap();
+        append(&store, "{\"Put\":[0\n{\"Truncate\":0}\n");
+
+        let error = Store::open_in(dir.path().to_path_buf(), true)
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("line 2"), "{}", error);
+    }
+
+    #[test]
+    fn compacts_once_the_log_is_full() {
+        let dir = tempfile::tempdir().unwrap();
+        let (mut store, _) = Store::open_in(dir.path().to_path_buf(), true).unwrap();
+        let mut lists = vec![list("Home", &["Milk"])];
+        for i in 0..COMPACT_AFTER {
+            lists[0].items[0].description = i.to_string();
+            store.save(&lists).unwrap();
+        }
+        assert_eq!(log_lines(&store), COMPACT_AFTER);
+        assert!(!store.db_path().exists());
+
+        lists[0].items[0].description = "last".into();
+        store.save(&lists).unwrap();
+        assert!(!store.log_path().exists());
+        let (_, reopened) = Store::open_in(dir.path().to_path_buf(), true).unwrap();
+        assert_eq!(reopened, lists);
+    }
+
+    #[test]
+    fn closing_folds_the_log_into_the_snapshot() {
+        let dir = tempfile::tempdir().unwrap();
+        let (mut store, _) = Store::open_in(dir.path().to_path_buf(), true).unwrap();
+        let lists = vec![list("Home", &["Milk"])];
+        store.save(&lists).unwrap();
+        assert!(store.log_path().exists());
+        store.close(&lists).unwrap();
+        assert!(!store.log_path().exists());
+        let (_, reopened) = Store::open_in(dir.path().to_path_buf(), false).unwrap();
+        assert_eq!(reopened, lists);
+    }
+}
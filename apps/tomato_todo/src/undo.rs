@@ -0,0 +1,122 @@
+use std::collections::VecDeque;
+
+/// Snapshots taken before each change, labelled with what the change was,
+/// so it can be undone and then redone. Only the last `limit` are kept.
+#[derive(Debug)]
+pub struct History<T> {
+    undo: VecDeque<(String, T)>,
+    redo: Vec<(String, T)>,
+    limit: usize,
+}
+
+impl<T> Default for History<T> {
+    fn default() -> Self {
+        Self::new(50)
+    }
+}
+
+impl<T> History<T> {
+    pub fn new(limit: usize) -> Self {
+        Self {
+            undo: VecDeque::new(),
+            redo: Vec::new(),
+            limit,
+        }
+    }
+
+    /// Remembers `before`, the state ahead of a change called `label`. A new
+    /// change makes whatever was undone before it unreachable.
+    pub fn record(&mut self, label: &str, before: T) {
+        self.redo.clear();
+        if self.undo.len() == self.limit {
+            self.undo.pop_front();
+        }
+        if self.limit > 0 {
+            self.undo.push_back((label.to_string(), before));
+        }
+    }
+
+    /// Steps `state` back over the last change and returns that change's
+    /// label, keeping the state it had for `redo`.
+    pub fn undo(&mut self, state: &mut T) -> Option<String> {
+        let (label, before) = self.undo.pop_back()?;
+        let after = std::mem::replace(state, before);
+        self.redo.push((label.clone(), after));
+        Some(label)
+    }
+
+    /// Reapplies the last undone change to `state` and returns its label.
+    pub fn redo(&mut self, state: &mut T) -> Option<String> {
+        let (label, after) = self.redo.pop()?;
+        let before = std::mem::replace(state, after);
+        self.undo.push_back((label.clone(), before));
+        Some(label)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Changes `state` to `to`, recording it as `label` first.
+    fn change(history: &mut History<i32>, state: &mut i32, label: &str, to: i32) {
+        history.record(label, *state);
+        *state = to;
+    }
+
+    #[test]
+    fn undo_and_redo_step_through_changes() {
+        let mut history = History::default();
+        let mut state = 0;
+        change(&mut history, &mut state, "one", 1);
+        change(&mut history, &mut state, "two", 2);
+
+        assert_eq!(history.undo(&mut state).as_deref(), Some("two"));
+        assert_eq!(state, 1);
+        assert_eq!(history.undo(&mut state).as_deref(), Some("one"));
+        assert_eq!(state, 0);
+        assert_eq!(history.undo(&mut state), None);
+        assert_eq!(state, 0);
+
+        assert_eq!(history.redo(&mut state).as_deref(), Some("one"));
+        assert_eq!(state, 1);
+        assert_eq!(history.redo(&mut state).as_deref(), Some("two"));
+        assert_eq!(state, 2);
+        assert_eq!(history.redo(&mut state), None);
+    }
+
+    #[test]
+    fn a_new_change_drops_the_redo_stack() {
+        let mut history = History::default();
+        let mut state = 0;
+        change(&mut history, &mut state, "one", 1);
+        history.undo(&mut state);
+        change(&mut history, &mut state, "other", 5);
+
+        assert_eq!(history.redo(&mut state), None);
+        assert_eq!(history.undo(&mut state).as_deref(), Some("other"));
+        assert_eq!(state, 0);
+    }
+
+    #[test]
+    fn only_the_last_limit_changes_are_kept() {
+        let mut history = History::new(2);
+        let mut state = 0;
+        for n in 1..=3 {
+            change(&mut history, &mut state, &n.to_string(), n);
+        }
+        assert_eq!(history.undo(&mut state).as_deref(), Some("3"));
+        assert_eq!(history.undo(&mut state).as_deref(), Some("2"));
+        assert_eq!(history.undo(&mut state), None);
+        assert_eq!(state, 1);
+    }
+
+    #[test]
+    fn limit_zero_keeps_nothing() {
+        let mut history = History::new(0);
+        let mut state = 0;
+        change(&mut history, &mut state, "one", 1);
+        assert_eq!(history.undo(&mut state), None);
+        assert_eq!(state, 1);
+    }
+}